@@ -4,10 +4,11 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::{fmt, str::FromStr};
 
+use crate::config::TargetConfig;
 use crate::sources::ToolType;
 use crate::utils;
 use crate::{
-    constants,
+    config, constants,
     files::{self, list_folders_in_path},
     sources::CompatTool,
 };
@@ -19,6 +20,8 @@ pub enum App {
     Lutris,
     /// Custom app used for user provided path
     Custom(String),
+    /// Named target registered in `config.toml`
+    Target(TargetConfig),
     // TODO:  HeroicGamesLauncher,
 }
 
@@ -27,10 +30,11 @@ pub static APP_VARIANTS: &[App] = &[App::Steam, App::Lutris];
 
 impl fmt::Display for App {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
+        match self {
             Self::Steam => write!(f, "Steam"),
             Self::Lutris => write!(f, "Lutris"),
             Self::Custom(_) => write!(f, "Custom"),
+            Self::Target(target) => write!(f, "{}", target.name),
         }
     }
 }
@@ -39,13 +43,17 @@ impl App {
     /// Parses a string into an App variant.
     /// - "steam" (case-insensitive) -> App::Steam
     /// - "lutris" (case-insensitive) -> App::Lutris
+    /// - The name of a target from `config.toml` -> App::Target(target)
     /// - Any other string -> App::Custom(path)
     pub fn from_str_or_path(s: &str) -> App {
         match s.to_lowercase().as_str() {
             "steam" => App::Steam,
             "lutris" => App::Lutris,
-            // default is a path. Should not do to_lowercase to the path
-            _ => App::Custom(s.to_string()),
+            _ => match config::current().targets.iter().find(|t| t.name == s) {
+                Some(target) => App::Target(target.clone()),
+                // default is a path. Should not do to_lowercase to the path
+                None => App::Custom(s.to_string()),
+            },
         }
     }
 }
@@ -59,13 +67,61 @@ impl FromStr for App {
 }
 
 impl App {
-    /// Returns the default compatibility tool for the App
+    /// Returns the default compatibility tool for the App,
+    /// the first one configured in `config.toml`
     pub fn default_compatibility_tool(&self) -> CompatTool {
-        match self {
+        // default_compatibility_tools always returns at least one tool
+        self.default_compatibility_tools().swap_remove(0)
+    }
+
+    /// Returns every default compatibility tool configured in `config.toml` for the App,
+    /// in order. Names that are not known, or tools that are not compatible with the App,
+    /// are skipped. Falls back to the built-in default if none is left
+    pub fn default_compatibility_tools(&self) -> Vec<CompatTool> {
+        let apps = &config::current().apps;
+        let (configured, fallback) = match self {
+            Self::Steam | Self::Custom(_) => (&apps.steam.tools, constants::DEFAULT_STEAM_TOOL),
+            Self::Lutris => (&apps.lutris.tools, constants::DEFAULT_LUTRIS_TOOL),
+            Self::Target(target) => {
+                let configured = if target.tools.is_empty() {
+                    &apps.steam.tools
+                } else {
+                    &target.tools
+                };
+                // the Steam default may not be compatible with the target
+                let fallback = match target.compatible_tools.first() {
+                    Some(first)
+                        if !target
+                            .compatible_tools
+                            .iter()
+                            .any(|tool| tool == constants::DEFAULT_STEAM_TOOL) =>
+                    {
+                        first.as_str()
+                    }
+                    _ => constants::DEFAULT_STEAM_TOOL,
+                };
+                (configured, fallback)
+            }
+        };
+        let tools: Vec<CompatTool> = configured
+            .iter()
+            .filter_map(|tool| CompatTool::from_str(tool).ok())
+            .filter(|tool| self.is_compatible(tool))
+            .collect();
+        if tools.is_empty() {
             // TODO: this could fail if the default apps change
-            Self::Steam => CompatTool::from_str(constants::DEFAULT_STEAM_TOOL).unwrap(),
-            Self::Lutris => CompatTool::from_str(constants::DEFAULT_LUTRIS_TOOL).unwrap(),
-            Self::Custom(_) => CompatTool::from_str(constants::DEFAULT_STEAM_TOOL).unwrap(),
+            return vec![CompatTool::from_str(fallback).unwrap()];
+        }
+        tools
+    }
+
+    /// Checks if the tool can be installed for the App.
+    /// Custom folders accept any tool, targets the ones in their `compatible_tools`
+    pub fn is_compatible(&self, compat_tool: &CompatTool) -> bool {
+        match self {
+            Self::Custom(_) => true,
+            Self::Target(target) => target.is_compatible(compat_tool),
+            _ => compat_tool.compatible_applications.contains(self),
         }
     }
 
@@ -75,6 +131,7 @@ impl App {
             Self::Steam => vec![AppInstallations::Steam, AppInstallations::SteamFlatpak],
             Self::Lutris => vec![AppInstallations::Lutris, AppInstallations::LutrisFlatpak],
             Self::Custom(path) => vec![AppInstallations::Custom(path.clone())],
+            Self::Target(target) => vec![AppInstallations::Target(target.clone())],
         }
     }
 
@@ -92,6 +149,9 @@ impl App {
             Self::Custom(path) => {
                 detect_installations(&[AppInstallations::Custom(path.clone())]).await
             }
+            Self::Target(target) => {
+                detect_installations(&[AppInstallations::Target(target.clone())]).await
+            }
         }
     }
 
@@ -100,6 +160,18 @@ impl App {
             App::Steam => None,
             App::Lutris => Some(vec!["runners/wine", "runtime"]),
             App::Custom(_) => None,
+            App::Target(target) => {
+                let mut sub_folders = vec![target.layout.wine_based.as_str()];
+                if target.layout.runtime != target.layout.wine_based {
+                    sub_folders.push(target.layout.runtime.as_str());
+                }
+                // a single empty subfolder is the target folder itself
+                if sub_folders == [""] {
+                    None
+                } else {
+                    Some(sub_folders)
+                }
+            }
         }
     }
 
@@ -113,11 +185,12 @@ impl App {
                 ToolType::Runtime => "runtime",
             },
             App::Custom(_) => "",
+            App::Target(target) => target.subfolder(&compat_tool.tool_type),
         }
     }
 }
 
-#[derive(Default, Debug, PartialEq, Eq, Clone)]
+#[derive(Default, Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 pub enum AppInstallations {
     #[default]
     Steam,
@@ -125,6 +198,7 @@ pub enum AppInstallations {
     Lutris,
     LutrisFlatpak,
     Custom(String),
+    Target(TargetConfig),
 }
 
 impl fmt::Display for AppInstallations {
@@ -135,6 +209,7 @@ impl fmt::Display for AppInstallations {
             Self::Lutris => write!(f, "Lutris \"Native\""),
             Self::LutrisFlatpak => write!(f, "Lutris Flatpak"),
             Self::Custom(path) => write!(f, "Custom: {path}"),
+            Self::Target(target) => write!(f, "{}: {}", target.name, target.path),
         }
     }
 }
@@ -164,6 +239,7 @@ impl AppInstallations {
             Self::Lutris => self.app_base_dir(),
             Self::LutrisFlatpak => self.app_base_dir(),
             Self::Custom(path) => arcstr::ArcStr::from(path),
+            Self::Target(target) => arcstr::ArcStr::from(&target.path),
         }
     }
 
@@ -177,20 +253,32 @@ impl AppInstallations {
             Self::Lutris => arcstr::literal!("~/.local/share/lutris/"),
             Self::LutrisFlatpak => arcstr::literal!("~/.var/app/net.lutris.Lutris/data/lutris/"),
             Self::Custom(path) => arcstr::ArcStr::from(path),
+            Self::Target(target) => arcstr::ArcStr::from(&target.path),
         }
     }
 
     /// Get a list of the currently installed wine versions
+    /// Subfolders that were not created yet (e.g. Lutris runtime) are skipped
     pub async fn list_installed_versions(&self) -> Result<Vec<files::Folder>, anyhow::Error> {
         let base_dir = self.default_install_dir().to_string();
         match self.as_app().list_subfolders() {
             Some(sub_folders) => {
                 let mut versions = Vec::new();
-                for sub_folder in sub_folders {
+                for sub_folder in &sub_folders {
                     let path = PathBuf::from(&base_dir).join(sub_folder);
+                    if !files::check_if_exists(&path).await {
+                        continue;
+                    }
                     let folders = list_folders_in_path(&path).await?;
                     let folders_with_path = folders
                         .into_iter()
+                        // a target layout can nest a subfolder in another one
+                        .filter(|folder| {
+                            let nested = PathBuf::from(sub_folder).join(folder);
+                            !sub_folders
+                                .iter()
+                                .any(|other| nested == PathBuf::from(other))
+                        })
                         .map(|folder| files::Folder((path.clone(), folder)))
                         .collect::<Vec<files::Folder>>();
                     versions.extend(folders_with_path);
@@ -214,13 +302,27 @@ impl AppInstallations {
             Self::Steam | Self::SteamFlatpak => App::Steam,
             Self::Lutris | Self::LutrisFlatpak => App::Lutris,
             Self::Custom(path) => App::Custom(path.to_owned()),
+            Self::Target(target) => App::Target(target.clone()),
         }
     }
 }
 
-/// list_installed_apps returns a vector of App variants that are installed
+/// list_installed_apps returns a vector of App variants that are installed,
+/// followed by the targets of `config.toml` whose folder exists
 pub async fn list_installed_apps() -> Vec<AppInstallations> {
-    detect_installations(APP_INSTALLATIONS_VARIANTS).await
+    let mut found = detect_installations(APP_INSTALLATIONS_VARIANTS).await;
+    found.extend(detect_installations(&configured_targets()).await);
+    found
+}
+
+/// configured_targets returns the targets registered in `config.toml`, found or not
+pub fn configured_targets() -> Vec<AppInstallations> {
+    config::current()
+        .targets
+        .iter()
+        .cloned()
+        .map(AppInstallations::Target)
+        .collect()
 }
 
 /// detect_installations returns a vector of App variants that are detected
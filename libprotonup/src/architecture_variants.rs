@@ -1,214 +1,309 @@
-//! Architecture variant detection for Proton CachyOS and similar tools.
+//! Asset variant selection for Proton CachyOS and similar tools.
 //!
-//! This module provides functions to detect, extract, and select CPU architecture
-//! variants from download files (e.g., x86_64, x86_64_v2, x86_64_v3, x86_64_v4).
+//! Tools declare the builds published in each release in `sources.ron`, see
+//! [`AssetVariant`]. This module finds the variant of each release asset, keeps the ones
+//! the [`Host`] can run, and selects the default one.
 
+use std::fmt;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::config;
+use crate::cpu;
 use crate::downloads::Download;
+use crate::sources::{AssetVariant, CompatTool};
+
+static SYSTEM_GLIBC: OnceLock<Option<String>> = OnceLock::new();
 
-/// Architecture variant for Proton CachyOS and similar tools.
+/// Asset variant of a release, for Proton CachyOS and similar tools.
 ///
 /// Contains the variant name, description, and download information.
 #[derive(Debug, Clone)]
 pub struct ArchitectureVariant {
-    /// The architecture variant name (x86_64, x86_64_v2, x86_64_v3, x86_64_v4)
+    /// The variant name, like x86_64_v3
     pub name: String,
     /// Extended description of this variant
     pub description: String,
+    /// The default variant is the supported one with the highest priority
+    pub priority: i32,
     /// The download information for this variant
     pub download: Download,
 }
 
+impl ArchitectureVariant {
+    fn new(variant: &AssetVariant, download: &Download) -> ArchitectureVariant {
+        ArchitectureVariant {
+            name: variant.name.clone(),
+            description: variant.description.clone(),
+            priority: variant.priority,
+            download: download.clone(),
+        }
+    }
+}
+
 impl std::fmt::Display for ArchitectureVariant {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{} - {}", self.name, self.description)
     }
 }
 
-/// Extracts the architecture variant code from a file name.
-///
-/// Returns:
-/// - `1`: x86_64 (universal)
-/// - `2`: x86_64_v2
-/// - `3`: x86_64_v3
-/// - `4`: x86_64_v4
-/// - `0`: unknown/not a recognized variant
-pub fn get_architecture_variant(file_name: &str) -> u8 {
-    if file_name.contains("_v4") {
-        4
-    } else if file_name.contains("_v3") {
-        3
-    } else if file_name.contains("_v2") {
-        2
-    } else if file_name.contains("-x86_64.") {
-        1
-    } else {
-        0
-    }
+/// The system the variants must run on, checked against their
+/// [`VariantRequirements`](crate::sources::VariantRequirements)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host {
+    /// CPU architecture, as in `std::env::consts::ARCH`
+    pub arch: String,
+    /// x86-64 level of the CPU, see [`cpu::x86_64_level`]
+    pub cpu_level: Option<u8>,
+    /// Version of the system glibc, None on systems using another C library
+    pub glibc: Option<String>,
 }
 
-/// Gets the variant name string from the variant code.
-pub fn get_variant_name(variant_code: u8) -> &'static str {
-    match variant_code {
-        1 => "x86_64",
-        2 => "x86_64_v2",
-        3 => "x86_64_v3",
-        4 => "x86_64_v4",
-        _ => "unknown",
+impl Host {
+    /// The running system. The `arch` and `cpu_level` settings replace the detected ones,
+    /// and the level of the CPU is not used when installing for another architecture
+    pub fn current() -> Host {
+        let detected_level = match cpu::is_cross_install() {
+            true => None,
+            false => cpu::x86_64_level(),
+        };
+        Host {
+            arch: cpu::target_arch(),
+            cpu_level: config::current().cpu_level.or(detected_level),
+            glibc: SYSTEM_GLIBC.get_or_init(system_glibc).clone(),
+        }
     }
 }
 
-/// Gets an extended description for an architecture variant.
-pub fn get_architecture_description(variant_code: u8) -> &'static str {
-    match variant_code {
-        4 => "Experimental - optimized for AVX-512",
-        3 => "Modern CPUs - optimized for AVX2",
-        2 => "Recommended - optimized for SSE3",
-        1 => "Universal - all x86-64 CPUs",
-        _ => "Unknown",
+impl fmt::Display for Host {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut details = vec![];
+        if let Some(level) = self.cpu_level {
+            details.push(format!("x86-64-v{level}"));
+        }
+        if let Some(glibc) = &self.glibc {
+            details.push(format!("glibc {glibc}"));
+        }
+        match details.is_empty() {
+            true => write!(f, "{}", self.arch),
+            false => write!(f, "{} ({})", self.arch, details.join(", ")),
+        }
     }
 }
 
-/// Extracts architecture variants from a list of downloads.
-///
-/// Returns a sorted Vec<ArchitectureVariant> sorted by variant priority
-/// (x86_64 < x86_64_v2 < x86_64_v3 < x86_64_v4).
-pub fn extract_variants(downloads: &[Download]) -> Vec<ArchitectureVariant> {
-    let mut variants: Vec<ArchitectureVariant> = downloads
-        .iter()
-        .filter_map(|download| {
-            let variant_code = get_architecture_variant(&download.file_name);
-            if variant_code == 0 {
-                return None;
-            }
-            Some(ArchitectureVariant {
-                name: get_variant_name(variant_code).to_string(),
-                description: get_architecture_description(variant_code).to_string(),
-                download: download.clone(),
-            })
-        })
-        .collect();
-
-    // Sort by variant priority (x86_64 < v2 < v3 < v4)
-    variants.sort_by_key(|v| match v.name.as_str() {
-        "x86_64" => 1,
-        "x86_64_v2" => 2,
-        "x86_64_v3" => 3,
-        "x86_64_v4" => 4,
-        _ => 99,
-    });
+/// Version of the glibc the tools are run with. Queried with getconf rather than from this
+/// process, which can be linked with another C library
+fn system_glibc() -> Option<String> {
+    let output = Command::new("getconf")
+        .arg("GNU_LIBC_VERSION")
+        .output()
+        .ok()?;
+    let output = String::from_utf8(output.stdout).ok()?;
+    output
+        .trim()
+        .strip_prefix("glibc ")
+        .map(|version| version.to_owned())
+}
 
+/// The declared variant of each download matching one
+fn variants_of<'a>(
+    compat_tool: &'a CompatTool,
+    downloads: &'a [Download],
+) -> impl Iterator<Item = (&'a AssetVariant, &'a Download)> {
+    downloads.iter().filter_map(|download| {
+        compat_tool
+            .variant_of(&download.file_name)
+            .map(|variant| (variant, download))
+    })
+}
+
+fn sorted(variants: impl Iterator<Item = ArchitectureVariant>) -> Vec<ArchitectureVariant> {
+    let mut variants: Vec<ArchitectureVariant> = variants.collect();
+    variants.sort_by_key(|variant| variant.priority);
     variants
 }
 
+/// Extracts the variants of `compat_tool` from a list of downloads.
+///
+/// Downloads matching none of the declared variants are left out.
+/// The variants are sorted by priority, lowest first.
+pub fn extract_variants(
+    compat_tool: &CompatTool,
+    downloads: &[Download],
+) -> Vec<ArchitectureVariant> {
+    sorted(
+        variants_of(compat_tool, downloads)
+            .map(|(variant, download)| ArchitectureVariant::new(variant, download)),
+    )
+}
+
+/// Like [`extract_variants`], without the variants `host` can't run
+pub fn supported_variants(
+    compat_tool: &CompatTool,
+    downloads: &[Download],
+    host: &Host,
+) -> Vec<ArchitectureVariant> {
+    sorted(
+        variants_of(compat_tool, downloads)
+            .filter(|(variant, _)| variant.requires.is_met_by(host))
+            .map(|(variant, download)| ArchitectureVariant::new(variant, download)),
+    )
+}
+
 /// Selects the default variant for quick mode.
 ///
-/// Prefers `_v2` variant if available, otherwise falls back to the first available variant.
-/// Returns `None` if no variants are available.
-pub fn select_default_variant(downloads: &[Download]) -> Option<Download> {
-    downloads
-        .iter()
-        .find(|d| d.file_name.contains("_v2"))
-        .or_else(|| downloads.first())
-        .cloned()
+/// Prefers the `default_variant` from the configuration, then the variant with the highest
+/// priority the host can run.
+/// Returns `None` if no variant can run on the host.
+pub fn select_default_variant(
+    compat_tool: &CompatTool,
+    downloads: &[Download],
+) -> Option<Download> {
+    select_variant(
+        supported_variants(compat_tool, downloads, &Host::current()),
+        config::current().default_variant.as_deref(),
+    )
+}
+
+/// The `preferred` variant among `variants`, or the one with the highest priority
+pub fn select_variant(
+    variants: Vec<ArchitectureVariant>,
+    preferred: Option<&str>,
+) -> Option<Download> {
+    let preferred = preferred.and_then(|name| variants.iter().find(|v| v.name == name));
+    preferred
+        .or_else(|| variants.iter().max_by_key(|v| v.priority))
+        .map(|variant| variant.download.clone())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sources::{UnmetRequirement, VariantRequirements};
+    use std::str::FromStr;
 
-    #[test]
-    fn test_get_architecture_variant() {
-        assert_eq!(get_architecture_variant("proton-cachyos-x86_64.tar.gz"), 1);
-        assert_eq!(
-            get_architecture_variant("proton-cachyos-x86_64_v2.tar.gz"),
-            2
-        );
-        assert_eq!(
-            get_architecture_variant("proton-cachyos-x86_64_v3.tar.gz"),
-            3
-        );
-        assert_eq!(
-            get_architecture_variant("proton-cachyos-x86_64_v4.tar.gz"),
-            4
-        );
-        assert_eq!(get_architecture_variant("some-other-file.tar.gz"), 0);
+    fn cachyos() -> CompatTool {
+        CompatTool::from_str("Proton CachyOS").unwrap()
     }
 
-    #[test]
-    fn test_get_variant_name() {
-        assert_eq!(get_variant_name(1), "x86_64");
-        assert_eq!(get_variant_name(2), "x86_64_v2");
-        assert_eq!(get_variant_name(3), "x86_64_v3");
-        assert_eq!(get_variant_name(4), "x86_64_v4");
-        assert_eq!(get_variant_name(0), "unknown");
+    fn host(arch: &str, cpu_level: Option<u8>, glibc: Option<&str>) -> Host {
+        Host {
+            arch: arch.to_owned(),
+            cpu_level,
+            glibc: glibc.map(str::to_owned),
+        }
     }
 
-    #[test]
-    fn test_get_architecture_description() {
-        assert!(get_architecture_description(1).contains("Universal"));
-        assert!(get_architecture_description(2).contains("SSE3"));
-        assert!(get_architecture_description(3).contains("AVX2"));
-        assert!(get_architecture_description(4).contains("AVX-512"));
+    fn names(variants: &[ArchitectureVariant]) -> Vec<&str> {
+        variants.iter().map(|v| v.name.as_str()).collect()
     }
 
-    #[test]
-    fn test_extract_variants_sorts_correctly() {
-        let downloads = vec![
-            create_mock_download("proton-x86_64_v3.tar.gz"),
-            create_mock_download("proton-x86_64.tar.gz"),
-            create_mock_download("proton-x86_64_v4.tar.gz"),
-            create_mock_download("proton-x86_64_v2.tar.gz"),
-        ];
-
-        let variants = extract_variants(&downloads);
+    fn cachyos_downloads() -> Vec<Download> {
+        vec![
+            create_mock_download("proton-cachyos-10.0-20250714-slr-x86_64_v3.tar.xz"),
+            create_mock_download("proton-cachyos-10.0-20250714-slr-x86_64.tar.xz"),
+            create_mock_download("proton-cachyos-10.0-20250714-slr-x86_64_v4.tar.xz"),
+            create_mock_download("proton-cachyos-10.0-20250714-slr-x86_64_v2.tar.xz"),
+        ]
+    }
 
-        assert_eq!(variants.len(), 4);
-        assert_eq!(variants[0].name, "x86_64");
-        assert_eq!(variants[1].name, "x86_64_v2");
-        assert_eq!(variants[2].name, "x86_64_v3");
-        assert_eq!(variants[3].name, "x86_64_v4");
+    #[test]
+    fn test_extract_variants_sorts_by_priority() {
+        let variants = extract_variants(&cachyos(), &cachyos_downloads());
+        assert_eq!(
+            names(&variants),
+            vec!["x86_64", "x86_64_v2", "x86_64_v3", "x86_64_v4"]
+        );
+        assert!(variants[2].download.file_name.ends_with("_v3.tar.xz"));
+        assert!(variants[3].description.contains("AVX-512"));
     }
 
     #[test]
     fn test_extract_variants_filters_unknown() {
         let downloads = vec![
-            create_mock_download("proton-x86_64_v2.tar.gz"),
+            create_mock_download("proton-cachyos-10.0-20250714-slr-x86_64_v2.tar.xz"),
             create_mock_download("some-other-file.tar.gz"),
         ];
 
-        let variants = extract_variants(&downloads);
+        let variants = extract_variants(&cachyos(), &downloads);
+        assert_eq!(names(&variants), vec!["x86_64_v2"]);
+    }
+
+    #[test]
+    fn test_supported_variants_never_above_host_level() {
+        let downloads = cachyos_downloads();
+        let supported = |host: Host| {
+            names(&supported_variants(&cachyos(), &downloads, &host))
+                .into_iter()
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
 
-        assert_eq!(variants.len(), 1);
-        assert_eq!(variants[0].name, "x86_64_v2");
+        assert_eq!(
+            supported(host("x86_64", Some(2), None)),
+            vec!["x86_64", "x86_64_v2"]
+        );
+        assert_eq!(supported(host("x86_64", Some(4), None)).len(), 4);
+        assert_eq!(supported(host("x86_64", None, None)), vec!["x86_64"]);
+        assert!(supported(host("aarch64", None, Some("2.39"))).is_empty());
     }
 
     #[test]
-    fn test_select_default_variant_prefers_v2() {
-        let downloads = vec![
-            create_mock_download("proton-x86_64.tar.gz"),
-            create_mock_download("proton-x86_64_v2.tar.gz"),
-            create_mock_download("proton-x86_64_v3.tar.gz"),
-        ];
+    fn test_variant_requirements() {
+        let requires = VariantRequirements {
+            arch: Some("x86_64".to_owned()),
+            cpu_level: Some(3),
+            glibc: Some("2.35".to_owned()),
+        };
+        assert!(requires.is_met_by(&host("x86_64", Some(3), Some("2.35"))));
+        assert!(requires.is_met_by(&host("x86_64", Some(4), Some("2.39"))));
+        assert!(!requires.is_met_by(&host("x86_64", Some(4), Some("2.31"))));
+        assert!(!requires.is_met_by(&host("x86_64", Some(4), None)));
+        assert!(!requires.is_met_by(&host("x86_64", Some(2), Some("2.39"))));
+        assert!(!requires.is_met_by(&host("aarch64", Some(4), Some("2.39"))));
+        assert!(VariantRequirements::default().is_met_by(&host("riscv64", None, None)));
 
-        let default = select_default_variant(&downloads).unwrap();
-        assert!(default.file_name.contains("_v2"));
+        assert_eq!(
+            requires.unmet_by(&host("x86_64", Some(2), Some("2.39"))),
+            vec![UnmetRequirement::CpuLevel(3)]
+        );
+        assert_eq!(
+            requires.unmet_by(&host("aarch64", None, Some("2.31"))),
+            vec![
+                UnmetRequirement::Arch("x86_64".to_owned()),
+                UnmetRequirement::CpuLevel(3),
+                UnmetRequirement::Glibc("2.35".to_owned()),
+            ]
+        );
+        assert_eq!(UnmetRequirement::CpuLevel(3).to_string(), "cpu_level 3");
     }
 
     #[test]
-    fn test_select_default_variant_falls_back_to_first() {
-        let downloads = vec![
-            create_mock_download("proton-x86_64_v3.tar.gz"),
-            create_mock_download("proton-x86_64_v4.tar.gz"),
-        ];
+    fn test_select_variant() {
+        let downloads = cachyos_downloads();
+        let supported =
+            |level| supported_variants(&cachyos(), &downloads, &host("x86_64", Some(level), None));
 
-        let default = select_default_variant(&downloads).unwrap();
-        assert!(default.file_name.contains("x86_64_v3"));
+        let selected = select_variant(supported(4), None).unwrap();
+        assert!(selected.file_name.ends_with("_v4.tar.xz"));
+        let selected = select_variant(supported(3), None).unwrap();
+        assert!(selected.file_name.ends_with("_v3.tar.xz"));
+        // the configured variant
+        let selected = select_variant(supported(4), Some("x86_64_v2")).unwrap();
+        assert!(selected.file_name.ends_with("_v2.tar.xz"));
+        // unless the host can't run it
+        let selected = select_variant(supported(2), Some("x86_64_v3")).unwrap();
+        assert!(selected.file_name.ends_with("_v2.tar.xz"));
+        assert!(select_variant(vec![], None).is_none());
     }
 
     #[test]
-    fn test_select_default_variant_returns_none_for_empty() {
-        let downloads: Vec<Download> = vec![];
-        assert!(select_default_variant(&downloads).is_none());
+    fn test_host_display() {
+        assert_eq!(
+            host("x86_64", Some(3), Some("2.39")).to_string(),
+            "x86_64 (x86-64-v3, glibc 2.39)"
+        );
+        assert_eq!(host("aarch64", None, None).to_string(), "aarch64");
     }
 
     fn create_mock_download(file_name: &str) -> Download {
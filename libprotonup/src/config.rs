@@ -0,0 +1,559 @@
+//! User configuration, read from `~/.config/protonup-rs/config.toml`.
+//!
+//! Every field is optional in the file, missing ones take the default value.
+//! Programs load the file once with [`Config::load`] and register it with [`init`],
+//! the library then reads it with [`current`].
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::apps::App;
+use crate::constants;
+use crate::cpu;
+use crate::sources::{CompatTool, ToolType};
+use crate::utils;
+
+static CURRENT: OnceLock<Config> = OnceLock::new();
+
+/// Settings of protonup-rs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Compatibility tools kept up to date by quick update, per app.
+    /// Every listed tool is installed
+    pub apps: AppsConfig,
+    /// Variant installed by default for tools with multiple architecture variants,
+    /// like Proton CachyOS. Example "x86_64_v3"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_variant: Option<String>,
+    /// x86-64 level (1 to 4) used instead of the one of the host CPU to choose the variants,
+    /// for example to install a build for another machine
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_level: Option<u8>,
+    /// Architecture the tools are installed for instead of the one of the host,
+    /// to install onto the disk of another machine. Example "aarch64"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// Named custom installation targets
+    pub targets: Vec<TargetConfig>,
+    /// Number of versions of each tool kept by quick update, older ones are removed.
+    /// Every version is kept if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention: Option<usize>,
+    /// Cache of the release lists fetched from the forges
+    pub cache: CacheConfig,
+    /// Maximum number of downloads running at the same time
+    pub concurrency: usize,
+    /// Unpack the archives while they are downloaded, without writing them to a temporary file
+    pub stream_install: bool,
+    /// What to do with the assets of releases that publish no checksum and are not signed
+    pub checksum_policy: ChecksumPolicy,
+    /// Token sent to the GitHub API, raising its rate limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+    /// Install the releases marked as pre-releases as the latest version of every tool
+    pub include_prereleases: bool,
+    /// Settings of each compatibility tool, by name as in `sources.ron`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, ToolConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            apps: AppsConfig::default(),
+            default_variant: None,
+            cpu_level: None,
+            arch: None,
+            targets: vec![],
+            retention: None,
+            cache: CacheConfig::default(),
+            concurrency: 4,
+            stream_install: false,
+            checksum_policy: ChecksumPolicy::default(),
+            github_token: None,
+            include_prereleases: false,
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+/// Handling of release assets that can't be verified against a published checksum or signature.
+///
+/// Their digest is pinned in the install manifest the first time they are downloaded,
+/// and a later download with another digest is refused whatever the policy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumPolicy {
+    /// Refuse to install them
+    Require,
+    /// Install them with a warning
+    #[default]
+    Warn,
+    /// Install them silently
+    Allow,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppsConfig {
+    pub steam: AppConfig,
+    pub lutris: AppConfig,
+}
+
+impl Default for AppsConfig {
+    fn default() -> Self {
+        AppsConfig {
+            steam: AppConfig {
+                tools: vec![constants::DEFAULT_STEAM_TOOL.to_owned()],
+            },
+            lutris: AppConfig {
+                tools: vec![constants::DEFAULT_LUTRIS_TOOL.to_owned()],
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    /// Names of the default compatibility tools, as in `sources.ron`
+    pub tools: Vec<String>,
+}
+
+/// A custom installation folder registered with a name.
+/// Used like an app by quick update, the manage menu and `--for <name>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TargetConfig {
+    pub name: String,
+    pub path: String,
+    /// Subfolders of `path` each kind of tool is installed into
+    #[serde(default)]
+    pub layout: TargetLayout,
+    /// Tools installed by quick update. Defaults to `apps.steam.tools`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    /// Tools that can be installed in the target. Every tool if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compatible_tools: Vec<String>,
+}
+
+/// Subfolders of a target, relative to its path. Empty installs in the target folder
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TargetLayout {
+    pub wine_based: String,
+    pub runtime: String,
+}
+
+impl TargetConfig {
+    pub fn new(name: String, path: String) -> TargetConfig {
+        TargetConfig {
+            name,
+            path,
+            layout: TargetLayout::default(),
+            tools: vec![],
+            compatible_tools: vec![],
+        }
+    }
+
+    pub fn is_compatible(&self, compat_tool: &CompatTool) -> bool {
+        self.compatible_tools.is_empty() || self.compatible_tools.contains(&compat_tool.name)
+    }
+
+    /// Subfolder the tool is installed into, as set in the layout
+    pub fn subfolder(&self, tool_type: &ToolType) -> &str {
+        match tool_type {
+            ToolType::WineBased => &self.layout.wine_based,
+            ToolType::Runtime => &self.layout.runtime,
+        }
+    }
+}
+
+/// Settings of a compatibility tool
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolConfig {
+    /// Install the releases marked as pre-releases as the latest version of the tool
+    pub include_prereleases: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// Time after which a cached release list is fetched again
+    pub ttl_seconds: u64,
+    /// Keep the downloaded archives, to reinstall or repair without downloading again
+    pub keep_archives: bool,
+    /// Cache folder. Defaults to `~/.cache/protonup-rs`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            enabled: false,
+            ttl_seconds: 3600,
+            keep_archives: false,
+            dir: None,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// The configured cache folder, or the user cache folder
+    pub fn dir(&self) -> Option<PathBuf> {
+        match &self.dir {
+            Some(dir) => utils::expand_tilde(dir),
+            None => dirs::cache_dir().map(|dir| dir.join(constants::CONFIG_DIR_NAME)),
+        }
+    }
+
+    /// Folder of the kept archives, if enabled
+    pub fn archive_dir(&self) -> Option<PathBuf> {
+        if !self.keep_archives {
+            return None;
+        }
+        self.dir().map(|dir| dir.join("archives"))
+    }
+}
+
+/// Registers the configuration used by the library. Only the first call has an effect
+pub fn init(config: Config) {
+    let _ = CURRENT.set(config);
+}
+
+/// The configuration registered with [`init`], or the default one
+pub fn current() -> &'static Config {
+    CURRENT.get_or_init(Config::default)
+}
+
+impl Config {
+    /// Location of the configuration file, `$XDG_CONFIG_HOME/protonup-rs/config.toml`
+    pub fn path() -> Result<PathBuf> {
+        dirs::config_dir()
+            .map(|dir| {
+                dir.join(constants::CONFIG_DIR_NAME)
+                    .join(constants::CONFIG_FILE_NAME)
+            })
+            .ok_or_else(|| anyhow!("Could not find the user configuration folder"))
+    }
+
+    /// Loads the configuration file, returning the defaults if it does not exist
+    pub fn load() -> Result<Config> {
+        Config::load_from(&Config::path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Config> {
+        match fs::read_to_string(path) {
+            Ok(content) => Config::parse(&content)
+                .map_err(|e| anyhow!("Invalid configuration file {}: {e:#}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e)
+                .with_context(|| format!("Failed to read configuration file {}", path.display())),
+        }
+    }
+
+    /// Parses and validates the content of a configuration file
+    pub fn parse(content: &str) -> Result<Config> {
+        let config: Config = toml::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the values that can't be checked by their type
+    pub fn validate(&self) -> Result<()> {
+        for (app, tools) in [
+            (App::Steam, &self.apps.steam.tools),
+            (App::Lutris, &self.apps.lutris.tools),
+        ] {
+            for tool in tools {
+                let compat_tool = CompatTool::from_str(tool)
+                    .map_err(|_| anyhow!("Unknown compatibility tool: '{tool}'"))?;
+                if !compat_tool.compatible_applications.contains(&app) {
+                    bail!("'{tool}' is not compatible with {app}");
+                }
+            }
+        }
+        if self.concurrency == 0 {
+            bail!("concurrency must be at least 1");
+        }
+        if self.retention == Some(0) {
+            bail!("retention must be at least 1");
+        }
+        if self
+            .cpu_level
+            .is_some_and(|level| !(1..=4).contains(&level))
+        {
+            bail!("cpu_level must be between 1 and 4");
+        }
+        if let Some(arch) = &self.arch
+            && !cpu::ARCHITECTURES.contains(&arch.as_str())
+        {
+            bail!(
+                "Unknown arch: '{arch}'. Supported architectures: {}",
+                cpu::ARCHITECTURES.join(", ")
+            );
+        }
+        for tool in self.tools.keys() {
+            CompatTool::from_str(tool)
+                .map_err(|_| anyhow!("Unknown compatibility tool: '{tool}'"))?;
+        }
+        for (i, target) in self.targets.iter().enumerate() {
+            if self.targets[..i].iter().any(|t| t.name == target.name) {
+                bail!("Duplicated target name: '{}'", target.name);
+            }
+            target.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Whether the pre-releases of `compat_tool` are installed as its latest version,
+    /// with `include_prereleases` or the setting of the tool
+    pub fn includes_prereleases(&self, compat_tool: &CompatTool) -> bool {
+        self.include_prereleases
+            || self.tools.iter().any(|(name, tool)| {
+                name.eq_ignore_ascii_case(&compat_tool.name) && tool.include_prereleases
+            })
+    }
+
+    /// Returns the value of a dotted key, like "apps.steam.tools".
+    /// Returns None for optional keys that are not set
+    pub fn get(&self, key: &str) -> Result<Option<toml::Value>> {
+        let value = toml::Value::try_from(self)?;
+        let mut current = &value;
+        for part in key.split('.') {
+            match current.get(part) {
+                Some(value) => current = value,
+                None if is_optional_key(key) => return Ok(None),
+                None => bail!("Unknown configuration key: '{key}'"),
+            }
+        }
+        Ok(Some(current.clone()))
+    }
+
+    /// Sets a dotted key in the configuration file and returns the new configuration.
+    ///
+    /// `value` is parsed as a TOML value (`4`, `true`, `["GEProton", "Luxtorpeda"]`),
+    /// and is used as a string otherwise.
+    /// Only the keys present in the file are written back, comments are not preserved.
+    pub fn set(path: &Path, key: &str, value: &str) -> Result<Config> {
+        let mut table: toml::Table = match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("Invalid configuration file {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml::Table::new(),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read configuration file {}", path.display())
+                });
+            }
+        };
+
+        let value = parse_value(value);
+        let (parents, last) = match key.rsplit_once('.') {
+            Some((parents, last)) => (parents.split('.').collect::<Vec<_>>(), last),
+            None => (vec![], key),
+        };
+        let mut current = &mut table;
+        for part in parents {
+            current = current
+                .entry(part)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| anyhow!("Configuration key '{part}' is not a table"))?;
+        }
+        current.insert(last.to_owned(), value);
+
+        let content = toml::to_string_pretty(&table)?;
+        let config =
+            Config::parse(&content).map_err(|e| anyhow!("Invalid value for '{key}': {e:#}"))?;
+        config.save_raw(path, &content)?;
+        Ok(config)
+    }
+
+    /// Writes the configuration file with every value, including the defaults
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.save_raw(path, &toml::to_string_pretty(self)?)
+    }
+
+    fn save_raw(&self, path: &Path, content: &str) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write configuration file {}", path.display()))
+    }
+}
+
+impl TargetConfig {
+    fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            bail!("Target names can't be empty");
+        }
+        // --for accepts both app and target names
+        if matches!(self.name.to_lowercase().as_str(), "steam" | "lutris") {
+            bail!(
+                "'{}' is reserved and can't be used as a target name",
+                self.name
+            );
+        }
+        for tool in self.tools.iter().chain(&self.compatible_tools) {
+            if CompatTool::from_str(tool).is_err() {
+                bail!(
+                    "Unknown compatibility tool in target '{}': '{tool}'",
+                    self.name
+                );
+            }
+        }
+        for tool in &self.tools {
+            if !self.compatible_tools.is_empty() && !self.compatible_tools.contains(tool) {
+                bail!(
+                    "'{tool}' is not in the compatible tools of target '{}'",
+                    self.name
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Keys that can be absent from the configuration
+fn is_optional_key(key: &str) -> bool {
+    matches!(
+        key,
+        "default_variant" | "cpu_level" | "arch" | "retention" | "github_token" | "cache.dir"
+    ) || key.starts_with("tools.")
+}
+
+fn parse_value(value: &str) -> toml::Value {
+    match toml::from_str::<toml::Table>(&format!("value = {value}")) {
+        Ok(mut table) => table.remove("value").unwrap(),
+        Err(_) => toml::Value::String(value.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            r#"
+            concurrency = 2
+            default_variant = "x86_64_v3"
+
+            [apps.steam]
+            tools = ["GEProton", "Luxtorpeda"]
+
+            [[targets]]
+            name = "heroic"
+            path = "~/Games/Heroic/tools/proton"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.concurrency, 2);
+        assert_eq!(config.default_variant.as_deref(), Some("x86_64_v3"));
+        assert_eq!(config.apps.steam.tools, vec!["GEProton", "Luxtorpeda"]);
+        // untouched sections keep their defaults
+        assert_eq!(config.apps.lutris, AppsConfig::default().lutris);
+        assert_eq!(config.targets[0].name, "heroic");
+        assert_eq!(config.targets[0].layout, TargetLayout::default());
+
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert!(Config::parse("unknown_key = 1").is_err());
+        assert!(Config::parse("[apps.steam]\ntools = [\"NotATool\"]").is_err());
+        assert!(Config::parse("[apps.lutris]\ntools = [\"Luxtorpeda\"]").is_err());
+        assert!(Config::parse("concurrency = 0").is_err());
+        assert!(Config::parse("cpu_level = 5").is_err());
+        assert!(Config::parse("arch = \"aarch64\"").is_ok());
+        assert!(Config::parse("arch = \"arm64\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_targets() {
+        let config = Config::parse(
+            r#"
+            [[targets]]
+            name = "bottles"
+            path = "~/.local/share/bottles"
+            tools = ["GEProton"]
+            compatible_tools = ["GEProton", "DXVK"]
+
+            [targets.layout]
+            wine_based = "runners"
+            runtime = "dxvk"
+            "#,
+        )
+        .unwrap();
+
+        let target = &config.targets[0];
+        assert_eq!(target.subfolder(&ToolType::WineBased), "runners");
+        assert_eq!(target.subfolder(&ToolType::Runtime), "dxvk");
+        assert!(target.is_compatible(&CompatTool::from_str("DXVK").unwrap()));
+        assert!(!target.is_compatible(&CompatTool::from_str("Luxtorpeda").unwrap()));
+
+        let invalid = |target: &str| Config::parse(&format!("[[targets]]\n{target}")).is_err();
+        assert!(invalid("name = \"steam\"\npath = \"/tmp\""));
+        assert!(invalid("name = \"\"\npath = \"/tmp\""));
+        assert!(invalid(
+            "name = \"a\"\npath = \"/tmp\"\ntools = [\"Luxtorpeda\"]\ncompatible_tools = [\"DXVK\"]"
+        ));
+    }
+
+    #[test]
+    fn test_get_and_set() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("protonup-rs/config.toml");
+
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(
+            config.get("concurrency").unwrap(),
+            Some(toml::Value::Integer(4))
+        );
+        assert_eq!(config.get("retention").unwrap(), None);
+        assert!(config.get("not.a.key").is_err());
+
+        Config::set(&path, "apps.steam.tools", r#"["GEProton", "Luxtorpeda"]"#).unwrap();
+        Config::set(&path, "default_variant", "x86_64_v3").unwrap();
+        Config::set(&path, "checksum_policy", "require").unwrap();
+        let config = Config::set(&path, "cache.enabled", "true").unwrap();
+        assert_eq!(config.apps.steam.tools, vec!["GEProton", "Luxtorpeda"]);
+        assert_eq!(config.checksum_policy, ChecksumPolicy::Require);
+        assert_eq!(config.default_variant.as_deref(), Some("x86_64_v3"));
+        assert!(config.cache.enabled);
+        assert_eq!(Config::load_from(&path).unwrap(), config);
+
+        let tool = |name: &str| CompatTool::from_str(name).unwrap();
+        assert!(!config.includes_prereleases(&tool("Proton CachyOS")));
+        assert_eq!(
+            config
+                .get("tools.Proton CachyOS.include_prereleases")
+                .unwrap(),
+            None
+        );
+        let config =
+            Config::set(&path, "tools.Proton CachyOS.include_prereleases", "true").unwrap();
+        assert!(config.includes_prereleases(&tool("Proton CachyOS")));
+        assert!(!config.includes_prereleases(&tool("GEProton")));
+
+        // invalid values are rejected and the file is kept
+        assert!(Config::set(&path, "concurrency", "0").is_err());
+        assert!(Config::set(&path, "unknown", "1").is_err());
+        assert!(Config::set(&path, "checksum_policy", "never").is_err());
+        assert!(Config::set(&path, "tools.NotATool.include_prereleases", "true").is_err());
+        assert_eq!(Config::load_from(&path).unwrap(), config);
+    }
+}
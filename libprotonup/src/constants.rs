@@ -7,7 +7,17 @@ pub const USER_AGENT: &str = "protoup-rs";
 
 pub const MIN_TEMP_SPACE_BYTES: u64 = 1_073_741_824; // 1GB
 pub const FALLBACK_TEMP_DIR: &str = ".local/state/protonup-rs/tmp";
+/// Prefix of the temporary folders the downloads are written into
+pub const TEMP_DIR_PREFIX: &str = "protonup-rs-";
+
+/// Folder inside the user config and cache folders
+pub const CONFIG_DIR_NAME: &str = "protonup-rs";
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+/// Prefix of the folders a tool is unpacked into before being moved in place,
+/// when it is installed without a temporary archive
+pub const STAGING_DIR_PREFIX: &str = ".protonup-staging-";
+/// Install-state manifest, in the user state folder
+pub const MANIFEST_FILE_NAME: &str = "installed.json";
 
-// pub const CONFIG_FILE: &str = "~/.config/protonup/config.ini";
 // use const_format::formatcp;
 // pub const USER_AGENT: &'static str =  formatcp!("{}/v{}", USER_AGENT, VERSION);
@@ -0,0 +1,207 @@
+//! Detection of the architecture and the x86-64 microarchitecture level of the host CPU.
+//!
+//! Tools are installed for the architecture of the host, or for the one of the `arch`
+//! setting when installing onto the disk of another machine.
+//!
+//! The levels are the ones of the x86-64 psABI: v1 is every x86-64 CPU, v2 adds SSE4.2 and
+//! POPCNT, v3 adds AVX2 and FMA, and v4 adds AVX-512. Tools like Proton CachyOS publish a
+//! build per level, and a build above the host level crashes on start.
+
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::config;
+
+/// Architectures tools can be installed for, as in `std::env::consts::ARCH`
+pub const ARCHITECTURES: [&str; 2] = ["x86_64", "aarch64"];
+
+/// Other names of each architecture found in the file names of release assets
+const ARCH_ALIASES: [(&str, &[&str]); 2] = [
+    ("x86_64", &["x86_64", "amd64", "x64"]),
+    ("aarch64", &["aarch64", "arm64"]),
+];
+
+/// Flags of `/proc/cpuinfo` required by each level above v1, as named by the kernel
+const CPUINFO_FLAGS: [&[&str]; 3] = [
+    // v2
+    &[
+        "cx16", "lahf_lm", "popcnt", "pni", "sse4_1", "sse4_2", "ssse3",
+    ],
+    // v3
+    &[
+        "avx", "avx2", "bmi1", "bmi2", "f16c", "fma", "abm", "movbe", "xsave",
+    ],
+    // v4
+    &["avx512f", "avx512bw", "avx512cd", "avx512dq", "avx512vl"],
+];
+
+static HOST_LEVEL: OnceLock<Option<u8>> = OnceLock::new();
+
+/// The architecture tools are installed for: the `arch` setting, or the one of the host
+pub fn target_arch() -> String {
+    config::current()
+        .arch
+        .clone()
+        .unwrap_or_else(|| std::env::consts::ARCH.to_owned())
+}
+
+/// Whether tools are installed for another architecture than the one of the host
+pub fn is_cross_install() -> bool {
+    target_arch() != std::env::consts::ARCH
+}
+
+/// The architecture a release asset is built for, from its file name.
+/// None if the name doesn't mention one, like "GE-Proton9-20.tar.gz"
+pub fn asset_arch(file_name: &str) -> Option<&'static str> {
+    let file_name = file_name.to_ascii_lowercase();
+    let words: Vec<&str> = file_name
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .collect();
+    ARCH_ALIASES.iter().find_map(|(arch, aliases)| {
+        words
+            .iter()
+            .any(|word| {
+                aliases.iter().any(|alias| {
+                    // "x86_64_v3" is a build for x86_64
+                    word == alias || word.strip_prefix(alias).is_some_and(|s| s.starts_with('_'))
+                })
+            })
+            .then_some(*arch)
+    })
+}
+
+/// The x86-64 level (1 to 4) of the host CPU, None on other architectures.
+///
+/// Detected with CPUID and with the flags of `/proc/cpuinfo`, keeping the lowest of both:
+/// the kernel hides the features it doesn't support, like AVX-512 on some hypervisors
+pub fn x86_64_level() -> Option<u8> {
+    *HOST_LEVEL.get_or_init(|| match (cpuid_level(), cpuinfo_level()) {
+        (Some(cpuid), Some(cpuinfo)) => Some(cpuid.min(cpuinfo)),
+        (cpuid, cpuinfo) => cpuid.or(cpuinfo),
+    })
+}
+
+/// The x86-64 level supported by a list of `/proc/cpuinfo` flags
+pub fn level_from_cpuinfo_flags<'a>(flags: impl IntoIterator<Item = &'a str>) -> u8 {
+    let flags: Vec<&str> = flags.into_iter().collect();
+    let supported = CPUINFO_FLAGS
+        .iter()
+        .take_while(|required| required.iter().all(|flag| flags.contains(flag)))
+        .count();
+    1 + supported as u8
+}
+
+/// The x86-64 level of the first processor of a `/proc/cpuinfo` content,
+/// None if it doesn't list x86 flags
+pub fn level_from_cpuinfo(cpuinfo: &str) -> Option<u8> {
+    let flags = cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "flags").then_some(value)
+    })?;
+    let flags: Vec<&str> = flags.split_whitespace().collect();
+    // "lm" (long mode) is set on every 64-bit CPU
+    flags
+        .contains(&"lm")
+        .then(|| level_from_cpuinfo_flags(flags))
+}
+
+fn cpuinfo_level() -> Option<u8> {
+    level_from_cpuinfo(&fs::read_to_string("/proc/cpuinfo").ok()?)
+}
+
+#[cfg(target_arch = "x86_64")]
+fn cpuid_level() -> Option<u8> {
+    use std::arch::is_x86_feature_detected as has;
+
+    // LAHF/SAHF can't be queried, every CPU with the other v2 features has it
+    let v2 = has!("cmpxchg16b")
+        && has!("popcnt")
+        && has!("sse3")
+        && has!("sse4.1")
+        && has!("sse4.2")
+        && has!("ssse3");
+    // the AVX checks include the support of the AVX state by the OS (OSXSAVE)
+    let v3 = has!("avx")
+        && has!("avx2")
+        && has!("bmi1")
+        && has!("bmi2")
+        && has!("f16c")
+        && has!("fma")
+        && has!("lzcnt")
+        && has!("movbe")
+        && has!("xsave");
+    let v4 = has!("avx512f")
+        && has!("avx512bw")
+        && has!("avx512cd")
+        && has!("avx512dq")
+        && has!("avx512vl");
+    let supported = [v2, v3, v4].iter().take_while(|level| **level).count();
+    Some(1 + supported as u8)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cpuid_level() -> Option<u8> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_cpuinfo() {
+        let v2 =
+            "fpu cx8 cmov mmx fxsr sse sse2 syscall lm pni ssse3 cx16 sse4_1 sse4_2 popcnt lahf_lm";
+        let v3 = format!("{v2} avx avx2 bmi1 bmi2 f16c fma abm movbe xsave");
+        let v4 = format!("{v3} avx512f avx512bw avx512cd avx512dq avx512vl");
+        let cpuinfo = |flags: &str| {
+            format!(
+                "processor\t: 0\nvendor_id\t: GenuineIntel\nflags\t\t: {flags}\n\nprocessor\t: 1\n"
+            )
+        };
+
+        assert_eq!(
+            level_from_cpuinfo(&cpuinfo("fpu cx8 cmov mmx sse sse2 lm")),
+            Some(1)
+        );
+        assert_eq!(level_from_cpuinfo(&cpuinfo(v2)), Some(2));
+        assert_eq!(level_from_cpuinfo(&cpuinfo(&v3)), Some(3));
+        assert_eq!(level_from_cpuinfo(&cpuinfo(&v4)), Some(4));
+        // AVX-512 without AVX2 is not v4
+        let no_avx2 = v4.replace(" avx2", "");
+        assert_eq!(level_from_cpuinfo(&cpuinfo(&no_avx2)), Some(2));
+        // 32-bit CPU
+        assert_eq!(level_from_cpuinfo(&cpuinfo("fpu cx8 cmov sse sse2")), None);
+        // aarch64 lists "Features"
+        assert_eq!(
+            level_from_cpuinfo("processor\t: 0\nFeatures\t: fp asimd evtstrm aes\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_asset_arch() {
+        assert_eq!(asset_arch("GE-Proton9-20.tar.gz"), None);
+        assert_eq!(asset_arch("wine-10.0-amd64.tar.xz"), Some("x86_64"));
+        assert_eq!(
+            asset_arch("proton-cachyos-10.0-20250714-slr-x86_64_v3.tar.xz"),
+            Some("x86_64")
+        );
+        assert_eq!(asset_arch("luxtorpeda-76-arm64.tar.xz"), Some("aarch64"));
+        assert_eq!(
+            asset_arch("tool-1.0-linux-AARCH64.tar.zst"),
+            Some("aarch64")
+        );
+        // only whole words
+        assert_eq!(asset_arch("farm64-1.0.tar.gz"), None);
+        assert_eq!(asset_arch("wine-10.0-x86.tar.xz"), None);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_host_level() {
+        let level = x86_64_level().unwrap();
+        assert!((1..=4).contains(&level));
+        assert!(level <= cpuid_level().unwrap());
+    }
+}
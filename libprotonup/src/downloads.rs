@@ -1,16 +1,20 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 use super::constants;
 use crate::apps;
+use crate::config;
+use crate::cpu;
 use crate::files;
 use crate::hashing;
 use crate::http_client;
-use crate::sources::CompatTool;
+use crate::sources::{CompatTool, Forge};
 use anyhow::{self, Context, Result};
 use futures_util::TryStreamExt;
-use reqwest::header::USER_AGENT;
+use reqwest::StatusCode;
+use reqwest::header::{RANGE, USER_AGENT};
 use serde::{Deserialize, Serialize};
-use tokio::io::{self, AsyncWrite};
+use tokio::io::{self, AsyncRead, AsyncWrite};
 use tokio_util::io::StreamReader;
 
 pub type ReleaseList = Vec<Release>;
@@ -26,17 +30,42 @@ pub struct Release {
     /// Release notes / changelog
     #[serde(default)]
     pub body: Option<String>,
+    /// Publication date in RFC 3339 format, example "2024-01-01T00:00:00Z"
+    #[serde(default)]
+    pub published_at: Option<String>,
+    /// True if the forge marks the Release as a pre-release
+    #[serde(default)]
+    pub prerelease: bool,
+    /// True if the Release is an unpublished draft, only listed to the maintainers
+    #[serde(default)]
+    pub draft: bool,
     /// Asset list for each Release, usually the tar.gz/tar.xz file and a sha512sum file for integrity checking
     assets: Vec<Asset>,
 }
 
 impl std::fmt::Display for Release {
+    /// The tag name, with a pre-release badge and the publication date when known,
+    /// example "GE-Proton10-1-rc1 [pre-release] (2025-03-04)"
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}", self.tag_name)
+        write!(f, "{}", self.tag_name)?;
+        if self.prerelease {
+            write!(f, " [pre-release]")?;
+        }
+        if let Some(date) = self.published_date() {
+            write!(f, " ({date})")?;
+        }
+        Ok(())
     }
 }
 
 impl Release {
+    /// Returns the date part of `published_at`, example "2024-01-01"
+    pub fn published_date(&self) -> Option<&str> {
+        self.published_at
+            .as_deref()
+            .map(|date| date.split('T').next().unwrap_or(date))
+    }
+
     /// Returns a Download struct corresponding to the Release
     /// For most tools, where there is no variation in a single release
     pub fn get_download_info(
@@ -50,29 +79,16 @@ impl Release {
             ..Download::default()
         };
 
-        for asset in &self.assets {
-            if asset.name.contains("sha512") {
-                download.file_name = asset.name.clone();
-                download.hash_sum = Some(hashing::HashSums {
-                    sum_content: asset.browser_download_url.clone(),
-                    sum_type: hashing::HashSumType::Sha512,
-                })
-            } else if asset.name.contains("sha256") {
-                download.file_name = asset.name.clone();
-                download.hash_sum = Some(hashing::HashSums {
-                    sum_content: asset.browser_download_url.clone(),
-                    sum_type: hashing::HashSumType::Sha256,
-                })
-            } else if compat_tool.filter_asset(asset.download_file_name().as_str())
+        if let Some(asset) = self.assets.iter().find(|asset| {
+            compat_tool.filter_asset(asset.download_file_name().as_str())
                 && files::check_supported_extension(&asset.name).is_ok()
-            {
-                download.file_name = asset.name.clone();
-                download
-                    .download_url
-                    .clone_from(&asset.browser_download_url);
-                download.size = asset.size as u64;
-                break;
-            }
+        }) {
+            download.file_name = asset.name.clone();
+            download
+                .download_url
+                .clone_from(&asset.browser_download_url);
+            download.size = asset.size as u64;
+            download.hash_sum = self.hash_sum_for(&asset.name);
         }
         download
     }
@@ -84,73 +100,79 @@ impl Release {
         for_app: &apps::AppInstallations,
         compat_tool: &CompatTool,
     ) -> Vec<Download> {
-        // Create a map from base filename to hash file URL
-        let mut asset_hashsum_map: std::collections::HashMap<
-            String,
-            (String, hashing::HashSumType),
-        > = std::collections::HashMap::new();
-
-        // Build the hash map by matching hash files to their base names
-        for asset in &self.assets {
-            if asset.name.contains("sha512") {
-                // hash_type = hashing::HashSumType::Sha512;
-                let base_name = asset.name.split(".sha512").collect::<Vec<&str>>()[0].to_owned();
-                asset_hashsum_map.insert(
-                    base_name,
-                    (
-                        asset.browser_download_url.clone(),
-                        hashing::HashSumType::Sha512,
-                    ),
-                );
-            } else if asset.name.contains("sha256") {
-                // hash_type = hashing::HashSumType::Sha256;
-                let base_name = asset.name.split(".sha256").collect::<Vec<&str>>()[0].to_owned();
-                asset_hashsum_map.insert(
-                    base_name,
-                    (
-                        asset.browser_download_url.clone(),
-                        hashing::HashSumType::Sha256,
-                    ),
-                );
-            }
-        }
-
-        let mut variants = Vec::new();
+        self.assets
+            .iter()
+            .filter(|asset| {
+                compat_tool.filter_asset(asset.download_file_name().as_str())
+                    && files::check_supported_extension(&asset.name).is_ok()
+            })
+            .map(|asset| Download {
+                file_name: asset.name.clone(),
+                download_url: asset.browser_download_url.clone(),
+                size: asset.size as u64,
+                for_app: for_app.to_owned(),
+                version: self.tag_name.clone(),
+                hash_sum: self.hash_sum_for(&asset.name),
+            })
+            .collect()
+    }
 
-        // Collect all matching asset variants with their corresponding hash
-        for asset in &self.assets {
-            if compat_tool.filter_asset(asset.download_file_name().as_str())
-                && files::check_supported_extension(&asset.name).is_ok()
-            {
+    /// The checksum asset of the archive `file_name`: the one named after it, like
+    /// `<name>.sha512sum`, or else a checksum file of the whole release, like `SHA256SUMS`
+    fn hash_sum_for(&self, file_name: &str) -> Option<hashing::HashSums> {
+        let archives: Vec<&str> = self
+            .assets
+            .iter()
+            .filter(|asset| files::check_supported_extension(&asset.name).is_ok())
+            .map(|asset| archive_base_name(&asset.name))
+            .collect();
+        let checksums = self
+            .assets
+            .iter()
+            .filter(|asset| files::check_supported_extension(&asset.name).is_err())
+            .filter_map(|asset| {
+                let sum_type = hashing::HashSumType::from_asset_name(&asset.name)?;
                 let base_name = asset
                     .name
-                    .strip_suffix(".tar.gz")
-                    .or_else(|| asset.name.strip_suffix(".tar.xz"))
-                    .or_else(|| asset.name.strip_suffix(".tar.zst"))
-                    .unwrap_or(&asset.name);
-
-                let hash_sum = asset_hashsum_map
-                    .get(base_name)
-                    .map(|(hash_url, hash_type)| hashing::HashSums {
-                        sum_content: hash_url.clone(),
-                        sum_type: hash_type.clone(),
-                    });
-
-                variants.push(Download {
-                    file_name: asset.name.clone(),
-                    download_url: asset.browser_download_url.clone(),
-                    size: asset.size as u64,
-                    for_app: for_app.to_owned(),
-                    version: self.tag_name.clone(),
-                    hash_sum,
-                });
+                    .rsplit_once('.')
+                    .map(|(name, _)| archive_base_name(name));
+                let hash_sum = hashing::HashSums {
+                    sum_content: asset.browser_download_url.clone(),
+                    sum_type,
+                };
+                Some((base_name, hash_sum))
+            });
+
+        let mut shared = None;
+        for (base_name, hash_sum) in checksums {
+            match base_name {
+                Some(base_name) if base_name == archive_base_name(file_name) => {
+                    return Some(hash_sum);
+                }
+                // checksums of another archive of the release
+                Some(base_name) if archives.contains(&base_name) => {}
+                _ => shared = shared.or(Some(hash_sum)),
             }
         }
-
-        variants
+        shared
     }
 }
 
+/// Name of an archive without its extension
+fn archive_base_name(name: &str) -> &str {
+    [
+        ".tar.gz",
+        ".tgz",
+        ".tar.xz",
+        ".txz",
+        ".tar.zst",
+        ".tar.zstd",
+    ]
+    .iter()
+    .find_map(|extension| name.strip_suffix(extension))
+    .unwrap_or(name)
+}
+
 /// Holds the information from the different Assets for each GitHub release
 ///
 /// An Asset could be for the wine tar folder or for the sha512sum
@@ -219,27 +241,169 @@ pub(crate) async fn download_to_async_write_with_sender<
     write: &mut W,
     sender: &S,
 ) -> Result<()> {
+    let mut reader = download_reader_with_sender(url, sender).await?;
+    io::copy(&mut reader, write).await?;
+    Ok(())
+}
+
+/// Starts a download, returning a reader of the response body.
+/// Used to unpack an archive while it is downloaded
+pub async fn download_reader(url: &str) -> Result<impl AsyncRead + Unpin + Send + use<>> {
+    download_reader_with_sender(url, &http_client::RealSender).await
+}
+
+pub(crate) async fn download_reader_with_sender<S: http_client::HttpSend>(
+    url: &str,
+    sender: &S,
+) -> Result<impl AsyncRead + Unpin + Send + use<S>> {
+    let (reader, _) = download_reader_from_with_sender(url, 0, sender).await?;
+    Ok(reader)
+}
+
+/// Starts a download from byte `offset`, to resume a partial file. Returns a reader of the
+/// response body, and whether it starts at `offset`: servers without range support send
+/// the whole file
+pub async fn download_reader_from(
+    url: &str,
+    offset: u64,
+) -> Result<(impl AsyncRead + Unpin + Send + use<>, bool)> {
+    download_reader_from_with_sender(url, offset, &http_client::RealSender).await
+}
+
+pub(crate) async fn download_reader_from_with_sender<S: http_client::HttpSend>(
+    url: &str,
+    offset: u64,
+    sender: &S,
+) -> Result<(impl AsyncRead + Unpin + Send + use<S>, bool)> {
     let client = reqwest::Client::new();
+    let mut request = client
+        .get(url)
+        .header(USER_AGENT, format!("protonup-rs {}", constants::VERSION));
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={offset}-"));
+    }
     let res = sender
-        .send(
-            client
-                .get(url)
-                .header(USER_AGENT, format!("protonup-rs {}", constants::VERSION)),
-        )
+        .send(request)
         .await
+        .and_then(reqwest::Response::error_for_status)
         .with_context(|| format!("[Download] Failed to call remote server on URL: {}", url))?;
+    let resumed = offset > 0 && res.status() == StatusCode::PARTIAL_CONTENT;
 
-    io::copy(
-        &mut StreamReader::new(res.bytes_stream().map_err(io::Error::other)),
-        write,
-    )
-    .await?;
-    Ok(())
+    Ok((
+        StreamReader::new(res.bytes_stream().map_err(io::Error::other)),
+        resumed,
+    ))
 }
 
 /// Returns a Vec of Releases from a GitHub repository, the URL used for the request is built from the passed in VariantParameters
+///
+/// Only the assets built for the target architecture are kept, see [`cpu::target_arch`],
+/// and releases left without one are removed. Drafts are never listed.
+/// Pre-releases are listed, [`latest_release`] skips them.
+///
+/// If the cache is enabled in the configuration, a list fetched less than `ttl_seconds` ago is reused
 pub async fn list_releases(compat_tool: &CompatTool) -> Result<ReleaseList, reqwest::Error> {
-    list_releases_with_sender(compat_tool, &http_client::RealSender).await
+    let r_list = list_all_releases(compat_tool, false).await?;
+    Ok(installable_releases(r_list, compat_tool))
+}
+
+/// Same as [`list_releases`], but the list is always fetched from the forge,
+/// and replaces the cached one if the cache is enabled
+pub async fn refresh_releases(compat_tool: &CompatTool) -> Result<ReleaseList, reqwest::Error> {
+    let r_list = list_all_releases(compat_tool, true).await?;
+    Ok(installable_releases(r_list, compat_tool))
+}
+
+fn installable_releases(mut r_list: ReleaseList, compat_tool: &CompatTool) -> ReleaseList {
+    r_list.retain(|rel| !rel.draft);
+    releases_for_arch(r_list, compat_tool, &cpu::target_arch())
+}
+
+/// The newest release of `r_list`, as returned by [`list_releases`].
+/// Pre-releases are skipped, unless the configuration includes them for `compat_tool`,
+/// see [`config::Config::includes_prereleases`]
+pub fn latest_release<'a>(r_list: &'a [Release], compat_tool: &CompatTool) -> Option<&'a Release> {
+    let include_prereleases = config::current().includes_prereleases(compat_tool);
+    r_list
+        .iter()
+        .find(|rel| include_prereleases || !rel.prerelease)
+}
+
+/// Keeps the assets of `r_list` that can be installed on `arch`, see
+/// [`CompatTool::asset_supports_arch`], and the releases that still have an archive
+fn releases_for_arch(r_list: ReleaseList, compat_tool: &CompatTool, arch: &str) -> ReleaseList {
+    r_list
+        .into_iter()
+        .filter_map(|mut rel| {
+            // checksum and signature files are kept
+            rel.assets.retain(|asset| {
+                files::check_supported_extension(&asset.name).is_err()
+                    || compat_tool.asset_supports_arch(&asset.name, arch)
+            });
+            rel.assets
+                .iter()
+                .any(|asset| {
+                    compat_tool.filter_asset(asset.download_file_name().as_str())
+                        && files::check_supported_extension(&asset.name).is_ok()
+                })
+                .then_some(rel)
+        })
+        .collect()
+}
+
+/// The release list of every architecture, from the cache or the forge.
+/// With `refresh`, the cache is not read
+async fn list_all_releases(
+    compat_tool: &CompatTool,
+    refresh: bool,
+) -> Result<ReleaseList, reqwest::Error> {
+    let cache = &config::current().cache;
+    let cache_file = cache
+        .dir()
+        .filter(|_| cache.enabled)
+        .map(|dir| release_cache_file(&dir, compat_tool));
+
+    if let Some(cached) = cache_file
+        .as_ref()
+        .filter(|_| !refresh)
+        .and_then(|file| read_release_cache(file, Duration::from_secs(cache.ttl_seconds)))
+    {
+        return Ok(cached);
+    }
+
+    let r_list = list_releases_with_sender(compat_tool, &http_client::RealSender).await?;
+    if let Some(file) = cache_file {
+        // the cache is only an optimization, failing to write it is not an error
+        let _ = write_release_cache(&file, &r_list);
+    }
+    Ok(r_list)
+}
+
+/// Cache file of the release list of a tool
+fn release_cache_file(cache_dir: &Path, compat_tool: &CompatTool) -> PathBuf {
+    let name: String = compat_tool
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    cache_dir.join("releases").join(format!("{name}.json"))
+}
+
+/// Returns the cached release list, if it is younger than `ttl`
+fn read_release_cache(file: &Path, ttl: Duration) -> Option<ReleaseList> {
+    let modified = std::fs::metadata(file).ok()?.modified().ok()?;
+    if SystemTime::now().duration_since(modified).ok()? > ttl {
+        return None;
+    }
+    serde_json::from_slice(&std::fs::read(file).ok()?).ok()
+}
+
+fn write_release_cache(file: &Path, r_list: &ReleaseList) -> Result<()> {
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(file, serde_json::to_vec(r_list)?)?;
+    Ok(())
 }
 
 pub(crate) async fn list_releases_with_sender<S: http_client::HttpSend>(
@@ -257,7 +421,12 @@ pub(crate) async fn list_releases_with_sender<S: http_client::HttpSend>(
 
     let client = reqwest::Client::builder().user_agent(agent).build()?;
 
-    let r_list: ReleaseList = sender.send(client.get(url)).await?.json().await?;
+    let mut request = client.get(url);
+    if let (Forge::GitHub, Some(token)) = (&compat_tool.forge, &config::current().github_token) {
+        request = request.bearer_auth(token);
+    }
+
+    let r_list: ReleaseList = sender.send(request).await?.json().await?;
 
     // filter releases without assets
     let r_list: ReleaseList = r_list
@@ -594,6 +763,191 @@ mod tests {
         assert_eq!(rel.tag_name, "GE-Proton9-10");
     }
 
+    #[test]
+    fn test_release_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let compat_tool = sources::CompatTool::from_str("Kron4ek Wine").unwrap();
+        let file = release_cache_file(dir.path(), &compat_tool);
+        assert!(file.ends_with("releases/Kron4ek_Wine.json"));
+        assert!(read_release_cache(&file, Duration::from_secs(60)).is_none());
+
+        let r_list: ReleaseList = serde_json::from_value(json!([{
+            "tag_name": "10.0",
+            "name": "10.0",
+            "url": null,
+            "assets": []
+        }]))
+        .unwrap();
+        write_release_cache(&file, &r_list).unwrap();
+
+        let cached = read_release_cache(&file, Duration::from_secs(60)).unwrap();
+        assert_eq!(cached[0].tag_name, "10.0");
+        assert!(read_release_cache(&file, Duration::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_release_publication_fields() {
+        let rel: Release = serde_json::from_value(json!({
+            "tag_name": "GE-Proton10-1-rc1",
+            "name": "GE-Proton10-1-rc1",
+            "url": null,
+            "assets": [],
+            "published_at": "2025-03-04T12:00:00Z",
+            "prerelease": true
+        }))
+        .unwrap();
+        assert_eq!(rel.published_date(), Some("2025-03-04"));
+        assert!(rel.prerelease);
+        assert_eq!(
+            rel.to_string(),
+            "GE-Proton10-1-rc1 [pre-release] (2025-03-04)"
+        );
+
+        let rel: Release = serde_json::from_value(json!({
+            "tag_name": "GE-Proton9-10",
+            "name": "GE-Proton9-10",
+            "url": null,
+            "assets": []
+        }))
+        .unwrap();
+        assert_eq!(rel.published_date(), None);
+        assert!(!rel.prerelease, "prerelease defaults to false");
+        assert!(!rel.draft, "draft defaults to false");
+        assert_eq!(rel.to_string(), "GE-Proton9-10");
+    }
+
+    #[test]
+    fn test_hash_sum_for() {
+        let asset = |name: &str| {
+            json!({
+                "url": "",
+                "id": 0,
+                "name": name,
+                "size": 1,
+                "updated_at": "",
+                "browser_download_url": format!("https://example.com/{name}"),
+            })
+        };
+        let rel: Release = serde_json::from_value(json!({
+            "tag_name": "10.0",
+            "name": "10.0",
+            "url": null,
+            "assets": [
+                asset("tool-10.0-x86_64.sha512sum"),
+                asset("tool-10.0-x86_64.tar.xz"),
+                asset("tool-10.0-x86_64_v3.tar.xz"),
+                asset("tool-10.0-x86_64_v3.tar.xz.b3"),
+                asset("tool-10.0-aarch64.tar.xz"),
+                asset("SHA256SUMS.asc"),
+                asset("SHA256SUMS"),
+                asset("tool-10.0-x86_64.sha512sum.minisig"),
+            ]
+        }))
+        .unwrap();
+
+        let hash_sum = |file_name: &str| {
+            rel.hash_sum_for(file_name)
+                .map(|hash_sum| (hash_sum.sum_content, hash_sum.sum_type))
+        };
+        assert_eq!(
+            hash_sum("tool-10.0-x86_64.tar.xz"),
+            Some((
+                "https://example.com/tool-10.0-x86_64.sha512sum".to_owned(),
+                hashing::HashSumType::Sha512
+            ))
+        );
+        assert_eq!(
+            hash_sum("tool-10.0-x86_64_v3.tar.xz"),
+            Some((
+                "https://example.com/tool-10.0-x86_64_v3.tar.xz.b3".to_owned(),
+                hashing::HashSumType::Blake3
+            ))
+        );
+        // without its own checksum file, the one of the whole release is used, not its signature
+        assert_eq!(
+            hash_sum("tool-10.0-aarch64.tar.xz"),
+            Some((
+                "https://example.com/SHA256SUMS".to_owned(),
+                hashing::HashSumType::Sha256
+            ))
+        );
+    }
+
+    #[test]
+    fn test_releases_for_arch() {
+        let asset = |name: &str| {
+            json!({
+                "url": "",
+                "id": 0,
+                "name": name,
+                "size": 1,
+                "updated_at": "",
+                "browser_download_url": format!("https://example.com/{name}"),
+            })
+        };
+        let r_list: ReleaseList = serde_json::from_value(json!([
+            {
+                "tag_name": "2.0",
+                "name": "2.0",
+                "url": null,
+                "assets": [
+                    asset("tool-2.0-x86_64.tar.xz"),
+                    asset("tool-2.0-arm64.tar.xz"),
+                    asset("SHA256SUMS"),
+                ]
+            },
+            {
+                "tag_name": "1.0",
+                "name": "1.0",
+                "url": null,
+                "assets": [asset("tool-1.0-x86_64.tar.xz")]
+            },
+            {
+                "tag_name": "0.9",
+                "name": "0.9",
+                "url": null,
+                "assets": [asset("tool-0.9.tar.xz")]
+            }
+        ]))
+        .unwrap();
+        let mut tool = CompatTool::new_custom(
+            "Tool".to_owned(),
+            sources::Forge::GitHub,
+            "".to_owned(),
+            "".to_owned(),
+            sources::ToolType::Runtime,
+            None,
+            None,
+            None,
+        );
+        let names = |r_list: &ReleaseList| -> Vec<Vec<String>> {
+            r_list
+                .iter()
+                .map(|rel| rel.assets.iter().map(|a| a.name.clone()).collect())
+                .collect()
+        };
+
+        let x86_64 = releases_for_arch(r_list.clone(), &tool, "x86_64");
+        assert_eq!(
+            names(&x86_64),
+            vec![
+                vec!["tool-2.0-x86_64.tar.xz", "SHA256SUMS"],
+                vec!["tool-1.0-x86_64.tar.xz"],
+                vec!["tool-0.9.tar.xz"],
+            ]
+        );
+        // the tool is declared for x86_64 only, assets without an architecture are x86_64
+        let aarch64 = releases_for_arch(r_list.clone(), &tool, "aarch64");
+        assert_eq!(
+            names(&aarch64),
+            vec![vec!["tool-2.0-arm64.tar.xz", "SHA256SUMS"]]
+        );
+        tool.architectures = vec!["x86_64".to_owned(), "aarch64".to_owned()];
+        let aarch64 = releases_for_arch(r_list, &tool, "aarch64");
+        assert_eq!(aarch64.len(), 2);
+        assert_eq!(aarch64[1].tag_name, "0.9");
+    }
+
     #[tokio::test]
     async fn test_list_releases_with_mock_sender() {
         let sender = http_client::MockSender {
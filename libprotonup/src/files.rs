@@ -1,23 +1,61 @@
+use std::collections::HashSet;
 use std::fmt;
 use std::future;
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::task::Poll;
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Error, Result, anyhow};
 use async_compression::tokio::bufread::{GzipDecoder, XzDecoder, ZstdDecoder};
 use futures_util::StreamExt;
 use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tokio::fs::File;
-use tokio::io::{AsyncBufRead, AsyncRead, BufReader, ReadBuf};
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader, ReadBuf};
 use tokio::{fs, io, pin};
 use tokio_stream::wrappers::ReadDirStream;
-use tokio_tar::ArchiveBuilder;
+use tokio_tar::{ArchiveBuilder, Entry};
 
+use crate::constants;
 use crate::downloads::Download;
+use crate::hashing::{self, HashSumType};
 use crate::sources::CompatTool;
 use crate::utils;
 
+/// A file or symbolic link unpacked from an archive, relative to the tool folder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub kind: FileKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileKind {
+    File { size: u64, sha256: String },
+    Symlink { target: PathBuf },
+}
+
+/// Differences between an installed tool folder and the files it was unpacked with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeReport {
+    /// Files with a different content, or replaced by another kind of file
+    pub modified: Vec<PathBuf>,
+    pub missing: Vec<PathBuf>,
+    /// Files that were not unpacked from the archive
+    pub extra: Vec<PathBuf>,
+}
+
+impl TreeReport {
+    pub fn is_intact(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
 #[pin_project(project = DecompressorProject)]
 pub enum Decompressor<R: AsyncBufRead + Unpin> {
     Gzip(#[pin] GzipDecoder<R>),
@@ -116,28 +154,49 @@ pub async fn unpack_file<R: AsyncRead + Unpin>(
     reader: R,
     install_path: &Path,
 ) -> Result<()> {
-    let install_dir = utils::expand_tilde(install_path).unwrap();
+    unpack_file_with_progress(compat_tool, download, reader, install_path, |_| {})
+        .await
+        .map(|_| ())
+}
 
-    fs::create_dir_all(&install_dir).await.unwrap();
+/// Same as [`unpack_file`], calling `on_entry` with the number of archive entries
+/// written so far after each one is extracted.
+/// Returns the unpacked files, with the SHA-256 of their content
+pub async fn unpack_file_with_progress<R: AsyncRead + Unpin, F: FnMut(u64)>(
+    compat_tool: &CompatTool,
+    download: &Download,
+    reader: R,
+    install_path: &Path,
+    on_entry: F,
+) -> Result<Vec<FileEntry>> {
+    let install_dir = utils::expand_tilde(install_path)
+        .with_context(|| format!("[Unpack] Invalid install path: {}", install_path.display()))?;
+
+    fs::create_dir_all(&install_dir).await.with_context(|| {
+        format!(
+            "[Unpack] Failed creating install directory: {}",
+            install_dir.display()
+        )
+    })?;
 
     decompress_with_new_top_level(
         reader,
         install_dir.as_path(),
         compat_tool.installation_name(&download.version).as_str(),
+        on_entry,
     )
     .await
-    .unwrap();
-
-    Ok(())
 }
 
 /// decompress_with_new_top_level unpacks the tarrball,
-/// replacing the top level folder with the provided value
-async fn decompress_with_new_top_level<R: AsyncRead + Unpin>(
+/// replacing the top level folder with the provided value.
+/// Regular files are hashed while they are written
+async fn decompress_with_new_top_level<R: AsyncRead + Unpin, F: FnMut(u64)>(
     reader: R,
     destination_path: &Path,
     new_top_level: &str,
-) -> Result<()> {
+    mut on_entry: F,
+) -> Result<Vec<FileEntry>> {
     let mut archive = ArchiveBuilder::new(reader)
         .set_unpack_xattrs(false)
         .set_preserve_permissions(true)
@@ -148,6 +207,9 @@ async fn decompress_with_new_top_level<R: AsyncRead + Unpin>(
 
     // Get the entries from the archive
     let mut entries = archive.entries()?;
+    let mut unpacked: u64 = 0;
+    let tool_dir = destination_path.join(new_top_level);
+    let mut files = vec![];
 
     while let Some(entry) = entries.next().await {
         let mut entry = entry?;
@@ -185,18 +247,145 @@ async fn decompress_with_new_top_level<R: AsyncRead + Unpin>(
 
         if path.is_dir() {
             fs::create_dir_all(&new_path).await?;
-            continue;
+        } else {
+            if let Some(parent) = new_path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+
+            let entry_type = entry.header().entry_type();
+            let kind = if entry_type.is_file() {
+                let (size, sha256) = unpack_hashed(&mut entry, &new_path).await?;
+                Some(FileKind::File { size, sha256 })
+            } else {
+                // Extract the file
+                entry.unpack(&new_path).await?;
+                if entry_type.is_symlink() {
+                    Some(FileKind::Symlink {
+                        target: fs::read_link(&new_path).await?,
+                    })
+                } else if entry_type.is_hard_link() {
+                    let (size, sha256) = hash_file(&new_path).await?;
+                    Some(FileKind::File { size, sha256 })
+                } else {
+                    None
+                }
+            };
+            // entries moved out of the tool folder are not part of it
+            if let (Some(kind), Ok(relative)) = (kind, new_path.strip_prefix(&tool_dir)) {
+                files.push(FileEntry {
+                    path: relative.to_path_buf(),
+                    kind,
+                });
+            }
         }
 
-        if let Some(parent) = new_path.parent() {
-            fs::create_dir_all(parent).await?;
+        unpacked += 1;
+        on_entry(unpacked);
+    }
+
+    Ok(files)
+}
+
+/// Writes a regular file entry to `path` while hashing it,
+/// keeping its permissions and modification time
+async fn unpack_hashed<R: AsyncRead + Unpin>(
+    entry: &mut Entry<R>,
+    path: &Path,
+) -> Result<(u64, String)> {
+    let mode = entry.header().mode()?;
+    let mtime = entry.header().mtime()?;
+
+    // replaces any existing file, even read-only ones
+    let _ = fs::remove_file(path).await;
+    let mut file = File::create(path)
+        .await
+        .with_context(|| format!("[Unpack] Failed creating file: {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let count = entry.read(&mut buffer).await?;
+        if count == 0 {
+            break;
         }
+        hasher.update(&buffer[..count]);
+        file.write_all(&buffer[..count]).await?;
+        size += count as u64;
+    }
+    file.flush().await?;
+
+    let file = file.into_std().await;
+    // setuid, setgid and sticky bits of the archive are dropped
+    file.set_permissions(std::fs::Permissions::from_mode(mode & 0o777))?;
+    file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(mtime))?;
+    Ok((size, hex::encode(hasher.finalize())))
+}
 
-        // Extract the file
-        entry.unpack(&new_path).await?;
+/// Size and SHA-256 of a file
+async fn hash_file(path: &Path) -> Result<(u64, String)> {
+    let file = File::open(path)
+        .await
+        .with_context(|| format!("[Verify] Failed to open file: {}", path.display()))?;
+    let size = file.metadata().await?.len();
+    let sha256 = hashing::digest(&mut BufReader::new(file), &HashSumType::Sha256).await?;
+    Ok((size, sha256))
+}
+
+/// Compares a tool folder with the files it was unpacked with
+pub async fn verify_tree(tool_dir: &Path, files: &[FileEntry]) -> Result<TreeReport> {
+    let tool_dir = utils::expand_tilde(tool_dir)
+        .with_context(|| format!("[Verify] Invalid path: {}", tool_dir.display()))?;
+    let mut report = TreeReport::default();
+
+    for file in files {
+        let path = tool_dir.join(&file.path);
+        let metadata = match fs::symlink_metadata(&path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                report.missing.push(file.path.clone());
+                continue;
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("[Verify] Failed to read {}", path.display()));
+            }
+        };
+        let intact = match &file.kind {
+            FileKind::File { size, sha256 } => {
+                metadata.is_file()
+                    && metadata.len() == *size
+                    && hash_file(&path).await?.1 == *sha256
+            }
+            FileKind::Symlink { target } => {
+                metadata.is_symlink() && fs::read_link(&path).await? == *target
+            }
+        };
+        if !intact {
+            report.modified.push(file.path.clone());
+        }
     }
 
-    Ok(())
+    let known: HashSet<&Path> = files.iter().map(|file| file.path.as_path()).collect();
+    let mut pending = vec![tool_dir.clone()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .with_context(|| format!("[Verify] Failed to read directory: {}", dir.display()))?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if fs::symlink_metadata(&path).await?.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let relative = path.strip_prefix(&tool_dir)?;
+            if !known.contains(relative) {
+                report.extra.push(relative.to_path_buf());
+            }
+        }
+    }
+    report.extra.sort();
+
+    Ok(report)
 }
 
 /// check_if_exists checks if a folder exists in a path
@@ -213,11 +402,36 @@ pub async fn list_folders_in_path(path: &PathBuf) -> Result<Vec<String>, anyhow:
         .filter_map(|e| future::ready(e.ok()))
         .filter(|e| future::ready(e.path().is_dir()))
         .map(|e| e.path().file_name().unwrap().to_str().unwrap().to_string())
+        // unfinished installs are not listed
+        .filter(|name| future::ready(!name.starts_with(constants::STAGING_DIR_PREFIX)))
         .collect()
         .await;
     Ok(paths_real)
 }
 
+/// Returns the total size in bytes of the files in a directory, recursively.
+/// Symbolic links are counted by their own size and not followed
+pub async fn dir_size(path: &Path) -> Result<u64> {
+    let root = utils::expand_tilde(path)
+        .with_context(|| format!("[Size] Invalid path: {}", path.display()))?;
+    let mut size = 0;
+    let mut pending = vec![root];
+    while let Some(dir) = pending.pop() {
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .with_context(|| format!("[Size] Failed to read directory: {}", dir.display()))?;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = fs::symlink_metadata(entry.path()).await?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                size += metadata.len();
+            }
+        }
+    }
+    Ok(size)
+}
+
 /// Removes a directory and all its contents
 pub async fn remove_dir_all(path: &PathBuf) -> Result<()> {
     let f_path = utils::expand_tilde(path).unwrap();
@@ -428,4 +642,58 @@ mod test {
         assert!(file2.is_file(), "Nested file not found");
         assert_eq!(fs::read_to_string(file2).unwrap(), "more content");
     }
+
+    #[tokio::test]
+    async fn test_unpack_drops_setuid_bits() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let s = CompatTool::new_custom(
+            "".to_owned(),
+            sources::Forge::GitHub,
+            "".to_owned(),
+            "".to_owned(),
+            sources::ToolType::WineBased,
+            None,
+            None,
+            None,
+        );
+        let d = Download {
+            file_name: "test".to_owned(),
+            for_app: AppInstallations::Steam,
+            version: "setuid".to_owned(),
+            hash_sum: None,
+            download_url: "test.tar".to_owned(),
+            size: 1,
+        };
+
+        let temp_dir = tempdir().unwrap();
+        let tar_path = temp_dir.path().join("test.tar");
+        let mut builder = tar::Builder::new(fs::File::create(&tar_path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_mode(0o6755);
+        builder
+            .append_data(&mut header, "original_top/bin/wine", &b"wine"[..])
+            .unwrap();
+        builder.finish().unwrap();
+
+        let output_dir = temp_dir.path().join("output");
+        let file = File::open(tar_path).await.unwrap();
+        unpack_file(&s, &d, file, &output_dir)
+            .await
+            .expect("Unpacking failed");
+
+        let metadata = fs::metadata(output_dir.join("setuid/bin/wine")).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o7777, 0o755);
+    }
+
+    #[tokio::test]
+    async fn test_dir_size() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("file1.txt"), "1234").unwrap();
+        fs::create_dir_all(temp_dir.path().join("subdir/nested")).unwrap();
+        fs::write(temp_dir.path().join("subdir/nested/file2.txt"), "123456").unwrap();
+
+        assert_eq!(dir_size(temp_dir.path()).await.unwrap(), 10);
+    }
 }
@@ -1,7 +1,17 @@
-use anyhow::{Context, Result};
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+
+use anyhow::{Context, Result, anyhow, bail};
+use blake2::Blake2b512;
+use pin_project::pin_project;
 use serde::{Deserialize, Serialize};
+use sha1::Sha1;
 use sha2::{Digest, Sha256, Sha512};
-use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::fs::{self, File};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, BufReader, ReadBuf};
+use tokio::process::Command;
 
 #[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct HashSums {
@@ -9,84 +19,520 @@ pub struct HashSums {
     pub sum_type: HashSumType,
 }
 
-#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 pub enum HashSumType {
     Sha512,
     Sha256,
+    Sha1,
+    /// BLAKE2b-512, as printed by `b2sum`
+    Blake2b,
+    Blake3,
 }
 
-/// Checks the downloaded file integrity with the sha512sum
+impl HashSumType {
+    /// Algorithm of a checksum asset, from names like `GE-Proton10-1.sha512sum`,
+    /// `SHA256SUMS` or `wine.tar.xz.b3`. Only the last extension, or the whole name without
+    /// any, is read, so signatures like `SHA256SUMS.asc` are not checksum files
+    pub fn from_asset_name(name: &str) -> Option<HashSumType> {
+        let name = name.to_lowercase();
+        let extension = name.rsplit_once('.').map_or(name.as_str(), |(_, ext)| ext);
+        let algorithm = extension
+            .strip_suffix("sums")
+            .or_else(|| extension.strip_suffix("sum"))
+            .unwrap_or(extension);
+        HashSumType::from_name(algorithm)
+    }
+
+    /// Parses the name of an algorithm, like the tag of a BSD style checksum line
+    fn from_name(name: &str) -> Option<HashSumType> {
+        match name.to_lowercase().as_str() {
+            "sha512" => Some(HashSumType::Sha512),
+            "sha256" => Some(HashSumType::Sha256),
+            "sha1" => Some(HashSumType::Sha1),
+            "b2" | "blake2" | "blake2b" | "blake2b-512" => Some(HashSumType::Blake2b),
+            "b3" | "blake3" => Some(HashSumType::Blake3),
+            _ => None,
+        }
+    }
+}
+
+/// Checks the downloaded file integrity with the published checksum file
 pub async fn hash_check_file<R: AsyncRead + Unpin + ?Sized>(
     file_name: &str,
     reader: &mut R,
     git_hash: HashSums,
 ) -> Result<bool> {
-    // find line with the file name
-    let expected_hash_line = git_hash
-        .sum_content
+    let hash = digest(reader, &git_hash.sum_type)
+        .await
+        .context("[Hash Check] Failed reading download file for checking")?;
+    Ok(expected_hash(file_name, &git_hash.sum_content).is_some_and(|expected| hash == expected))
+}
+
+/// A line of a checksum file
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ChecksumEntry {
+    /// File name as written in the checksum file. None for a file containing only a hash
+    pub file_name: Option<String>,
+    /// Lowercase hex encoded digest
+    pub hash: String,
+    /// Algorithm, when written in the line (BSD style)
+    pub algorithm: Option<HashSumType>,
+}
+
+/// Parses the entries of a checksum file, in any of the formats:
+/// - GNU coreutils: `<hex>  <name>`, `<hex> *<name>` for binary mode,
+///   and `\<hex>  <name>` when the name has escaped characters
+/// - BSD: `SHA512 (<name>) = <hex>`
+/// - A single hash without a file name
+///
+/// Empty lines, comments and unrecognized lines are skipped
+pub fn parse_checksums(content: &str) -> Vec<ChecksumEntry> {
+    content
         .lines()
-        .find(|line| line.contains(file_name));
-
-    let (expected_hash, _) = expected_hash_line
-        // if no line found with the file name, assume the content is only the sum
-        .unwrap_or(&git_hash.sum_content)
-        .rsplit_once(' ')
-        // if the split fails, assume the content is only the sum without any spaces
-        .unwrap_or((&git_hash.sum_content, ""));
-
-    match git_hash.sum_type {
-        HashSumType::Sha512 => {
-            let mut hasher = Sha512::new();
-            read_all_into_digest(reader, &mut hasher)
-                .await
-                .context("[Hash Check] Failed reading download file for checking")?;
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| parse_bsd_line(line).or_else(|| parse_gnu_line(line)))
+        .collect()
+}
+
+fn is_hex(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_hexdigit())
+}
 
-            let hash = hasher.finalize();
+/// `SHA512 (<name>) = <hex>`
+fn parse_bsd_line(line: &str) -> Option<ChecksumEntry> {
+    let (tag, rest) = line.split_once(" (")?;
+    let (file_name, hash) = rest.rsplit_once(") = ")?;
+    let hash = hash.trim();
+    is_hex(hash).then(|| ChecksumEntry {
+        file_name: Some(file_name.to_owned()),
+        hash: hash.to_lowercase(),
+        algorithm: HashSumType::from_name(tag),
+    })
+}
+
+/// `<hex>  <name>`, `<hex> *<name>` or `<hex>`
+fn parse_gnu_line(line: &str) -> Option<ChecksumEntry> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let (hash, file_name) = match line.split_once(char::is_whitespace) {
+        Some((hash, file_name)) => {
+            // one space and a space or '*' separate the name in text and binary mode
+            let file_name = file_name.strip_prefix([' ', '*']).unwrap_or(file_name);
+            (hash, Some(file_name))
+        }
+        None => (line, None),
+    };
+    if !is_hex(hash) || file_name.is_some_and(str::is_empty) {
+        return None;
+    }
+    Some(ChecksumEntry {
+        file_name: file_name.map(|name| match escaped {
+            true => unescape_file_name(name),
+            false => name.to_owned(),
+        }),
+        hash: hash.to_lowercase(),
+        algorithm: None,
+    })
+}
 
-            if hex::encode(hash) != expected_hash.trim() {
-                return Ok(false);
+/// Reverts the escaping of `\\` and `\n` done by coreutils for names with those characters
+fn unescape_file_name(name: &str) -> String {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                unescaped.push('\n');
+                chars.next();
             }
-            Ok(true)
+            ('\\', Some('\\')) => {
+                unescaped.push('\\');
+                chars.next();
+            }
+            _ => unescaped.push(c),
         }
-        HashSumType::Sha256 => {
-            let mut hasher = Sha256::new();
-            read_all_into_digest(reader, &mut hasher)
-                .await
-                .context("[Hash Check] Failed reading download file for checking")?;
+    }
+    unescaped
+}
+
+/// Finds the hash of `file_name` in the content of a checksum file.
+///
+/// The name must match exactly, ignoring a leading directory like `./`.
+/// A file with a single hash and no name applies to any file
+pub fn expected_hash(file_name: &str, sum_content: &str) -> Option<String> {
+    let entries = parse_checksums(sum_content);
+    let matching = entries.iter().find(|entry| {
+        entry
+            .file_name
+            .as_deref()
+            .is_some_and(|name| name == file_name || name.rsplit('/').next() == Some(file_name))
+    });
+    match (matching, entries.as_slice()) {
+        (Some(entry), _) => Some(entry.hash.clone()),
+        (
+            None,
+            [
+                ChecksumEntry {
+                    file_name: None,
+                    hash,
+                    ..
+                },
+            ],
+        ) => Some(hash.clone()),
+        _ => None,
+    }
+}
 
-            let hash = hasher.finalize();
+/// Incremental digest of one of the supported algorithms
+#[derive(Clone)]
+pub enum Hasher {
+    Sha512(Sha512),
+    Sha256(Sha256),
+    Sha1(Sha1),
+    Blake2b(Blake2b512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    pub fn new(sum_type: &HashSumType) -> Hasher {
+        match sum_type {
+            HashSumType::Sha512 => Hasher::Sha512(Sha512::new()),
+            HashSumType::Sha256 => Hasher::Sha256(Sha256::new()),
+            HashSumType::Sha1 => Hasher::Sha1(Sha1::new()),
+            HashSumType::Blake2b => Hasher::Blake2b(Blake2b512::new()),
+            HashSumType::Blake3 => Hasher::Blake3(Box::default()),
+        }
+    }
 
-            if hex::encode(hash) != expected_hash.trim() {
-                return Ok(false);
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha512(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Sha1(hasher) => hasher.update(data),
+            Hasher::Blake2b(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
             }
-            Ok(true)
+        }
+    }
+
+    /// Hex encoded digest of everything passed to [`Hasher::update`]
+    pub fn finalize_hex(self) -> String {
+        match self {
+            Hasher::Sha512(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Sha256(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Sha1(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Blake2b(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
         }
     }
 }
 
-async fn read_all_into_digest<R: AsyncRead + Unpin + ?Sized, D: Digest>(
-    read: &mut R,
-    digest: &mut D,
-) -> Result<()> {
+/// Hex encoded digest of everything read from `reader`
+pub async fn digest<R: AsyncRead + Unpin + ?Sized>(
+    reader: &mut R,
+    sum_type: &HashSumType,
+) -> Result<String> {
     const BUFFER_LEN: usize = 8 * 1024; // 8KB
     let mut buffer = [0u8; BUFFER_LEN];
+    let mut hasher = Hasher::new(sum_type);
 
     loop {
-        let count = read.read(&mut buffer).await?;
-        digest.update(&buffer[..count]);
-        if count != BUFFER_LEN {
+        let count = reader.read(&mut buffer).await?;
+        // reads can be shorter than the buffer before the end of the file
+        if count == 0 {
             break;
         }
+        hasher.update(&buffer[..count]);
+    }
+
+    Ok(hasher.finalize_hex())
+}
+
+/// AsyncWrite wrapper hashing the bytes as they are written,
+/// so a download is checked without reading the file again
+#[pin_project]
+pub struct HashingWriter<W> {
+    #[pin]
+    inner: W,
+    hasher: Hasher,
+}
+
+impl<W: AsyncWrite> HashingWriter<W> {
+    pub fn new(inner: W, sum_type: &HashSumType) -> Self {
+        HashingWriter {
+            inner,
+            hasher: Hasher::new(sum_type),
+        }
     }
 
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the inner writer and the hex encoded digest of the bytes written to it
+    pub fn finish(self) -> (W, String) {
+        (self.inner, self.hasher.finalize_hex())
+    }
+}
+
+impl<W: AsyncWrite> AsyncWrite for HashingWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let res = this.inner.poll_write(cx, buf);
+        // only the bytes accepted by the inner writer are hashed
+        if let Poll::Ready(Ok(written)) = res {
+            this.hasher.update(&buf[..written]);
+        }
+        res
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+/// AsyncRead wrapper hashing the bytes as they are read
+#[pin_project]
+pub struct HashingReader<R> {
+    #[pin]
+    inner: R,
+    hasher: Hasher,
+}
+
+impl<R: AsyncRead> HashingReader<R> {
+    pub fn new(inner: R, sum_type: &HashSumType) -> Self {
+        HashingReader {
+            inner,
+            hasher: Hasher::new(sum_type),
+        }
+    }
+
+    /// Returns the inner reader and the hex encoded digest of the bytes read from it
+    pub fn finish(self) -> (R, String) {
+        (self.inner, self.hasher.finalize_hex())
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for HashingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let before = buf.filled().len();
+        let res = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = res {
+            this.hasher.update(&buf.filled()[before..]);
+        }
+        res
+    }
+}
+
+/// Format of a detached signature
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+pub enum SignatureKind {
+    /// `.minisig` files from minisign or rsign2, made with a prehashed signature (the default)
+    Minisign,
+    /// ASCII armored (`.asc`) or binary (`.sig`) OpenPGP signatures, checked with `gpgv`
+    OpenPgp,
+}
+
+/// Checks the detached `signature` of the file at `path` with any of the trusted `keys`.
+///
+/// Minisign keys are the base64 line of the public key file, with or without its comment.
+/// OpenPGP keys are ASCII armored public keys.
+pub async fn verify_signature(
+    path: &Path,
+    signature: &[u8],
+    kind: SignatureKind,
+    keys: &[String],
+) -> Result<()> {
+    if keys.is_empty() {
+        bail!("No trusted key to check the signature with");
+    }
+    match kind {
+        SignatureKind::Minisign => verify_minisign(path, signature, keys).await,
+        SignatureKind::OpenPgp => verify_openpgp(path, signature, keys).await,
+    }
+}
+
+async fn verify_minisign(path: &Path, signature: &[u8], keys: &[String]) -> Result<()> {
+    let signature = std::str::from_utf8(signature)
+        .ok()
+        .and_then(|signature| minisign_verify::Signature::decode(signature).ok())
+        .ok_or_else(|| anyhow!("Invalid minisign signature"))?;
+
+    // a malformed key is skipped like a key with another id, the others may still match
+    let mut invalid = vec![];
+    for key in keys {
+        let key = key.trim();
+        let public_key = match key.contains('\n') {
+            true => minisign_verify::PublicKey::decode(key),
+            false => minisign_verify::PublicKey::from_base64(key),
+        };
+        let public_key = match public_key {
+            Ok(public_key) => public_key,
+            Err(e) => {
+                invalid.push(e.to_string());
+                continue;
+            }
+        };
+        let mut verifier = match public_key.verify_stream(&signature) {
+            Ok(verifier) => verifier,
+            Err(minisign_verify::Error::UnexpectedKeyId) => continue,
+            Err(e) => bail!("Unsupported minisign signature: {e}"),
+        };
+
+        let mut reader = BufReader::new(File::open(path).await?);
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let count = reader.read(&mut buffer).await?;
+            if count == 0 {
+                break;
+            }
+            verifier.update(&buffer[..count]);
+        }
+        return verifier
+            .finalize()
+            .map_err(|_| anyhow!("{} has an invalid minisign signature", path.display()));
+    }
+    if !invalid.is_empty() {
+        bail!(
+            "{} is signed with an untrusted minisign key, {} trusted key(s) could not be read: {}",
+            path.display(),
+            invalid.len(),
+            invalid.join(", ")
+        );
+    }
+    Err(anyhow!(
+        "{} is signed with an untrusted minisign key",
+        path.display()
+    ))
+}
+
+async fn verify_openpgp(path: &Path, signature: &[u8], keys: &[String]) -> Result<()> {
+    // gpgv only reads binary keyrings, the armored keys are converted with gpg
+    let home = tempfile::tempdir()?;
+    let mut keyring = vec![];
+    for (i, key) in keys.iter().enumerate() {
+        let armored = home.path().join(format!("key{i}.asc"));
+        let binary = home.path().join(format!("key{i}.gpg"));
+        fs::write(&armored, key).await?;
+        let output = Command::new("gpg")
+            .arg("--batch")
+            .arg("--homedir")
+            .arg(home.path())
+            .arg("--output")
+            .arg(&binary)
+            .arg("--dearmor")
+            .arg(&armored)
+            .output()
+            .await
+            .context("Failed to run gpg, it is needed to check OpenPGP signatures")?;
+        if !output.status.success() {
+            bail!(
+                "Invalid OpenPGP public key: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        keyring.extend(fs::read(&binary).await?);
+    }
+    let keyring_path = home.path().join("trusted.gpg");
+    fs::write(&keyring_path, keyring).await?;
+    let signature_path = home.path().join("signature");
+    fs::write(&signature_path, signature).await?;
+
+    let output = Command::new("gpgv")
+        .arg("--homedir")
+        .arg(home.path())
+        .arg("--keyring")
+        .arg(&keyring_path)
+        .arg(&signature_path)
+        .arg(path)
+        .output()
+        .await
+        .context("Failed to run gpgv, it is needed to check OpenPGP signatures")?;
+    if !output.status.success() {
+        bail!(
+            "{} has an invalid or untrusted OpenPGP signature: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
     Ok(())
 }
 
+/// Minisign signatures made with keys generated in the tests
+#[cfg(test)]
+pub(crate) mod test_signing {
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use super::*;
+
+    pub(crate) struct MinisignKey {
+        signing_key: SigningKey,
+        key_id: [u8; 8],
+    }
+
+    impl MinisignKey {
+        pub(crate) fn new(seed: u8) -> MinisignKey {
+            MinisignKey {
+                signing_key: SigningKey::from_bytes(&[seed; 32]),
+                key_id: [seed; 8],
+            }
+        }
+
+        pub(crate) fn public_key(&self) -> String {
+            let mut key = b"Ed".to_vec();
+            key.extend(self.key_id);
+            key.extend(self.signing_key.verifying_key().as_bytes());
+            STANDARD.encode(key)
+        }
+
+        /// Prehashed signature of `data`, as made by `minisign -S`
+        pub(crate) fn sign(&self, data: &[u8]) -> String {
+            let signature = self.signing_key.sign(&Blake2b512::digest(data)).to_bytes();
+            let trusted_comment = "timestamp:0\tfile:test";
+            let mut global = signature.to_vec();
+            global.extend(trusted_comment.as_bytes());
+            let global_signature = self.signing_key.sign(&global).to_bytes();
+
+            let mut line = b"ED".to_vec();
+            line.extend(self.key_id);
+            line.extend(signature);
+            format!(
+                "untrusted comment: test signature\n{}\ntrusted comment: {trusted_comment}\n{}\n",
+                STANDARD.encode(line),
+                STANDARD.encode(global_signature)
+            )
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use sha2::{Digest, Sha256, Sha512};
 
-    use crate::hashing::{HashSumType, HashSums};
+    use crate::hashing::{HashSumType, HashSums, SignatureKind};
 
     #[tokio::test]
     async fn hash_check_file() {
@@ -123,4 +569,231 @@ mod test {
             "Hash didn't match"
         );
     }
+
+    #[tokio::test]
+    async fn hashing_writer_matches_digest() {
+        use tokio::io::AsyncWriteExt;
+
+        let test_data = b"This Is A Test".repeat(10_000);
+        for sum_type in [HashSumType::Sha512, HashSumType::Sha256] {
+            let mut writer = super::HashingWriter::new(Vec::new(), &sum_type);
+            for chunk in test_data.chunks(1000) {
+                writer.write_all(chunk).await.unwrap();
+            }
+            writer.flush().await.unwrap();
+            let (written, hash) = writer.finish();
+
+            assert_eq!(written, test_data);
+            assert_eq!(
+                hash,
+                super::digest(&mut &test_data[..], &sum_type).await.unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn hashing_reader_matches_digest() {
+        use tokio::io::AsyncReadExt;
+
+        let test_data = b"This Is A Test".repeat(10_000);
+        let mut reader = super::HashingReader::new(&test_data[..], &HashSumType::Sha256);
+        let mut read = vec![];
+        reader.read_to_end(&mut read).await.unwrap();
+        let (_, hash) = reader.finish();
+
+        assert_eq!(read, test_data);
+        assert_eq!(
+            hash,
+            hex::encode(Sha256::new_with_prefix(&test_data).finalize())
+        );
+    }
+
+    #[tokio::test]
+    async fn digest_algorithms() {
+        let digest =
+            |sum_type| async move { super::digest(&mut &b"abc"[..], &sum_type).await.unwrap() };
+        assert_eq!(
+            digest(HashSumType::Sha1).await,
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            digest(HashSumType::Blake2b).await,
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+        assert_eq!(
+            digest(HashSumType::Blake3).await,
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[test]
+    fn sum_type_from_asset_name() {
+        let cases = [
+            ("GE-Proton10-1.sha512sum", Some(HashSumType::Sha512)),
+            ("SHA256SUMS", Some(HashSumType::Sha256)),
+            ("wine-lutris.tar.xz.sha256", Some(HashSumType::Sha256)),
+            ("tool.sha1", Some(HashSumType::Sha1)),
+            ("tool.tar.gz.b2sum", Some(HashSumType::Blake2b)),
+            ("BLAKE3SUMS", Some(HashSumType::Blake3)),
+            ("tool.b3", Some(HashSumType::Blake3)),
+            ("SHA256SUMS.asc", None),
+            ("SHA256SUMS.sig", None),
+            ("tool.sha512sum.minisig", None),
+            ("sha256-checksums", None),
+            ("checksums.txt", None),
+            ("GE-Proton10-1.tar.gz", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(HashSumType::from_asset_name(name), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn expected_hash_formats() {
+        let content = "\
+# checksums of the release
+aaaa  GE-Proton10-1.tar.gz
+bbbb  GE-Proton10-11.tar.gz
+CCCC *binary-mode.tar.gz
+dddd  ./dist/nested.tar.gz
+SHA512 (bsd-style.tar.gz) = eeee
+\\ffff  escaped\\nname.tar.gz
+";
+        let expected = |name| super::expected_hash(name, content);
+        // similar names don't match each other
+        assert_eq!(expected("GE-Proton10-1.tar.gz").as_deref(), Some("aaaa"));
+        assert_eq!(expected("GE-Proton10-11.tar.gz").as_deref(), Some("bbbb"));
+        assert_eq!(expected("binary-mode.tar.gz").as_deref(), Some("cccc"));
+        assert_eq!(expected("nested.tar.gz").as_deref(), Some("dddd"));
+        assert_eq!(expected("bsd-style.tar.gz").as_deref(), Some("eeee"));
+        assert_eq!(expected("escaped\nname.tar.gz").as_deref(), Some("ffff"));
+        assert_eq!(expected("GE-Proton10-1"), None);
+        assert_eq!(expected("other.tar.gz"), None);
+
+        let bsd = &super::parse_checksums(content)[4];
+        assert_eq!(bsd.algorithm, Some(HashSumType::Sha512));
+
+        // a file with only the hash applies to any name
+        assert_eq!(
+            super::expected_hash("anything.tar.gz", "0123abcd\n").as_deref(),
+            Some("0123abcd")
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_minisign_signature() {
+        use super::test_signing::MinisignKey;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("tool.tar.gz");
+        std::fs::write(&file, b"This Is A Test").unwrap();
+        let key = MinisignKey::new(1);
+        let other_key = MinisignKey::new(2);
+        let signature = key.sign(b"This Is A Test");
+        let verify = |keys: Vec<String>, signature: String| {
+            let file = file.clone();
+            async move {
+                super::verify_signature(&file, signature.as_bytes(), SignatureKind::Minisign, &keys)
+                    .await
+            }
+        };
+
+        verify(
+            vec![
+                "not a minisign key".to_owned(),
+                other_key.public_key(),
+                key.public_key(),
+            ],
+            signature.clone(),
+        )
+        .await
+        .expect("valid signature rejected");
+        assert!(
+            verify(vec![other_key.public_key()], signature.clone())
+                .await
+                .is_err(),
+            "signature from an untrusted key accepted"
+        );
+        let error = verify(vec!["not a minisign key".to_owned()], signature.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("1 trusted key(s) could not be read")
+        );
+        assert!(
+            verify(vec![key.public_key()], key.sign(b"Other data"))
+                .await
+                .is_err(),
+            "signature of other data accepted"
+        );
+        assert!(verify(vec![], signature).await.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs gpg and gpgv, run with --ignored"]
+    async fn verify_openpgp_signature() {
+        use std::process::Command;
+
+        let dir = tempfile::tempdir().unwrap();
+        let gpg = |home: &str, args: &[&str]| {
+            let home = dir.path().join(home);
+            std::fs::create_dir_all(&home).unwrap();
+            let output = Command::new("gpg")
+                .args(["--batch", "--pinentry-mode", "loopback", "--passphrase", ""])
+                .arg("--homedir")
+                .arg(&home)
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{output:?}");
+            String::from_utf8(output.stdout).unwrap()
+        };
+        for home in ["signer", "other"] {
+            gpg(
+                home,
+                &[
+                    "--quick-gen-key",
+                    "Test <test@example.com>",
+                    "ed25519",
+                    "sign",
+                    "never",
+                ],
+            );
+        }
+        let key = gpg("signer", &["--armor", "--export"]);
+        let other_key = gpg("other", &["--armor", "--export"]);
+
+        let file = dir.path().join("tool.tar.gz");
+        std::fs::write(&file, b"This Is A Test").unwrap();
+        gpg(
+            "signer",
+            &[
+                "--armor",
+                "--output",
+                "tool.tar.gz.asc",
+                "--detach-sign",
+                "tool.tar.gz",
+            ],
+        );
+        let signature = std::fs::read(dir.path().join("tool.tar.gz.asc")).unwrap();
+
+        super::verify_signature(
+            &file,
+            &signature,
+            SignatureKind::OpenPgp,
+            &[other_key.clone(), key],
+        )
+        .await
+        .expect("valid signature rejected");
+        assert!(
+            super::verify_signature(&file, &signature, SignatureKind::OpenPgp, &[other_key])
+                .await
+                .is_err(),
+            "signature from an untrusted key accepted"
+        );
+    }
 }
@@ -0,0 +1,1858 @@
+//! Download → verify → unpack pipeline for a single release asset.
+//!
+//! The [`Installer`] drives the whole installation of a [`Download`] into one or more
+//! [`AppInstallations`] and reports its progress as [`InstallEvent`]s, so front-ends
+//! only have to render them.
+
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result, anyhow, bail};
+use futures_util::StreamExt;
+use futures_util::stream::FuturesUnordered;
+use pin_project::pin_project;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{self, AsyncRead, AsyncWrite, BufReader, ReadBuf};
+pub use tokio_util::sync::CancellationToken;
+
+use crate::apps::AppInstallations;
+use crate::config::{self, ChecksumPolicy};
+use crate::constants;
+use crate::downloads::{self, Download};
+use crate::files;
+use crate::hashing::{self, SignatureKind};
+use crate::manifest::{self, AssetPin, Checksum, InstallRecord, Manifest, PinCheck};
+use crate::sources::CompatTool;
+
+/// Number of times a failed download is retried by default
+pub const DEFAULT_DOWNLOAD_RETRIES: u32 = 2;
+
+/// Events emitted by the [`Installer`] while it runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallEvent {
+    /// The download of the asset started
+    Started {
+        file_name: String,
+        version: String,
+        total: u64,
+        targets: Vec<AppInstallations>,
+    },
+    /// Bytes of the asset downloaded so far
+    Bytes { done: u64, total: u64 },
+    /// Bytes of the downloaded file checked against the expected hash so far
+    Verifying { done: u64, total: u64 },
+    /// Unpacking progress into one of the targets.
+    /// `done` and `total` are in compressed bytes, `entries` is the count of archive entries written
+    Unpacking {
+        target: AppInstallations,
+        install_dir: PathBuf,
+        entries: u64,
+        done: u64,
+        total: u64,
+    },
+    /// The download failed and is being retried
+    Retry { attempt: u32, error: String },
+    /// The asset can't be verified, it has no published checksum nor signature
+    Warning { message: String },
+    /// The tool was installed into `target`
+    Finished {
+        target: AppInstallations,
+        install_path: PathBuf,
+    },
+    /// The installation failed, for a single target or for all of them when `target` is None
+    Failed {
+        target: Option<AppInstallations>,
+        error: String,
+    },
+    /// The installation was cancelled, and the partial files were removed
+    Cancelled,
+}
+
+/// Error returned by [`Installer::run`] when its [`CancellationToken`] is cancelled.
+///
+/// Use `error.is::<Cancelled>()` to tell it apart from other failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Installation cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Error of an asset whose digest doesn't match the published hash sum or the expected checksum
+#[derive(Debug)]
+struct DigestMismatch(String);
+
+impl fmt::Display for DigestMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed validation", self.0)
+    }
+}
+
+impl std::error::Error for DigestMismatch {}
+
+/// Result of the checks of the downloaded asset
+struct Verification {
+    checksum: Checksum,
+    /// Kind of the signature the asset was verified with, if the tool is signed
+    signature: Option<SignatureKind>,
+}
+
+/// Callback receiving every [`InstallEvent`]
+pub type EventCallback = Arc<dyn Fn(InstallEvent) + Send + Sync>;
+
+/// Installs one release asset of a compatibility tool into one or more app installations.
+///
+/// The asset is downloaded once into a temporary directory, validated against its hash sum
+/// (if the release provides one), and then unpacked into every target.
+/// With [`Installer::with_streaming`], it is unpacked while downloaded instead.
+/// Each installed folder is recorded in the install [`Manifest`].
+///
+/// ```no_run
+/// # async fn example(download: libprotonup::downloads::Download, tool: libprotonup::sources::CompatTool) -> anyhow::Result<()> {
+/// use libprotonup::installer::Installer;
+///
+/// let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+/// let targets = vec![download.for_app.clone()];
+/// let installer = Installer::new(tool, download, targets).on_event(move |event| {
+///     let _ = tx.send(event);
+/// });
+/// tokio::spawn(async move {
+///     while let Some(event) = rx.recv().await {
+///         println!("{event:?}");
+///     }
+/// });
+/// installer.run().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Installer {
+    compat_tool: CompatTool,
+    download: Download,
+    targets: Vec<AppInstallations>,
+    retries: u32,
+    on_event: Option<EventCallback>,
+    cancel: Option<CancellationToken>,
+    manifest_path: Option<PathBuf>,
+    archive_dir: Option<PathBuf>,
+    expected_checksum: Option<Checksum>,
+    stream: bool,
+    checksum_policy: ChecksumPolicy,
+}
+
+impl Installer {
+    /// Creates an installer for `download` of `compat_tool`, unpacking it into every target
+    pub fn new(
+        compat_tool: CompatTool,
+        download: Download,
+        targets: Vec<AppInstallations>,
+    ) -> Installer {
+        Installer {
+            compat_tool,
+            download,
+            targets,
+            retries: DEFAULT_DOWNLOAD_RETRIES,
+            on_event: None,
+            cancel: None,
+            manifest_path: Manifest::path().ok(),
+            archive_dir: config::current().cache.archive_dir(),
+            expected_checksum: None,
+            stream: config::current().stream_install,
+            checksum_policy: config::current().checksum_policy,
+        }
+    }
+
+    /// Sets how many times a failed download is retried before giving up
+    pub fn with_retries(mut self, retries: u32) -> Installer {
+        self.retries = retries;
+        self
+    }
+
+    /// Registers the callback that receives the progress events
+    pub fn on_event<F: Fn(InstallEvent) + Send + Sync + 'static>(
+        mut self,
+        callback: F,
+    ) -> Installer {
+        self.on_event = Some(Arc::new(callback));
+        self
+    }
+
+    /// Stops the download, validation or unpacking as soon as `token` is cancelled.
+    ///
+    /// The temporary download and the partially unpacked folders are removed before
+    /// [`Installer::run`] returns a [`Cancelled`] error.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Installer {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Sets the install manifest the installed folders are recorded in.
+    /// Defaults to [`Manifest::path`], `None` disables the recording
+    pub fn with_manifest_path(mut self, manifest_path: Option<PathBuf>) -> Installer {
+        self.manifest_path = manifest_path;
+        self
+    }
+
+    /// Sets the folder the downloaded archives are kept in, and reused from.
+    /// Defaults to the `cache.keep_archives` setting, `None` disables it
+    pub fn with_archive_dir(mut self, archive_dir: Option<PathBuf>) -> Installer {
+        self.archive_dir = archive_dir;
+        self
+    }
+
+    /// Checks the asset against a known checksum when the release doesn't publish one,
+    /// like the one recorded in the install manifest
+    pub fn with_expected_checksum(mut self, checksum: Checksum) -> Installer {
+        self.expected_checksum = Some(checksum);
+        self
+    }
+
+    /// Unpacks the asset while it is downloaded, without a temporary archive.
+    ///
+    /// Each target is unpacked into a staging folder next to the installation, moved in
+    /// place only once the digest of the whole asset matches. The asset is downloaded once
+    /// per target, and is not kept in the archive folder.
+    /// Defaults to the `stream_install` setting
+    pub fn with_streaming(mut self, stream: bool) -> Installer {
+        self.stream = stream;
+        self
+    }
+
+    /// Sets what to do when the release publishes no checksum and the tool is not signed.
+    /// Defaults to the `checksum_policy` setting
+    pub fn with_checksum_policy(mut self, checksum_policy: ChecksumPolicy) -> Installer {
+        self.checksum_policy = checksum_policy;
+        self
+    }
+
+    /// The asset being installed
+    pub fn download(&self) -> &Download {
+        &self.download
+    }
+
+    /// The compatibility tool being installed
+    pub fn compat_tool(&self) -> &CompatTool {
+        &self.compat_tool
+    }
+
+    fn emit(&self, event: InstallEvent) {
+        if let Some(callback) = &self.on_event {
+            callback(event);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+    }
+
+    /// Runs `fut` until it completes or the cancellation token is cancelled
+    async fn cancellable<T>(&self, fut: impl Future<Output = Result<T>>) -> Result<T> {
+        match &self.cancel {
+            Some(token) => tokio::select! {
+                biased;
+                _ = token.cancelled() => Err(Cancelled.into()),
+                res = fut => res,
+            },
+            None => fut.await,
+        }
+    }
+
+    /// Path of the tool folder inside `target`
+    fn install_path(&self, target: &AppInstallations) -> Result<PathBuf> {
+        let install_dir = target
+            .installation_dir(&self.compat_tool)
+            .ok_or_else(|| anyhow!("Could not resolve the installation directory for {target}"))?;
+        Ok(install_dir.join(self.compat_tool.installation_name(&self.download.version)))
+    }
+
+    /// Removes the temporary download folder after a cancellation, with the `protonup-rs-*`
+    /// folder created for it. The shared fallback folder is kept
+    async fn discard_download(&self, file_path: &Path) {
+        let Some(download_dir) = file_path.parent() else {
+            return;
+        };
+        let _ = fs::remove_dir_all(download_dir).await;
+        if let Some(temp_dir) = download_dir.parent()
+            && temp_dir.file_name().is_some_and(|name| {
+                name.to_string_lossy()
+                    .starts_with(constants::TEMP_DIR_PREFIX)
+            })
+        {
+            // only removed if empty, another download could be using it
+            let _ = fs::remove_dir(temp_dir).await;
+        }
+    }
+
+    /// Runs the whole pipeline, returning the paths of the installed tool for each target.
+    ///
+    /// A failure to download or validate the asset aborts the installation for all targets.
+    /// Unpack failures are reported per target with [`InstallEvent::Failed`], and the other
+    /// targets are still installed before the error is returned.
+    pub async fn run(&self) -> Result<Vec<PathBuf>> {
+        if self.targets.is_empty() {
+            return Ok(vec![]);
+        }
+        if self.checksum_policy == ChecksumPolicy::Require && !self.is_verifiable() {
+            let error = anyhow!(
+                "{} {} publishes no checksum for {}, refusing to install it",
+                self.compat_tool.name,
+                self.download.version,
+                self.download.file_name
+            );
+            self.emit(InstallEvent::Failed {
+                target: None,
+                error: format!("{error:#}"),
+            });
+            return Err(error);
+        }
+        // signatures are checked on the whole file, signed tools are never streamed
+        if self.stream {
+            if self.compat_tool.signature.is_none() {
+                return self.run_streaming().await;
+            }
+            self.emit(InstallEvent::Warning {
+                message: format!(
+                    "{} is signed, {} is downloaded to a temporary file instead of streamed",
+                    self.compat_tool.name, self.download.file_name
+                ),
+            });
+        }
+
+        let file = match self.download.download_dir() {
+            Ok(file) => file,
+            Err(e) => {
+                self.emit(InstallEvent::Failed {
+                    target: None,
+                    error: format!("{e:#}"),
+                });
+                return Err(e);
+            }
+        };
+
+        let verification = match self.cancellable(self.download_and_verify(&file)).await {
+            Ok(verification) => verification,
+            Err(e) => {
+                if e.is::<Cancelled>() {
+                    self.discard_download(&file).await;
+                    self.emit(InstallEvent::Cancelled);
+                } else {
+                    self.emit(InstallEvent::Failed {
+                        target: None,
+                        error: format!("{e:#}"),
+                    });
+                }
+                return Err(e);
+            }
+        };
+
+        let mut unpack_joins = self
+            .targets
+            .iter()
+            .map(|target| {
+                let file = &file;
+                let verification = &verification;
+                async move {
+                    let res = self
+                        .cancellable(self.unpack_into(file, target, verification))
+                        .await;
+                    if res.as_ref().is_err_and(|e| e.is::<Cancelled>())
+                        && let Ok(install_path) = self.install_path(target)
+                    {
+                        let _ = fs::remove_dir_all(install_path).await;
+                    }
+                    (target, res)
+                }
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        let mut installed = vec![];
+        let mut errors = vec![];
+        while let Some((target, res)) = unpack_joins.next().await {
+            match res {
+                Ok(install_path) => {
+                    self.emit(InstallEvent::Finished {
+                        target: target.clone(),
+                        install_path: install_path.clone(),
+                    });
+                    installed.push(install_path);
+                }
+                Err(e) if e.is::<Cancelled>() => {}
+                Err(e) => {
+                    self.emit(InstallEvent::Failed {
+                        target: Some(target.clone()),
+                        error: format!("{e:#}"),
+                    });
+                    errors.push(format!("{e:#}"));
+                }
+            }
+        }
+
+        if self.is_cancelled() {
+            self.discard_download(&file).await;
+            self.emit(InstallEvent::Cancelled);
+            return Err(Cancelled.into());
+        }
+
+        if !errors.is_empty() {
+            return Err(anyhow!(errors.join("\n")));
+        }
+
+        Ok(installed)
+    }
+
+    /// Downloads the asset and checks its hash sum and signature
+    async fn download_and_verify(&self, file: &Path) -> Result<Verification> {
+        let mut checksum = self.download_and_check(file).await?;
+        let signature = self.check_signature(file).await?;
+        match signature {
+            Some(_) => checksum.verified = true,
+            None => self.trust_on_first_use(&checksum).await?,
+        }
+        Ok(Verification {
+            checksum,
+            signature,
+        })
+    }
+
+    /// Whether the asset can be checked against a published checksum, a known one or a signature
+    fn is_verifiable(&self) -> bool {
+        self.download.hash_sum.is_some()
+            || self.expected_checksum.is_some()
+            || self.compat_tool.signature.is_some()
+    }
+
+    /// Pins the digest of an unverified asset the first time it is downloaded,
+    /// and refuses it if it changed since. Warns about it with the `warn` policy
+    async fn trust_on_first_use(&self, checksum: &Checksum) -> Result<()> {
+        if checksum.verified {
+            return Ok(());
+        }
+        let file_name = &self.download.file_name;
+        let check = match &self.manifest_path {
+            Some(manifest_path) => {
+                let pin = AssetPin {
+                    tool: self.compat_tool.name.clone(),
+                    version: self.download.version.clone(),
+                    asset: file_name.clone(),
+                    algorithm: checksum.algorithm.clone(),
+                    value: checksum.value.clone(),
+                    first_seen: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+                };
+                Some(manifest::pin_digest(manifest_path, pin).await?)
+            }
+            None => None,
+        };
+        if let Some(PinCheck::Changed(pinned)) = check {
+            bail!(
+                "{file_name} changed since it was first downloaded on {}: its digest was {}, it is now {}. \
+                 Remove its pin from {} if the release was updated on purpose",
+                pinned.first_seen,
+                pinned.value,
+                checksum.value,
+                self.manifest_path
+                    .as_deref()
+                    .unwrap_or(Path::new(""))
+                    .display()
+            );
+        }
+        if self.checksum_policy == ChecksumPolicy::Warn {
+            let message = match check {
+                Some(PinCheck::Matched) => format!(
+                    "No checksum published for {file_name}, it matches the digest of its first download"
+                ),
+                _ => format!(
+                    "No checksum published for {file_name}, its digest is pinned on first use"
+                ),
+            };
+            self.emit(InstallEvent::Warning { message });
+        }
+        Ok(())
+    }
+
+    /// Checks the detached signature of the downloaded asset, if the tool is signed.
+    /// Returns the kind of the valid signature
+    async fn check_signature(&self, file: &Path) -> Result<Option<SignatureKind>> {
+        let Some(config) = &self.compat_tool.signature else {
+            return Ok(None);
+        };
+        // the signature is published next to the asset
+        let name = config.asset_name(&self.download.file_name);
+        let url = match self.download.download_url.rsplit_once('/') {
+            Some((base, _)) => format!("{base}/{name}"),
+            None => name.clone(),
+        };
+        let mut signature = vec![];
+        downloads::download_to_async_write(&url, &mut signature)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to download the signature {name}, refusing the unsigned {}",
+                    self.download.file_name
+                )
+            })?;
+        hashing::verify_signature(file, &signature, config.kind, &config.keys)
+            .await
+            .with_context(|| format!("Refusing {}", self.download.file_name))?;
+        Ok(Some(config.kind))
+    }
+
+    /// Downloads the asset into the temp directory, or copies it from the kept archives,
+    /// and checks its hash sum if available. Returns the checksum of the file
+    async fn download_and_check(&self, file: &Path) -> Result<Checksum> {
+        self.emit(InstallEvent::Started {
+            file_name: self.download.file_name.clone(),
+            version: self.download.version.clone(),
+            total: self.download.size,
+            targets: self.targets.clone(),
+        });
+
+        if let Some(kept) = self.kept_archive().await
+            && let Ok(total) = fs::copy(&kept, file).await
+        {
+            self.emit(InstallEvent::Bytes { done: total, total });
+            // copied without hashing, the file is read again
+            match self.check(file, None).await {
+                Ok(checksum) => return Ok(checksum),
+                // a corrupted archive is downloaded again
+                Err(e) if e.is::<DigestMismatch>() => {
+                    let _ = fs::remove_file(&kept).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut attempt = 0;
+        let streamed = loop {
+            // a retry resumes the partial file of the failed attempt
+            match self.download_once(file, attempt > 0).await {
+                Ok(digest) => break digest,
+                Err(e) if attempt < self.retries => {
+                    attempt += 1;
+                    self.emit(InstallEvent::Retry {
+                        attempt,
+                        error: format!("{e:#}"),
+                    });
+                    tokio::time::sleep(Duration::from_secs(u64::from(attempt))).await;
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!(
+                            "Error downloading {}, make sure you're connected to the internet",
+                            self.download.version
+                        )
+                    });
+                }
+            }
+        };
+
+        let checksum = self.check(file, streamed).await?;
+        // the kept archives are only an optimization, failing to keep one is not an error
+        let _ = self.keep_archive(file).await;
+        Ok(checksum)
+    }
+
+    /// Path of the asset in the kept archives, under the tool and release it belongs to,
+    /// as several tools and releases publish assets with the same name
+    fn archive_path(&self) -> Option<PathBuf> {
+        Some(
+            self.archive_dir
+                .as_ref()?
+                .join(&self.compat_tool.name)
+                .join(&self.download.version)
+                .join(&self.download.file_name),
+        )
+    }
+
+    /// Path of the asset in the kept archives, if it is there
+    async fn kept_archive(&self) -> Option<PathBuf> {
+        let kept = self.archive_path()?;
+        fs::metadata(&kept)
+            .await
+            .is_ok_and(|metadata| metadata.is_file())
+            .then_some(kept)
+    }
+
+    async fn keep_archive(&self, file: &Path) -> Result<()> {
+        if let Some(kept) = self.archive_path() {
+            if let Some(parent) = kept.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::copy(file, kept).await?;
+        }
+        Ok(())
+    }
+
+    /// Algorithm of the published hash sum, or of the expected checksum. SHA-256 without any
+    fn sum_type(&self) -> hashing::HashSumType {
+        match (&self.download.hash_sum, &self.expected_checksum) {
+            (Some(hash_sum), _) => hash_sum.sum_type.clone(),
+            (None, Some(checksum)) => checksum.algorithm.clone(),
+            (None, None) => hashing::HashSumType::Sha256,
+        }
+    }
+
+    /// The published hash sum of the asset, or the expected checksum, and whether it is
+    /// verified. Only a published hash sum is, an expected checksum keeps its own flag
+    async fn expected_hash(&self) -> Result<Option<(String, bool)>> {
+        if let Some(ref git_hash_sum) = self.download.hash_sum {
+            let hash_content = downloads::download_file_into_memory(&git_hash_sum.sum_content)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Error getting expected download hash for {}",
+                        self.download.version
+                    )
+                })?;
+            return hashing::expected_hash(&self.download.file_name, &hash_content)
+                .map(|hash| Some((hash, true)))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "No hash for {} in the published checksum file",
+                        self.download.file_name
+                    )
+                });
+        }
+        Ok(self
+            .expected_checksum
+            .as_ref()
+            .map(|checksum| (checksum.value.clone(), checksum.verified)))
+    }
+
+    /// Checks the file against the published hash sum, or the expected checksum.
+    /// `streamed` is the digest computed while downloading, the file is read again without it
+    async fn check(&self, file: &Path, streamed: Option<String>) -> Result<Checksum> {
+        let hash = match streamed {
+            Some(hash) => hash,
+            None => self.hash_file(file, &self.sum_type()).await?,
+        };
+        let total = fs::metadata(file).await?.len();
+        self.validate(hash, total).await
+    }
+
+    /// Compares the digest of the asset with the published hash sum, or the expected checksum.
+    /// Without any, the digest is only recorded
+    async fn validate(&self, hash: String, total: u64) -> Result<Checksum> {
+        let verified = match self.expected_hash().await? {
+            Some((expected, verified)) => {
+                self.emit(InstallEvent::Verifying { done: total, total });
+                if hash != expected {
+                    return Err(DigestMismatch(self.download.file_name.clone()).into());
+                }
+                verified
+            }
+            None => false,
+        };
+        Ok(Checksum {
+            algorithm: self.sum_type(),
+            value: hash,
+            verified,
+        })
+    }
+
+    /// Downloads the asset into `file_path`, returning its digest. With `resume`, the bytes
+    /// already in the file are kept if the server supports ranges. The digest of a resumed
+    /// file can't be computed while downloading, and None is returned
+    async fn download_once(&self, file_path: &Path, resume: bool) -> Result<Option<String>> {
+        let offset = match resume {
+            true => fs::metadata(file_path)
+                .await
+                .map(|metadata| metadata.len())
+                .unwrap_or(0),
+            false => 0,
+        };
+        let (mut body, resumed) =
+            downloads::download_reader_from(&self.download.download_url, offset).await?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .write(true)
+            .open(file_path)
+            .await
+            .with_context(|| {
+                format!(
+                    "[Download] Failed creating destination file: {}",
+                    file_path.display()
+                )
+            })?;
+
+        let total = self.download.size;
+        let start = if resumed { offset } else { 0 };
+        let writer = ProgressWriter::new(file, |done| {
+            self.emit(InstallEvent::Bytes {
+                done: start + done,
+                total,
+            })
+        });
+        if resumed {
+            let mut writer = writer;
+            let copied = io::copy(&mut body, &mut writer).await;
+            // the bytes written before a failure are kept for the next attempt
+            io::AsyncWriteExt::flush(&mut writer).await?;
+            copied?;
+            return Ok(None);
+        }
+        let mut writer = hashing::HashingWriter::new(writer, &self.sum_type());
+        let copied = io::copy(&mut body, &mut writer).await;
+        io::AsyncWriteExt::flush(&mut writer).await?;
+        copied?;
+        Ok(Some(writer.finish().1))
+    }
+
+    async fn hash_file(
+        &self,
+        file_path: &Path,
+        algorithm: &hashing::HashSumType,
+    ) -> Result<String> {
+        let file = File::open(file_path).await.context(
+            "[Hash Check] Failed opening download file for checking. Was the file moved?",
+        )?;
+        let total = file.metadata().await?.len();
+
+        self.emit(InstallEvent::Verifying { done: 0, total });
+        let mut reader = ProgressReader::new(BufReader::new(file), |done| {
+            self.emit(InstallEvent::Verifying { done, total })
+        });
+
+        hashing::digest(&mut reader, algorithm)
+            .await
+            .context("[Hash Check] Failed reading download file for checking")
+    }
+
+    /// Streams the asset into each target in turn, see [`Installer::with_streaming`]
+    async fn run_streaming(&self) -> Result<Vec<PathBuf>> {
+        self.emit(InstallEvent::Started {
+            file_name: self.download.file_name.clone(),
+            version: self.download.version.clone(),
+            total: self.download.size,
+            targets: self.targets.clone(),
+        });
+
+        let mut installed = vec![];
+        let mut errors = vec![];
+        for target in &self.targets {
+            let res = self.cancellable(self.stream_into(target)).await;
+            if res.is_err()
+                && let Ok(staging_dir) = self.staging_dir(target)
+            {
+                let _ = fs::remove_dir_all(staging_dir).await;
+            }
+            match res {
+                Ok(install_path) => {
+                    self.emit(InstallEvent::Finished {
+                        target: target.clone(),
+                        install_path: install_path.clone(),
+                    });
+                    installed.push(install_path);
+                }
+                Err(e) if e.is::<Cancelled>() => {
+                    self.emit(InstallEvent::Cancelled);
+                    return Err(e);
+                }
+                Err(e) => {
+                    self.emit(InstallEvent::Failed {
+                        target: Some(target.clone()),
+                        error: format!("{e:#}"),
+                    });
+                    errors.push(format!("{e:#}"));
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(anyhow!(errors.join("\n")));
+        }
+        Ok(installed)
+    }
+
+    /// Folder the tool is unpacked into before being moved to its installation path
+    fn staging_dir(&self, target: &AppInstallations) -> Result<PathBuf> {
+        let install_path = self.install_path(target)?;
+        let name = install_path
+            .file_name()
+            .ok_or_else(|| anyhow!("Invalid install path {}", install_path.display()))?;
+        Ok(install_path.with_file_name(format!(
+            "{}{}",
+            constants::STAGING_DIR_PREFIX,
+            name.to_string_lossy()
+        )))
+    }
+
+    /// Unpacks the asset into the staging folder while it is downloaded,
+    /// and replaces the installation with it if its digest matches
+    async fn stream_into(&self, target: &AppInstallations) -> Result<PathBuf> {
+        let install_path = self.install_path(target)?;
+        let staging_dir = self.staging_dir(target)?;
+
+        let mut attempt = 0;
+        let (files, hash) = loop {
+            // a failed attempt leaves a partially unpacked folder
+            let _ = fs::remove_dir_all(&staging_dir).await;
+            match self.stream_once(target, &staging_dir).await {
+                Ok(res) => break res,
+                Err(e) if attempt < self.retries => {
+                    attempt += 1;
+                    self.emit(InstallEvent::Retry {
+                        attempt,
+                        error: format!("{e:#}"),
+                    });
+                    tokio::time::sleep(Duration::from_secs(u64::from(attempt))).await;
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!(
+                            "Error downloading {}, make sure you're connected to the internet",
+                            self.download.version
+                        )
+                    });
+                }
+            }
+        };
+        let checksum = self.validate(hash, self.download.size).await?;
+        self.trust_on_first_use(&checksum).await?;
+
+        if files::check_if_exists(&install_path).await {
+            fs::remove_dir_all(&install_path).await.with_context(|| {
+                format!(
+                    "Error removing existing install at {}",
+                    install_path.display()
+                )
+            })?;
+        }
+        let unpacked = staging_dir.join(install_path.file_name().unwrap_or_default());
+        fs::rename(&unpacked, &install_path)
+            .await
+            .with_context(|| format!("Error moving {} in place", install_path.display()))?;
+        fs::remove_dir_all(&staging_dir).await?;
+
+        let verification = Verification {
+            checksum,
+            signature: None,
+        };
+        self.record_install(target, &install_path, &verification, &files)
+            .await?;
+        Ok(install_path)
+    }
+
+    /// Downloads and unpacks the asset into `staging_dir`, hashing it on the way.
+    /// Returns the unpacked files and the digest of the asset
+    async fn stream_once(
+        &self,
+        target: &AppInstallations,
+        staging_dir: &Path,
+    ) -> Result<(Vec<files::FileEntry>, String)> {
+        let install_dir = self
+            .install_path(target)?
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let total = self.download.size;
+
+        let progress = std::sync::Mutex::new((0u64, 0u64));
+        let report = |progress: (u64, u64)| {
+            self.emit(InstallEvent::Unpacking {
+                target: target.clone(),
+                install_dir: install_dir.clone(),
+                entries: progress.1,
+                done: progress.0,
+                total,
+            })
+        };
+        report((0, 0));
+
+        let body = downloads::download_reader(&self.download.download_url).await?;
+        let progress_reader = ProgressReader::new(body, |done| {
+            let mut progress = progress.lock().unwrap();
+            progress.0 = done;
+            report(*progress);
+        });
+        let mut hashing_reader = hashing::HashingReader::new(progress_reader, &self.sum_type());
+
+        let decompressor = files::Decompressor::from_reader(
+            BufReader::new(&mut hashing_reader),
+            &self.download.file_name,
+        )
+        .with_context(|| format!("Error checking file type of {}", self.download.file_name))?;
+        let files = files::unpack_file_with_progress(
+            &self.compat_tool,
+            &self.download,
+            decompressor,
+            staging_dir,
+            |entries| {
+                let mut progress = progress.lock().unwrap();
+                progress.1 = entries;
+                report(*progress);
+            },
+        )
+        .await
+        .with_context(|| format!("Error unpacking {}", self.download.file_name))?;
+
+        // the archive can end before the compressed data, the rest is read to hash it all
+        io::copy(&mut hashing_reader, &mut io::sink()).await?;
+        Ok((files, hashing_reader.finish().1))
+    }
+
+    /// Adds the installed folder and its files to the install manifest, if enabled
+    async fn record_install(
+        &self,
+        target: &AppInstallations,
+        install_path: &Path,
+        verification: &Verification,
+        files: &[files::FileEntry],
+    ) -> Result<()> {
+        let Some(manifest_path) = &self.manifest_path else {
+            return Ok(());
+        };
+        let file_list = manifest::save_file_list(manifest_path, install_path, files)?;
+        let variant = self
+            .compat_tool
+            .variant_of(&self.download.file_name)
+            .map(|variant| variant.name.clone());
+        let record = InstallRecord {
+            tool: self.compat_tool.name.clone(),
+            version: self.download.version.clone(),
+            asset: self.download.file_name.clone(),
+            asset_url: self.download.download_url.clone(),
+            variant,
+            checksum: Some(verification.checksum.clone()),
+            signature: verification.signature,
+            installed_at: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            target: target.clone(),
+            path: install_path.to_path_buf(),
+            file_list: Some(file_list),
+        };
+        manifest::record_install(manifest_path, record)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to record {} in the install manifest",
+                    install_path.display()
+                )
+            })
+    }
+
+    /// Unpacks the downloaded file into `target`, replacing any existing installation
+    async fn unpack_into(
+        &self,
+        file_path: &Path,
+        target: &AppInstallations,
+        verification: &Verification,
+    ) -> Result<PathBuf> {
+        let install_path = self.install_path(target)?;
+        let install_dir = install_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        if files::check_if_exists(&install_path).await {
+            fs::remove_dir_all(&install_path).await.with_context(|| {
+                format!(
+                    "Error removing existing install at {}",
+                    install_path.display()
+                )
+            })?;
+        }
+
+        let compressed_file = File::open(file_path)
+            .await
+            .with_context(|| format!("Error opening compressed file {}", file_path.display()))?;
+        let total = compressed_file.metadata().await?.len();
+
+        let progress = std::sync::Mutex::new((0u64, 0u64));
+        let report = |progress: (u64, u64)| {
+            self.emit(InstallEvent::Unpacking {
+                target: target.clone(),
+                install_dir: install_dir.clone(),
+                entries: progress.1,
+                done: progress.0,
+                total,
+            })
+        };
+        report((0, 0));
+
+        let progress_reader = ProgressReader::new(compressed_file, |done| {
+            let mut progress = progress.lock().unwrap();
+            progress.0 = done;
+            report(*progress);
+        });
+
+        // Wrap with BufReader to provide AsyncBufRead for the decompressor
+        let buf_reader = BufReader::new(progress_reader);
+
+        let path_str = file_path.to_string_lossy();
+        let decompressor = files::Decompressor::from_reader(buf_reader, &path_str)
+            .with_context(|| format!("Error checking file type of {}", file_path.display()))?;
+
+        let files = files::unpack_file_with_progress(
+            &self.compat_tool,
+            &self.download,
+            decompressor,
+            &install_dir,
+            |entries| {
+                let mut progress = progress.lock().unwrap();
+                progress.1 = entries;
+                report(*progress);
+            },
+        )
+        .await
+        .with_context(|| format!("Error unpacking {}", file_path.display()))?;
+
+        self.record_install(target, &install_path, verification, &files)
+            .await?;
+        Ok(install_path)
+    }
+}
+
+/// AsyncWrite wrapper reporting the total of bytes written after each write
+#[pin_project]
+struct ProgressWriter<W, F> {
+    #[pin]
+    inner: W,
+    done: u64,
+    on_progress: F,
+}
+
+impl<W: AsyncWrite, F: FnMut(u64)> ProgressWriter<W, F> {
+    fn new(inner: W, on_progress: F) -> Self {
+        ProgressWriter {
+            inner,
+            done: 0,
+            on_progress,
+        }
+    }
+}
+
+impl<W: AsyncWrite, F: FnMut(u64)> AsyncWrite for ProgressWriter<W, F> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let res = this.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = res {
+            *this.done += written as u64;
+            (this.on_progress)(*this.done);
+        }
+        res
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+/// AsyncRead wrapper reporting the total of bytes read after each read
+#[pin_project]
+struct ProgressReader<R, F> {
+    #[pin]
+    inner: R,
+    done: u64,
+    on_progress: F,
+}
+
+impl<R: AsyncRead, F: FnMut(u64)> ProgressReader<R, F> {
+    fn new(inner: R, on_progress: F) -> Self {
+        ProgressReader {
+            inner,
+            done: 0,
+            on_progress,
+        }
+    }
+}
+
+impl<R: AsyncRead, F: FnMut(u64)> AsyncRead for ProgressReader<R, F> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let before = buf.filled().len();
+        let res = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = res {
+            let read = buf.filled().len() - before;
+            if read > 0 {
+                *this.done += read as u64;
+                (this.on_progress)(*this.done);
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::{Forge, ToolType};
+    use crate::test_util::make_tar_gz;
+    use std::sync::Mutex;
+    use tempfile::{TempDir, tempdir};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// A custom target in a new temporary folder, a test tool, and the download of the asset
+    /// `file_name` served by `mock_server`, without published hash sum
+    fn setup(
+        mock_server: &MockServer,
+        file_name: &str,
+        version: &str,
+        size: u64,
+    ) -> (TempDir, AppInstallations, CompatTool, Download) {
+        let temp_dir = tempdir().unwrap();
+        let target =
+            AppInstallations::new_custom_app_install(temp_dir.path().to_string_lossy().to_string());
+        let tool = CompatTool::new_custom(
+            "TestTool".to_owned(),
+            Forge::GitHub,
+            "owner".to_owned(),
+            "repo".to_owned(),
+            ToolType::WineBased,
+            None,
+            None,
+            None,
+        );
+        let download = Download {
+            file_name: file_name.to_owned(),
+            for_app: target.clone(),
+            version: version.to_owned(),
+            hash_sum: None,
+            download_url: format!("{}/{file_name}", mock_server.uri()),
+            size,
+        };
+        (temp_dir, target, tool, download)
+    }
+
+    #[tokio::test]
+    async fn test_installer_emits_events_in_order() {
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("original_top", &[("file1.txt", "test content")]).await;
+
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.clone()))
+            .mount(&mock_server)
+            .await;
+
+        let (temp_dir, target, tool, download) = setup(
+            &mock_server,
+            "tool-1.0.tar.gz",
+            "tool-1.0",
+            archive.len() as u64,
+        );
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let recorded = events.clone();
+        let manifest_path = temp_dir.path().join("state/installed.json");
+        let installed = Installer::new(tool, download, vec![target.clone()])
+            .with_manifest_path(Some(manifest_path.clone()))
+            .on_event(move |event| recorded.lock().unwrap().push(event))
+            .run()
+            .await
+            .expect("install failed");
+
+        let expected_path = temp_dir.path().join("tool-1.0");
+        assert_eq!(installed, vec![expected_path.clone()]);
+        assert_eq!(
+            std::fs::read_to_string(expected_path.join("file1.txt")).unwrap(),
+            "test content"
+        );
+
+        let manifest = Manifest::load_from(&manifest_path).unwrap();
+        let record = manifest.find(&expected_path).expect("install not recorded");
+        assert_eq!(record.tool, "TestTool");
+        assert_eq!(record.version, "tool-1.0");
+        assert_eq!(record.asset, "tool-1.0.tar.gz");
+        assert_eq!(record.target, target);
+        let checksum = record.checksum.as_ref().unwrap();
+        assert!(!checksum.verified);
+        assert_eq!(checksum.value.len(), 64, "sha256 hex digest");
+
+        let events = events.lock().unwrap();
+        assert!(matches!(events.first(), Some(InstallEvent::Started { .. })));
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, InstallEvent::Bytes { done, total } if done == total))
+        );
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, InstallEvent::Unpacking { entries: 1, .. }))
+        );
+        assert_eq!(
+            events.last(),
+            Some(&InstallEvent::Finished {
+                target,
+                install_path: expected_path,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_installer_file_list_and_kept_archive() {
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz(
+            "original_top",
+            &[("bin/wine", "binary"), ("version", "tool-2.0")],
+        )
+        .await;
+
+        Mock::given(method("GET"))
+            .and(path("/tool-2.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.clone()))
+            // the second install reuses the kept archive
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let (temp_dir, target, tool, download) = setup(
+            &mock_server,
+            "tool-2.0.tar.gz",
+            "tool-2.0",
+            archive.len() as u64,
+        );
+        let manifest_path = temp_dir.path().join("state/installed.json");
+        let installer = Installer::new(tool, download, vec![target])
+            .with_manifest_path(Some(manifest_path.clone()))
+            .with_archive_dir(Some(temp_dir.path().join("archives")));
+
+        let install_path = installer.run().await.unwrap().remove(0);
+        assert!(
+            temp_dir
+                .path()
+                .join("archives/TestTool/tool-2.0/tool-2.0.tar.gz")
+                .is_file()
+        );
+        let manifest = Manifest::load_from(&manifest_path).unwrap();
+        let record = manifest.find(&install_path).unwrap();
+        let file_list = manifest::load_file_list(record.file_list.as_ref().unwrap()).unwrap();
+        assert_eq!(file_list.len(), 2);
+        assert!(
+            files::verify_tree(&install_path, &file_list)
+                .await
+                .unwrap()
+                .is_intact()
+        );
+
+        std::fs::write(install_path.join("version"), "edited").unwrap();
+        std::fs::remove_file(install_path.join("bin/wine")).unwrap();
+        std::fs::write(install_path.join("bin/extra"), "").unwrap();
+        let report = files::verify_tree(&install_path, &file_list).await.unwrap();
+        assert_eq!(report.modified, vec![PathBuf::from("version")]);
+        assert_eq!(report.missing, vec![PathBuf::from("bin/wine")]);
+        assert_eq!(report.extra, vec![PathBuf::from("bin/extra")]);
+
+        // reinstalling from the kept archive repairs the folder
+        let expected_checksum = record.checksum.clone().unwrap();
+        installer
+            .with_expected_checksum(expected_checksum)
+            .run()
+            .await
+            .unwrap();
+        assert!(
+            files::verify_tree(&install_path, &file_list)
+                .await
+                .unwrap()
+                .is_intact()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_installer_hashes_while_downloading() {
+        use sha2::{Digest, Sha512};
+
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("original_top", &[("file1.txt", "test content")]).await;
+        let hash = hex::encode(Sha512::digest(&archive));
+
+        Mock::given(method("GET"))
+            .and(path("/sum.sha512sum"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(format!("{hash}  tool-1.0.tar.gz")),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.clone()))
+            .mount(&mock_server)
+            .await;
+
+        let (temp_dir, target, tool, mut download) = setup(
+            &mock_server,
+            "tool-1.0.tar.gz",
+            "tool-1.0",
+            archive.len() as u64,
+        );
+        download.hash_sum = Some(hashing::HashSums {
+            sum_content: format!("{}/sum.sha512sum", mock_server.uri()),
+            sum_type: hashing::HashSumType::Sha512,
+        });
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let recorded = events.clone();
+        let manifest_path = temp_dir.path().join("state/installed.json");
+        let installed = Installer::new(tool, download, vec![target])
+            .with_manifest_path(Some(manifest_path.clone()))
+            .on_event(move |event| recorded.lock().unwrap().push(event))
+            .run()
+            .await
+            .expect("install failed");
+
+        let manifest = Manifest::load_from(&manifest_path).unwrap();
+        let checksum = manifest.find(&installed[0]).unwrap().checksum.clone();
+        assert_eq!(
+            checksum,
+            Some(Checksum {
+                algorithm: hashing::HashSumType::Sha512,
+                value: hash,
+                verified: true,
+            })
+        );
+        // the digest is computed during the download, the file is not read again
+        let total = archive.len() as u64;
+        let verifying: Vec<_> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| matches!(e, InstallEvent::Verifying { .. }))
+            .cloned()
+            .collect();
+        assert_eq!(
+            verifying,
+            vec![InstallEvent::Verifying { done: total, total }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_installer_checks_detached_signature() {
+        use crate::hashing::test_signing::MinisignKey;
+        use crate::sources::SignatureConfig;
+
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("original_top", &[("file1.txt", "test content")]).await;
+        let key = MinisignKey::new(7);
+
+        for (name, signature) in [
+            ("signed", Some(key.sign(&archive))),
+            ("forged", Some(MinisignKey::new(8).sign(&archive))),
+            ("unsigned", None),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/{name}/tool-1.0.tar.gz")))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.clone()))
+                .mount(&mock_server)
+                .await;
+            if let Some(signature) = signature {
+                Mock::given(method("GET"))
+                    .and(path(format!("/{name}/tool-1.0.tar.gz.minisig")))
+                    .respond_with(ResponseTemplate::new(200).set_body_string(signature))
+                    .mount(&mock_server)
+                    .await;
+            }
+        }
+
+        let (temp_dir, target, mut tool, download) = setup(
+            &mock_server,
+            "tool-1.0.tar.gz",
+            "tool-1.0",
+            archive.len() as u64,
+        );
+        tool.signature = Some(SignatureConfig {
+            kind: SignatureKind::Minisign,
+            asset_pattern: "{asset}.minisig".to_owned(),
+            keys: vec![key.public_key()],
+        });
+        let manifest_path = temp_dir.path().join("state/installed.json");
+        let install = |name: &str| {
+            let download = Download {
+                download_url: format!("{}/{name}/tool-1.0.tar.gz", mock_server.uri()),
+                ..download.clone()
+            };
+            Installer::new(tool.clone(), download, vec![target.clone()])
+                .with_manifest_path(Some(manifest_path.clone()))
+                .with_retries(0)
+        };
+
+        for name in ["unsigned", "forged"] {
+            let error = install(name)
+                .run()
+                .await
+                .expect_err("unverified archive installed");
+            assert!(
+                format!("{error:#}").contains("tool-1.0.tar.gz"),
+                "{error:#}"
+            );
+            assert!(!temp_dir.path().join("tool-1.0").exists());
+        }
+
+        let installed = install("signed").run().await.expect("install failed");
+        let manifest = Manifest::load_from(&manifest_path).unwrap();
+        let record = manifest.find(&installed[0]).unwrap();
+        assert_eq!(record.signature, Some(SignatureKind::Minisign));
+        assert!(record.checksum.as_ref().unwrap().verified);
+
+        // streaming is refused for signed tools with a warning, the signature is still checked
+        std::fs::remove_dir_all(&installed[0]).unwrap();
+        let warnings = Arc::new(Mutex::new(vec![]));
+        let recorded = warnings.clone();
+        let installed = install("signed")
+            .with_streaming(true)
+            .on_event(move |event| {
+                if let InstallEvent::Warning { message } = event {
+                    recorded.lock().unwrap().push(message);
+                }
+            })
+            .run()
+            .await
+            .expect("install failed");
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![
+                "TestTool is signed, tool-1.0.tar.gz is downloaded to a temporary file instead of streamed"
+            ]
+        );
+        let manifest = Manifest::load_from(&manifest_path).unwrap();
+        let record = manifest.find(&installed[0]).unwrap();
+        assert_eq!(record.signature, Some(SignatureKind::Minisign));
+    }
+
+    #[tokio::test]
+    async fn test_installer_checksum_policy_and_pinning() {
+        use sha2::{Digest, Sha256};
+
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("original_top", &[("file1.txt", "first")]).await;
+        let replaced = make_tar_gz("original_top", &[("file1.txt", "replaced")]).await;
+
+        // the asset is replaced after three downloads
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.clone()))
+            .up_to_n_times(3)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(replaced))
+            .mount(&mock_server)
+            .await;
+
+        let (temp_dir, target, tool, download) = setup(
+            &mock_server,
+            "tool-1.0.tar.gz",
+            "tool-1.0",
+            archive.len() as u64,
+        );
+        let manifest_path = temp_dir.path().join("state/installed.json");
+        let warnings = Arc::new(Mutex::new(vec![]));
+        let recorded = warnings.clone();
+        let installer = Installer::new(tool, download, vec![target])
+            .with_manifest_path(Some(manifest_path.clone()))
+            .with_retries(0)
+            .on_event(move |event| {
+                if let InstallEvent::Warning { message } = event {
+                    recorded.lock().unwrap().push(message);
+                }
+            });
+        let install_path = temp_dir.path().join("tool-1.0");
+
+        let error = installer
+            .clone()
+            .with_checksum_policy(ChecksumPolicy::Require)
+            .run()
+            .await
+            .expect_err("unverified asset installed");
+        assert!(format!("{error:#}").contains("publishes no checksum"));
+        assert!(!install_path.exists());
+
+        let installer = installer.with_checksum_policy(ChecksumPolicy::Warn);
+        installer.run().await.expect("install failed");
+        installer.run().await.expect("reinstall failed");
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![
+                "No checksum published for tool-1.0.tar.gz, its digest is pinned on first use",
+                "No checksum published for tool-1.0.tar.gz, it matches the digest of its first download",
+            ]
+        );
+        let pins = Manifest::load_from(&manifest_path).unwrap().pins;
+        assert_eq!(pins.len(), 1);
+        assert_eq!(pins[0].value, hex::encode(Sha256::digest(&archive)));
+
+        // repairing from the pinned digest doesn't make it verified
+        let recorded_checksum = || {
+            Manifest::load_from(&manifest_path)
+                .unwrap()
+                .find(&install_path)
+                .and_then(|record| record.checksum.clone())
+                .unwrap()
+        };
+        let checksum = recorded_checksum();
+        assert!(!checksum.verified);
+        installer
+            .clone()
+            .with_expected_checksum(checksum)
+            .run()
+            .await
+            .expect("repair failed");
+        assert!(!recorded_checksum().verified);
+
+        // a replaced asset is refused whatever the policy, and the installation is kept
+        let error = installer
+            .with_checksum_policy(ChecksumPolicy::Allow)
+            .with_streaming(true)
+            .run()
+            .await
+            .expect_err("replaced asset installed");
+        assert!(format!("{error:#}").contains("changed since it was first downloaded"));
+        assert_eq!(
+            std::fs::read_to_string(install_path.join("file1.txt")).unwrap(),
+            "first"
+        );
+        assert_eq!(warnings.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_installer_streaming_promotes_on_matching_digest() {
+        use sha2::{Digest, Sha512};
+
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("original_top", &[("file1.txt", "new content")]).await;
+        let hash = hex::encode(Sha512::digest(&archive));
+
+        Mock::given(method("GET"))
+            .and(path("/good.sha512sum"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(format!("{hash}  tool-1.0.tar.gz")),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/bad.sha512sum"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("0000  tool-1.0.tar.gz"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.clone()))
+            .mount(&mock_server)
+            .await;
+
+        let (temp_dir, target, tool, download) = setup(
+            &mock_server,
+            "tool-1.0.tar.gz",
+            "tool-1.0",
+            archive.len() as u64,
+        );
+        let download = |sum_file: &str| Download {
+            hash_sum: Some(hashing::HashSums {
+                sum_content: format!("{}/{sum_file}", mock_server.uri()),
+                sum_type: hashing::HashSumType::Sha512,
+            }),
+            ..download.clone()
+        };
+        let install_path = temp_dir.path().join("tool-1.0");
+        std::fs::create_dir_all(&install_path).unwrap();
+        std::fs::write(install_path.join("file1.txt"), "old content").unwrap();
+
+        // a mismatching digest leaves the existing installation untouched
+        let result = Installer::new(
+            tool.clone(),
+            download("bad.sha512sum"),
+            vec![target.clone()],
+        )
+        .with_manifest_path(None)
+        .with_archive_dir(None)
+        .with_streaming(true)
+        .run()
+        .await;
+        assert!(result.is_err(), "hash mismatch should fail the install");
+        assert_eq!(
+            std::fs::read_to_string(install_path.join("file1.txt")).unwrap(),
+            "old content"
+        );
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        let manifest_path = temp_dir.path().join("state/installed.json");
+        let events = Arc::new(Mutex::new(vec![]));
+        let recorded = events.clone();
+        let installed = Installer::new(tool, download("good.sha512sum"), vec![target])
+            .with_manifest_path(Some(manifest_path.clone()))
+            .with_archive_dir(None)
+            .with_streaming(true)
+            .on_event(move |event| recorded.lock().unwrap().push(event))
+            .run()
+            .await
+            .expect("install failed");
+        assert_eq!(installed, vec![install_path.clone()]);
+        // unsigned tools are streamed without warning
+        let events = events.lock().unwrap().clone();
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, InstallEvent::Unpacking { .. }))
+        );
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, InstallEvent::Warning { .. }))
+        );
+        assert_eq!(
+            std::fs::read_to_string(install_path.join("file1.txt")).unwrap(),
+            "new content"
+        );
+        assert!(
+            !temp_dir
+                .path()
+                .join(format!("{}tool-1.0", constants::STAGING_DIR_PREFIX))
+                .exists()
+        );
+
+        let manifest = Manifest::load_from(&manifest_path).unwrap();
+        let record = manifest.find(&install_path).unwrap();
+        assert!(record.checksum.as_ref().unwrap().verified);
+        let file_list = manifest::load_file_list(record.file_list.as_ref().unwrap()).unwrap();
+        assert!(
+            files::verify_tree(&install_path, &file_list)
+                .await
+                .unwrap()
+                .is_intact()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_installer_retries_failed_download() {
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("original_top", &[("file1.txt", "test content")]).await;
+
+        // the first attempt fails, the second one succeeds
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.clone()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let (temp_dir, target, tool, download) = setup(
+            &mock_server,
+            "tool-1.0.tar.gz",
+            "tool-1.0",
+            archive.len() as u64,
+        );
+        let events = Arc::new(Mutex::new(vec![]));
+        let recorded = events.clone();
+        let installed = Installer::new(tool, download, vec![target])
+            .with_manifest_path(None)
+            .with_archive_dir(None)
+            .with_retries(1)
+            .on_event(move |event| recorded.lock().unwrap().push(event))
+            .run()
+            .await
+            .expect("install failed after a retry");
+
+        assert_eq!(installed, vec![temp_dir.path().join("tool-1.0")]);
+        let retries: Vec<u32> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|e| match e {
+                InstallEvent::Retry { attempt, .. } => Some(*attempt),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(retries, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_installer_resumes_partial_download() {
+        use sha2::{Digest, Sha512};
+        use wiremock::matchers::header;
+
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("original_top", &[("file1.txt", "test content")]).await;
+        let hash = hex::encode(Sha512::digest(&archive));
+        let split = archive.len() / 2;
+
+        Mock::given(method("GET"))
+            .and(path("/sum.sha512sum"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(format!("{hash}  tool-1.0.tar.gz")),
+            )
+            .mount(&mock_server)
+            .await;
+        // the range is only honoured once, then the whole file is sent
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .and(header("range", format!("bytes={split}-").as_str()))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(archive[split..].to_vec()))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.clone()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let (temp_dir, target, tool, mut download) = setup(
+            &mock_server,
+            "tool-1.0.tar.gz",
+            "tool-1.0",
+            archive.len() as u64,
+        );
+        download.hash_sum = Some(hashing::HashSums {
+            sum_content: format!("{}/sum.sha512sum", mock_server.uri()),
+            sum_type: hashing::HashSumType::Sha512,
+        });
+        let installer = Installer::new(tool, download, vec![target]).with_manifest_path(None);
+        let file = temp_dir.path().join("tool-1.0.tar.gz");
+
+        // the resumed file is read again to be checked
+        std::fs::write(&file, &archive[..split]).unwrap();
+        assert_eq!(installer.download_once(&file, true).await.unwrap(), None);
+        assert_eq!(std::fs::read(&file).unwrap(), archive);
+        let checksum = installer.check(&file, None).await.unwrap();
+        assert_eq!(checksum.value, hash);
+        assert!(checksum.verified);
+
+        // without range support, the partial file is replaced
+        std::fs::write(&file, &archive[..split]).unwrap();
+        assert_eq!(
+            installer.download_once(&file, true).await.unwrap(),
+            Some(hash)
+        );
+        assert_eq!(std::fs::read(&file).unwrap(), archive);
+    }
+
+    #[tokio::test]
+    async fn test_installer_reports_validation_failure() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/sum.sha512sum"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("0000  tool-1.0.tar.gz"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"not the expected data"))
+            .mount(&mock_server)
+            .await;
+
+        let (temp_dir, target, tool, mut download) =
+            setup(&mock_server, "tool-1.0.tar.gz", "tool-1.0-failing", 21);
+        download.hash_sum = Some(hashing::HashSums {
+            sum_content: format!("{}/sum.sha512sum", mock_server.uri()),
+            sum_type: hashing::HashSumType::Sha512,
+        });
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let recorded = events.clone();
+        let result = Installer::new(tool, download, vec![target])
+            .with_manifest_path(None)
+            .with_retries(0)
+            .on_event(move |event| recorded.lock().unwrap().push(event))
+            .run()
+            .await;
+
+        assert!(result.is_err(), "hash mismatch should fail the install");
+        assert!(!temp_dir.path().join("tool-1.0-failing").exists());
+        let events = events.lock().unwrap();
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, InstallEvent::Verifying { .. }))
+        );
+        assert!(matches!(
+            events.last(),
+            Some(InstallEvent::Failed { target: None, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_installer_cancellation_cleans_up() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/tool-1.0.tar.gz"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_bytes(b"never fully downloaded")
+                    .set_delay(Duration::from_secs(30)),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let (temp_dir, target, tool, download) =
+            setup(&mock_server, "tool-1.0.tar.gz", "tool-1.0-cancelled", 22);
+
+        let token = CancellationToken::new();
+        let events = Arc::new(Mutex::new(vec![]));
+        let recorded = events.clone();
+        let installer = Installer::new(tool, download, vec![target])
+            .with_manifest_path(None)
+            .with_cancellation(token.clone())
+            .on_event(move |event| recorded.lock().unwrap().push(event));
+
+        // the temporary folders holding a partial download, left by earlier runs or not
+        let partial_downloads = || -> Vec<PathBuf> {
+            std::fs::read_dir(std::env::temp_dir())
+                .unwrap()
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|dir| {
+                    dir.file_name().is_some_and(|name| {
+                        name.to_string_lossy()
+                            .starts_with(constants::TEMP_DIR_PREFIX)
+                    }) && dir.join("tool-1.0-cancelled.download").exists()
+                })
+                .collect()
+        };
+        let before = partial_downloads();
+        let canceller = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let download_dirs: Vec<PathBuf> = partial_downloads()
+                .into_iter()
+                .filter(|dir| !before.contains(dir))
+                .collect();
+            token.cancel();
+            download_dirs
+        };
+        let (result, download_dirs) = tokio::join!(installer.run(), canceller);
+
+        let err = result.expect_err("cancelled install should fail");
+        assert!(err.is::<Cancelled>());
+        assert!(!temp_dir.path().join("tool-1.0-cancelled").exists());
+        assert_eq!(download_dirs.len(), 1, "partial download not found");
+        assert!(!download_dirs[0].exists(), "temporary folder left behind");
+        assert_eq!(
+            events.lock().unwrap().last(),
+            Some(&InstallEvent::Cancelled)
+        );
+    }
+}
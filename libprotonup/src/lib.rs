@@ -1,9 +1,17 @@
 pub mod apps;
 pub mod architecture_variants;
+pub mod config;
 pub mod constants;
+pub mod cpu;
 pub mod downloads;
 pub mod files;
 pub mod hashing;
 pub mod http_client;
+pub mod installer;
+pub mod lockfile;
+pub mod manifest;
+pub mod release_notes;
 pub mod sources;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod utils;
@@ -0,0 +1,225 @@
+//! Lock file pinning the exact releases installed in each app.
+//!
+//! A `protonup.lock` lists, for every app, the tool, release tag, asset and checksum of each
+//! installed folder. It is written from the install [`manifest`](crate::manifest), and lets
+//! other machines install exactly the same builds, checked against the recorded digests.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::apps::AppInstallations;
+use crate::downloads::Download;
+use crate::manifest::{Checksum, InstallRecord};
+use crate::utils::compare_versions;
+
+/// Version of the lock file format, increased on incompatible changes
+pub const LOCKFILE_VERSION: u32 = 1;
+
+const HEADER: &str =
+    "# Written by `protonup-rs lock`. Install these releases with `protonup-rs sync`\n\n";
+
+/// A release installed in an app
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedInstall {
+    /// App the tool is installed in: "steam", "lutris", a target name from `config.toml`,
+    /// or a custom path, see [`target_name`]
+    pub target: String,
+    /// Name of the compatibility tool, as in `sources.ron`
+    pub tool: String,
+    /// Release tag
+    pub tag: String,
+    /// Architecture variant of the asset, for tools with several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    /// File name of the release asset
+    pub asset: String,
+    pub url: String,
+    /// Size of the asset, in bytes
+    pub size: u64,
+    /// Checksum of the asset
+    pub checksum: Checksum,
+}
+
+impl LockedInstall {
+    /// Locks the folder of `record`, None if no checksum was recorded for it
+    pub fn from_record(record: &InstallRecord, size: u64) -> Option<LockedInstall> {
+        Some(LockedInstall {
+            target: target_name(&record.target),
+            tool: record.tool.clone(),
+            tag: record.version.clone(),
+            variant: record.variant.clone(),
+            asset: record.asset.clone(),
+            url: record.asset_url.clone(),
+            size,
+            checksum: record.checksum.clone()?,
+        })
+    }
+
+    /// The locked asset, to be installed into `for_app`.
+    /// It has no published hash sum, the installer must be given the locked checksum
+    pub fn download(&self, for_app: &AppInstallations) -> Download {
+        Download {
+            file_name: self.asset.clone(),
+            for_app: for_app.clone(),
+            version: self.tag.clone(),
+            hash_sum: None,
+            download_url: self.url.clone(),
+            size: self.size,
+        }
+    }
+
+    /// Whether the folder of `record` was installed from the locked asset
+    pub fn matches(&self, record: &InstallRecord) -> bool {
+        record.tool == self.tool
+            && record.version == self.tag
+            && record.asset == self.asset
+            && record.checksum.as_ref().is_some_and(|checksum| {
+                checksum.algorithm == self.checksum.algorithm
+                    && checksum.value == self.checksum.value
+            })
+    }
+}
+
+/// Content of the lock file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(default, rename = "install")]
+    pub installs: Vec<LockedInstall>,
+}
+
+impl Lockfile {
+    /// A lock file of `installs`, sorted by target and tool, newest release first.
+    /// An asset installed in several installations of the same app is listed once
+    pub fn new(mut installs: Vec<LockedInstall>) -> Lockfile {
+        installs.sort_by(|a, b| {
+            (&a.target, &a.tool)
+                .cmp(&(&b.target, &b.tool))
+                .then_with(|| compare_versions(&b.tag, &a.tag))
+                .then_with(|| a.asset.cmp(&b.asset))
+        });
+        installs.dedup_by(|a, b| {
+            a.target == b.target && a.tool == b.tool && a.tag == b.tag && a.asset == b.asset
+        });
+        Lockfile {
+            version: LOCKFILE_VERSION,
+            installs,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Lockfile> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read lock file {}", path.display()))?;
+        let lockfile: Lockfile = toml::from_str(&content)
+            .with_context(|| format!("Invalid lock file {}", path.display()))?;
+        if lockfile.version > LOCKFILE_VERSION {
+            return Err(anyhow!(
+                "Lock file {} was written by a newer version of protonup-rs",
+                path.display()
+            ));
+        }
+        Ok(lockfile)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = format!("{HEADER}{}", toml::to_string(self)?);
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write lock file {}", path.display()))
+    }
+}
+
+/// Name of an app in the lock file, as accepted by `--for`. The native and Flatpak
+/// installations of an app share a name, so a lock file works with either
+pub fn target_name(app_inst: &AppInstallations) -> String {
+    match app_inst {
+        AppInstallations::Steam | AppInstallations::SteamFlatpak => "steam".to_owned(),
+        AppInstallations::Lutris | AppInstallations::LutrisFlatpak => "lutris".to_owned(),
+        AppInstallations::Custom(path) => path.clone(),
+        AppInstallations::Target(target) => target.name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn record(target: AppInstallations, version: &str, checksum: bool) -> InstallRecord {
+        let path = PathBuf::from(format!("/tmp/{version}"));
+        let record = test_util::install_record(target, version, path);
+        InstallRecord {
+            checksum: record.checksum.filter(|_| checksum),
+            ..record
+        }
+    }
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let installs = [
+            record(AppInstallations::Steam, "GE-Proton9-2", true),
+            record(AppInstallations::SteamFlatpak, "GE-Proton9-22", true),
+            record(AppInstallations::Steam, "GE-Proton9-22", true),
+            record(
+                AppInstallations::Custom("~/proton".to_owned()),
+                "GE-Proton9-22",
+                true,
+            ),
+        ]
+        .iter()
+        .map(|record| LockedInstall::from_record(record, 42).unwrap())
+        .collect();
+        let lockfile = Lockfile::new(installs);
+
+        let tags: Vec<(&str, &str)> = lockfile
+            .installs
+            .iter()
+            .map(|install| (install.target.as_str(), install.tag.as_str()))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("steam", "GE-Proton9-22"),
+                ("steam", "GE-Proton9-2"),
+                ("~/proton", "GE-Proton9-22"),
+            ]
+        );
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("protonup.lock");
+        lockfile.save(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("[[install]]"));
+        assert!(content.contains("tag = \"GE-Proton9-22\""));
+        assert_eq!(Lockfile::load(&path).unwrap(), lockfile);
+
+        fs::write(&path, content.replace("version = 1", "version = 2")).unwrap();
+        assert!(Lockfile::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_locked_install() {
+        assert_eq!(
+            LockedInstall::from_record(
+                &record(AppInstallations::Lutris, "GE-Proton9-22", false),
+                42
+            ),
+            None
+        );
+
+        let installed = record(AppInstallations::LutrisFlatpak, "GE-Proton9-22", true);
+        let locked = LockedInstall::from_record(&installed, 42).unwrap();
+        assert_eq!(locked.target, "lutris");
+        assert!(locked.matches(&installed));
+        assert!(!locked.matches(&record(AppInstallations::Lutris, "GE-Proton9-22", false)));
+
+        let download = locked.download(&AppInstallations::Lutris);
+        assert_eq!(download.version, "GE-Proton9-22");
+        assert_eq!(download.size, 42);
+        assert_eq!(download.hash_sum, None);
+    }
+}
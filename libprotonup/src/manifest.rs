@@ -0,0 +1,410 @@
+//! Install-state manifest, recording what protonup-rs installed.
+//!
+//! Every successful unpack adds an [`InstallRecord`] to
+//! `$XDG_STATE_HOME/protonup-rs/installed.json`, with the release, asset and checksum the
+//! folder was created from. Front-ends can rely on it instead of guessing the tool and
+//! version from the folder name, and should call [`record_removal`] when deleting a folder.
+//!
+//! The files unpacked in each folder are listed with their hash in a separate file under
+//! `files/`, used by [`crate::files::verify_tree`] to find modified, missing and extra files.
+//!
+//! Assets released without a checksum are pinned on first use with [`pin_digest`], so a
+//! release asset replaced after its first download is noticed.
+
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::apps::AppInstallations;
+use crate::constants;
+use crate::files::FileEntry;
+use crate::hashing::{HashSumType, SignatureKind};
+use crate::utils;
+
+/// Version of the manifest format, increased on incompatible changes
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// An installed tool folder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstallRecord {
+    /// Name of the compatibility tool, as in `sources.ron`
+    pub tool: String,
+    /// Release tag
+    pub version: String,
+    /// File name of the release asset
+    pub asset: String,
+    pub asset_url: String,
+    /// Architecture variant of the asset, for tools with several
+    pub variant: Option<String>,
+    /// Checksum of the downloaded asset
+    pub checksum: Option<Checksum>,
+    /// Kind of the detached signature the asset was verified with, for signed tools
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureKind>,
+    /// Installation date, in RFC 3339
+    pub installed_at: String,
+    pub target: AppInstallations,
+    /// Folder the tool was unpacked into
+    pub path: PathBuf,
+    /// File listing the unpacked files with their hash, see [`load_file_list`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_list: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checksum {
+    pub algorithm: HashSumType,
+    /// Hex encoded digest
+    pub value: String,
+    /// True if it matched the hash sum published with the release
+    pub verified: bool,
+}
+
+/// Digest of a release asset without published checksum, recorded when first downloaded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetPin {
+    pub tool: String,
+    /// Release tag
+    pub version: String,
+    /// File name of the release asset
+    pub asset: String,
+    pub algorithm: HashSumType,
+    /// Hex encoded digest
+    pub value: String,
+    /// Date of the first download, in RFC 3339
+    pub first_seen: String,
+}
+
+/// Result of [`pin_digest`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PinCheck {
+    /// The asset was not seen before, its digest is now pinned
+    New,
+    /// The digest matches the pinned one
+    Matched,
+    /// The asset changed since it was pinned
+    Changed(AssetPin),
+}
+
+/// Content of the manifest file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub installs: Vec<InstallRecord>,
+    /// Digests of the unverified assets, kept after their installs are removed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pins: Vec<AssetPin>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Manifest {
+            version: MANIFEST_VERSION,
+            installs: vec![],
+            pins: vec![],
+        }
+    }
+}
+
+impl Manifest {
+    /// Location of the manifest, `$XDG_STATE_HOME/protonup-rs/installed.json`
+    pub fn path() -> Result<PathBuf> {
+        dirs::state_dir()
+            .or_else(|| dirs::home_dir().map(|home| home.join(".local/state")))
+            .map(|dir| {
+                dir.join(constants::CONFIG_DIR_NAME)
+                    .join(constants::MANIFEST_FILE_NAME)
+            })
+            .ok_or_else(|| anyhow!("Could not find the user state folder"))
+    }
+
+    /// Reads the manifest, returning an empty one if it does not exist
+    pub fn load() -> Result<Manifest> {
+        Manifest::load_from(&Manifest::path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Manifest> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Invalid install manifest {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(e) => Err(e)
+                .with_context(|| format!("Failed to read install manifest {}", path.display())),
+        }
+    }
+
+    /// Writes the manifest, replacing the file atomically
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let parent = path
+            .parent()
+            .ok_or_else(|| anyhow!("Invalid install manifest path {}", path.display()))?;
+        fs::create_dir_all(parent)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(self)?)
+            .and_then(|()| fs::rename(&temp_path, path))
+            .with_context(|| format!("Failed to write install manifest {}", path.display()))
+    }
+
+    /// The record of the folder at `path`
+    pub fn find(&self, path: &Path) -> Option<&InstallRecord> {
+        self.installs.iter().find(|record| record.path == path)
+    }
+
+    /// The records of the folders installed in `target`
+    pub fn for_target<'a>(
+        &'a self,
+        target: &'a AppInstallations,
+    ) -> impl Iterator<Item = &'a InstallRecord> {
+        self.installs
+            .iter()
+            .filter(move |record| &record.target == target)
+    }
+
+    /// Adds a record, replacing the previous one of the same folder
+    pub fn insert(&mut self, record: InstallRecord) {
+        self.remove(&record.path);
+        self.installs.push(record);
+    }
+
+    /// Removes the record of the folder at `path`, and of the folders inside it.
+    /// Returns the removed records
+    pub fn remove(&mut self, path: &Path) -> Vec<InstallRecord> {
+        let (removed, kept) = std::mem::take(&mut self.installs)
+            .into_iter()
+            .partition(|record| record.path.starts_with(path));
+        self.installs = kept;
+        removed
+    }
+}
+
+/// Writes the list of files unpacked into `install_path`, next to the manifest.
+/// Returns the path of the list
+pub fn save_file_list(
+    manifest_path: &Path,
+    install_path: &Path,
+    files: &[FileEntry],
+) -> Result<PathBuf> {
+    let parent = manifest_path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid install manifest path {}", manifest_path.display()))?;
+    let id = hex::encode(Sha256::digest(install_path.as_os_str().as_encoded_bytes()));
+    let path = parent.join("files").join(format!("{}.json", &id[..16]));
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, serde_json::to_vec(files)?)
+        .with_context(|| format!("Failed to write file list {}", path.display()))?;
+    Ok(path)
+}
+
+/// Reads a list written by [`save_file_list`]
+pub fn load_file_list(path: &Path) -> Result<Vec<FileEntry>> {
+    let content =
+        fs::read(path).with_context(|| format!("Failed to read file list {}", path.display()))?;
+    serde_json::from_slice(&content)
+        .with_context(|| format!("Invalid file list {}", path.display()))
+}
+
+/// Adds an installation to the manifest at `manifest_path`
+pub async fn record_install(manifest_path: &Path, record: InstallRecord) -> Result<()> {
+    let manifest_path = manifest_path.to_path_buf();
+    blocking(move || {
+        update(&manifest_path, |manifest| {
+            manifest.insert(record);
+            true
+        })
+    })
+    .await
+}
+
+/// Compares the digest of an asset without published checksum with the one pinned the first
+/// time this asset of this release was downloaded, pinning it if it is new.
+/// A changed asset keeps its first pin
+pub async fn pin_digest(manifest_path: &Path, pin: AssetPin) -> Result<PinCheck> {
+    let manifest_path = manifest_path.to_path_buf();
+    blocking(move || {
+        let mut check = PinCheck::New;
+        update(&manifest_path, |manifest| {
+            let pinned = manifest.pins.iter().find(|pinned| {
+                pinned.tool == pin.tool
+                    && pinned.version == pin.version
+                    && pinned.asset == pin.asset
+            });
+            match pinned {
+                Some(pinned) if pinned.algorithm == pin.algorithm && pinned.value == pin.value => {
+                    check = PinCheck::Matched;
+                    false
+                }
+                Some(pinned) => {
+                    check = PinCheck::Changed(pinned.clone());
+                    false
+                }
+                None => {
+                    manifest.pins.push(pin);
+                    true
+                }
+            }
+        })?;
+        Ok(check)
+    })
+    .await
+}
+
+/// Removes a deleted folder from the user manifest, with its file list.
+/// `path` can start with `~`
+pub async fn record_removal(path: &Path) -> Result<()> {
+    record_removal_in(&Manifest::path()?, path).await
+}
+
+/// Removes a deleted folder from the manifest at `manifest_path`, see [`record_removal`]
+pub async fn record_removal_in(manifest_path: &Path, path: &Path) -> Result<()> {
+    let path = utils::expand_tilde(path).unwrap_or_else(|| path.to_path_buf());
+    let manifest_path = manifest_path.to_path_buf();
+    blocking(move || {
+        update(&manifest_path, |manifest| {
+            let removed = manifest.remove(&path);
+            for file_list in removed
+                .iter()
+                .filter_map(|record| record.file_list.as_ref())
+            {
+                let _ = fs::remove_file(file_list);
+            }
+            !removed.is_empty()
+        })
+    })
+    .await
+}
+
+/// Runs `change` on the blocking thread pool, as taking the manifest lock waits for the
+/// other installs
+async fn blocking<T: Send + 'static>(
+    change: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(change).await?
+}
+
+/// Loads, changes and saves the manifest while holding a lock,
+/// so concurrent installs don't lose each other's records.
+/// `change` returns false when nothing was changed
+fn update(manifest_path: &Path, change: impl FnOnce(&mut Manifest) -> bool) -> Result<()> {
+    if let Some(parent) = manifest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let lock = lock_file(manifest_path)?;
+    let mut manifest = Manifest::load_from(manifest_path)?;
+    if change(&mut manifest) {
+        manifest.save_to(manifest_path)?;
+    }
+    lock.unlock()?;
+    Ok(())
+}
+
+fn lock_file(manifest_path: &Path) -> Result<File> {
+    let lock_path = manifest_path.with_extension("json.lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use tempfile::tempdir;
+
+    fn record(path: &Path, version: &str) -> InstallRecord {
+        test_util::install_record(AppInstallations::Steam, version, path.join(version))
+    }
+
+    #[tokio::test]
+    async fn test_record_install_and_removal() {
+        let dir = tempdir().unwrap();
+        let manifest_path = dir.path().join("state/installed.json");
+
+        record_install(&manifest_path, record(dir.path(), "GE-Proton10-1"))
+            .await
+            .unwrap();
+        record_install(&manifest_path, record(dir.path(), "GE-Proton10-2"))
+            .await
+            .unwrap();
+        // installing again replaces the record
+        record_install(&manifest_path, record(dir.path(), "GE-Proton10-2"))
+            .await
+            .unwrap();
+
+        let mut manifest = Manifest::load_from(&manifest_path).unwrap();
+        assert_eq!(manifest.version, MANIFEST_VERSION);
+        assert_eq!(manifest.installs.len(), 2);
+        assert_eq!(
+            manifest
+                .find(&dir.path().join("GE-Proton10-1"))
+                .map(|r| r.version.as_str()),
+            Some("GE-Proton10-1")
+        );
+        assert_eq!(manifest.for_target(&AppInstallations::Steam).count(), 2);
+        assert_eq!(manifest.for_target(&AppInstallations::Lutris).count(), 0);
+
+        assert_eq!(manifest.remove(&dir.path().join("GE-Proton10-1")).len(), 1);
+        assert!(
+            manifest
+                .remove(&dir.path().join("GE-Proton10-1"))
+                .is_empty()
+        );
+        assert_eq!(manifest.installs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pin_digest() {
+        let dir = tempdir().unwrap();
+        let manifest_path = dir.path().join("state/installed.json");
+        let pin = |value: &str| AssetPin {
+            tool: "Luxtorpeda".to_owned(),
+            version: "v70".to_owned(),
+            asset: "luxtorpeda-v70.tar.xz".to_owned(),
+            algorithm: HashSumType::Sha256,
+            value: value.to_owned(),
+            first_seen: "2026-01-01T00:00:00Z".to_owned(),
+        };
+
+        assert_eq!(
+            pin_digest(&manifest_path, pin("aa")).await.unwrap(),
+            PinCheck::New
+        );
+        assert_eq!(
+            pin_digest(&manifest_path, pin("aa")).await.unwrap(),
+            PinCheck::Matched
+        );
+        assert_eq!(
+            pin_digest(&manifest_path, pin("bb")).await.unwrap(),
+            PinCheck::Changed(pin("aa"))
+        );
+        // other releases are pinned separately
+        let other = AssetPin {
+            version: "v71".to_owned(),
+            ..pin("bb")
+        };
+        assert_eq!(
+            pin_digest(&manifest_path, other).await.unwrap(),
+            PinCheck::New
+        );
+
+        // pins outlive the installs
+        record_install(&manifest_path, record(dir.path(), "v70"))
+            .await
+            .unwrap();
+        update(&manifest_path, |manifest| {
+            !manifest.remove(&dir.path().join("v70")).is_empty()
+        })
+        .unwrap();
+        let manifest = Manifest::load_from(&manifest_path).unwrap();
+        assert!(manifest.installs.is_empty());
+        assert_eq!(manifest.pins.len(), 2);
+    }
+}
@@ -0,0 +1,157 @@
+//! What's new since an installed version.
+//!
+//! [`installed_version`] finds the newest version of a tool installed in an app, and
+//! [`releases_since`] the releases published after it, so the notes of every release
+//! skipped by an update can be shown, not only the ones of the latest release.
+
+use std::cmp::Ordering;
+
+use crate::apps::AppInstallations;
+use crate::config;
+use crate::downloads::Release;
+use crate::files;
+use crate::manifest::Manifest;
+use crate::sources::CompatTool;
+use crate::utils::compare_versions;
+
+/// The newest version of `compat_tool` installed in `app_inst`, None if there is none.
+///
+/// The version of each folder is read from the install `manifest`, and otherwise from the
+/// folder name: with the tool templates, then with the installation name of each release
+/// of `r_list`
+pub async fn installed_version(
+    app_inst: &AppInstallations,
+    compat_tool: &CompatTool,
+    r_list: &[Release],
+    manifest: &Manifest,
+) -> Option<String> {
+    let install_dir = app_inst.installation_dir(compat_tool)?;
+    let folders = files::list_folders_in_path(&install_dir).await.ok()?;
+    folders
+        .iter()
+        .filter_map(|folder| {
+            let recorded = manifest
+                .find(&install_dir.join(folder))
+                .filter(|record| record.tool == compat_tool.name)
+                .map(|record| record.version.clone());
+            recorded
+                .or_else(|| compat_tool.version_from_installation_name(folder))
+                .or_else(|| {
+                    r_list
+                        .iter()
+                        .find(|rel| compat_tool.installation_name(&rel.tag_name) == *folder)
+                        .map(|rel| rel.tag_name.clone())
+                })
+        })
+        .max_by(|a, b| compare_versions(a, b))
+}
+
+/// The releases of `r_list` newer than the `installed` version, newest first as listed by
+/// the forge. Pre-releases are skipped, unless the configuration includes them for
+/// `compat_tool`, see [`config::Config::includes_prereleases`]
+pub fn releases_since<'a>(
+    r_list: &'a [Release],
+    compat_tool: &CompatTool,
+    installed: &str,
+) -> Vec<&'a Release> {
+    let include_prereleases = config::current().includes_prereleases(compat_tool);
+    r_list
+        .iter()
+        .filter(|rel| include_prereleases || !rel.prerelease)
+        .filter(|rel| compare_versions(&rel.tag_name, installed) == Ordering::Greater)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::InstallRecord;
+    use serde_json::json;
+    use std::str::FromStr;
+    use tempfile::tempdir;
+
+    fn releases(tags: &[(&str, bool)]) -> Vec<Release> {
+        tags.iter()
+            .map(|(tag, prerelease)| {
+                serde_json::from_value(json!({
+                    "tag_name": tag,
+                    "name": tag,
+                    "url": null,
+                    "assets": [],
+                    "prerelease": prerelease
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn tags(r_list: Vec<&Release>) -> Vec<&str> {
+        r_list.iter().map(|rel| rel.tag_name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_releases_since() {
+        let geproton = CompatTool::from_str("GEProton").unwrap();
+        let r_list = releases(&[
+            ("GE-Proton10-1-rc1", true),
+            ("GE-Proton9-22", false),
+            ("GE-Proton9-20", false),
+            ("GE-Proton9-16", false),
+            ("GE-Proton9-15", false),
+            ("GE-Proton9-2", false),
+        ]);
+
+        assert_eq!(
+            tags(releases_since(&r_list, &geproton, "GE-Proton9-15")),
+            vec!["GE-Proton9-22", "GE-Proton9-20", "GE-Proton9-16"]
+        );
+        assert!(releases_since(&r_list, &geproton, "GE-Proton9-22").is_empty());
+        // an installed version missing from the list
+        assert_eq!(
+            tags(releases_since(&r_list, &geproton, "GE-Proton9-18")),
+            vec!["GE-Proton9-22", "GE-Proton9-20"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_installed_version() {
+        let dir = tempdir().unwrap();
+        let app_inst = AppInstallations::Custom(dir.path().to_string_lossy().into_owned());
+        let luxtorpeda = CompatTool::from_str("Luxtorpeda").unwrap();
+        let manifest = Manifest::default();
+
+        assert_eq!(
+            installed_version(&app_inst, &luxtorpeda, &[], &manifest).await,
+            None
+        );
+
+        for folder in ["Luxtorpeda-v72.0", "Luxtorpeda-v76.2.0", "other-tool"] {
+            std::fs::create_dir(dir.path().join(folder)).unwrap();
+        }
+        assert_eq!(
+            installed_version(&app_inst, &luxtorpeda, &[], &manifest).await,
+            Some("v76.2.0".to_owned())
+        );
+
+        // the manifest is used first, the folder was renamed
+        std::fs::create_dir(dir.path().join("lux-latest")).unwrap();
+        let mut manifest = Manifest::default();
+        manifest.insert(InstallRecord {
+            tool: luxtorpeda.name.clone(),
+            version: "v77.0".to_owned(),
+            asset: "luxtorpeda-v77.0.tar.xz".to_owned(),
+            asset_url: "https://example.com/luxtorpeda-v77.0.tar.xz".to_owned(),
+            variant: None,
+            checksum: None,
+            signature: None,
+            installed_at: "2026-01-01T00:00:00Z".to_owned(),
+            target: app_inst.clone(),
+            path: dir.path().join("lux-latest"),
+            file_list: None,
+        });
+        assert_eq!(
+            installed_version(&app_inst, &luxtorpeda, &[], &manifest).await,
+            Some("v77.0".to_owned())
+        );
+    }
+}
@@ -1,4 +1,7 @@
 use super::apps;
+use crate::architecture_variants::Host;
+use crate::cpu;
+use crate::hashing::SignatureKind;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::{fmt, str::FromStr};
@@ -28,6 +31,10 @@ pub struct CompatTool {
     pub compatible_applications: Vec<apps::App>,
     /// ToolType can be used to change how it is installed
     pub tool_type: ToolType,
+    /// CPU architectures the tool is built for, as in `std::env::consts::ARCH`.
+    /// The tool is hidden on the others
+    #[serde(default = "default_architectures")]
+    pub architectures: Vec<String>,
 
     /// release asset filter is a regex to filter out uwanted release assets
     pub release_asset_filter: Option<String>,
@@ -41,9 +48,127 @@ pub struct CompatTool {
     /// it is applied after the replacement, if Some()
     pub file_name_template: Option<String>,
 
-    /// if true, a menu to choose the variant to download will be shown (for proton cachyos: x86_64, x86_64_v2, x86_64_v3, x86_64_v4)
+    /// Builds published in every release, a menu to choose one is shown
+    /// (for proton cachyos: x86_64, x86_64_v2, x86_64_v3, x86_64_v4).
+    /// Assets matching none of them are not offered
     #[serde(default)]
-    pub has_multiple_asset_variations: bool,
+    pub variants: Vec<AssetVariant>,
+
+    /// Detached signature published next to the release assets.
+    /// When set, assets without a valid signature from a trusted key are refused
+    #[serde(default)]
+    pub signature: Option<SignatureConfig>,
+}
+
+/// Tools are built for x86_64 unless they declare otherwise
+fn default_architectures() -> Vec<String> {
+    vec!["x86_64".to_owned()]
+}
+
+/// A build of a tool published in each release, like a CPU microarchitecture level,
+/// a flavor or a debug build
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AssetVariant {
+    /// Name shown in the menus and used by `default_variant`, like "x86_64_v3"
+    pub name: String,
+    /// Regex matching the file names of the assets of this variant
+    pub pattern: String,
+    pub description: String,
+    /// The supported variant with the highest priority is installed by default
+    #[serde(default)]
+    pub priority: i32,
+    /// Systems the variant runs on, it is not offered on the others
+    #[serde(default)]
+    pub requires: VariantRequirements,
+}
+
+impl AssetVariant {
+    /// Whether the asset `file_name` is a build of this variant.
+    /// False if the pattern does not compile
+    pub fn matches(&self, file_name: &str) -> bool {
+        regex::Regex::new(&self.pattern).is_ok_and(|re| re.is_match(file_name))
+    }
+}
+
+/// Conditions a [`Host`] must meet to run a variant. Every host meets the empty ones
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct VariantRequirements {
+    /// CPU architecture, as in `std::env::consts::ARCH`: "x86_64" or "aarch64"
+    pub arch: Option<String>,
+    /// Minimum x86-64 microarchitecture level, 1 to 4
+    pub cpu_level: Option<u8>,
+    /// Minimum version of the system glibc, like "2.35"
+    pub glibc: Option<String>,
+}
+
+impl VariantRequirements {
+    pub fn is_met_by(&self, host: &Host) -> bool {
+        self.unmet_by(host).is_empty()
+    }
+
+    /// The requirements `host` doesn't meet
+    pub fn unmet_by(&self, host: &Host) -> Vec<UnmetRequirement> {
+        let mut unmet = vec![];
+        if let Some(arch) = self.arch.as_ref().filter(|arch| **arch != host.arch) {
+            unmet.push(UnmetRequirement::Arch(arch.clone()));
+        }
+        if let Some(min) = self
+            .cpu_level
+            .filter(|min| host.cpu_level.is_none_or(|level| level < *min))
+        {
+            unmet.push(UnmetRequirement::CpuLevel(min));
+        }
+        if let Some(min) = self.glibc.as_ref().filter(|min| {
+            host.glibc
+                .as_deref()
+                .is_none_or(|version| !version_at_least(version, min))
+        }) {
+            unmet.push(UnmetRequirement::Glibc(min.clone()));
+        }
+        unmet
+    }
+}
+
+/// A [`VariantRequirements`] field a [`Host`] doesn't meet, with the required value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnmetRequirement {
+    Arch(String),
+    CpuLevel(u8),
+    Glibc(String),
+}
+
+impl fmt::Display for UnmetRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnmetRequirement::Arch(arch) => write!(f, "arch {arch}"),
+            UnmetRequirement::CpuLevel(level) => write!(f, "cpu_level {level}"),
+            UnmetRequirement::Glibc(version) => write!(f, "glibc {version}"),
+        }
+    }
+}
+
+/// Compares dotted numeric versions, "2.39" is at least "2.35"
+fn version_at_least(version: &str, min: &str) -> bool {
+    let parse = |v: &str| -> Vec<u32> { v.split('.').map(|n| n.parse().unwrap_or(0)).collect() };
+    parse(version) >= parse(min)
+}
+
+/// How the release assets of a tool are signed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignatureConfig {
+    pub kind: SignatureKind,
+    /// Name of the signature asset, where "{asset}" is replaced by the name of the signed
+    /// archive. Example: "{asset}.minisig", "{asset}.asc" or "{asset}.sig"
+    pub asset_pattern: String,
+    /// Trusted public keys: minisign public keys, or ASCII armored OpenPGP keys
+    pub keys: Vec<String>,
+}
+
+impl SignatureConfig {
+    /// Name of the signature asset of `file_name`
+    pub fn asset_name(&self, file_name: &str) -> String {
+        self.asset_pattern.replace("{asset}", file_name)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -102,7 +227,9 @@ impl CompatTool {
             release_asset_filter,
             file_name_replacement,
             file_name_template,
-            has_multiple_asset_variations: false,
+            architectures: default_architectures(),
+            variants: vec![],
+            signature: None,
             compatible_applications: vec![], // TODO: fill this if it becomes helpful
         }
     }
@@ -121,15 +248,74 @@ impl CompatTool {
         name
     }
 
+    /// Reverses `installation_name`, returning the version an installed folder was created from.
+    /// Returns None if the folder name can't come from this tool.
+    /// Tools without a `file_name_template` are matched with their `release_asset_filter`,
+    /// and never match if they have none.
+    pub fn version_from_installation_name(&self, name: &str) -> Option<String> {
+        let version = match &self.file_name_template {
+            Some(template) => {
+                let (prefix, suffix) = template.split_once("{version}")?;
+                name.strip_prefix(prefix)?.strip_suffix(suffix)?
+            }
+            None => {
+                self.release_asset_filter.as_ref()?;
+                if !["tar.gz", "tar.xz", "tar.zst"]
+                    .iter()
+                    .any(|ext| self.filter_asset(&format!("{name}.{ext}")))
+                {
+                    return None;
+                }
+                name
+            }
+        };
+        if version.is_empty() {
+            return None;
+        }
+        Some(match &self.file_name_replacement {
+            Some((from, to)) => version.replace(to, from),
+            None => version.to_owned(),
+        })
+    }
+
+    /// Whether the releases have several builds to choose from, see [`CompatTool::variants`]
+    pub fn has_variants(&self) -> bool {
+        !self.variants.is_empty()
+    }
+
+    /// The variant the asset `file_name` is a build of
+    pub fn variant_of(&self, file_name: &str) -> Option<&AssetVariant> {
+        self.variants
+            .iter()
+            .find(|variant| variant.matches(file_name))
+    }
+
+    /// The tools compatible with `app` that are built for the target architecture,
+    /// see [`cpu::target_arch`]
     pub fn sources_for_app(app: &apps::App) -> Vec<CompatTool> {
+        let arch = cpu::target_arch();
         CompatTools
             .iter()
             .cloned()
             .to_owned()
-            .filter(move |s| s.compatible_applications.contains(app))
+            .filter(move |s| app.is_compatible(s) && s.supports_arch(&arch))
             .collect()
     }
 
+    /// Whether the tool is built for `arch`
+    pub fn supports_arch(&self, arch: &str) -> bool {
+        self.architectures.iter().any(|supported| supported == arch)
+    }
+
+    /// Whether the release asset `file_name` can be installed on `arch`: it names `arch`, or
+    /// names no architecture, like most tools publishing a single build, and the tool supports it
+    pub fn asset_supports_arch(&self, file_name: &str, arch: &str) -> bool {
+        match cpu::asset_arch(file_name) {
+            Some(asset_arch) => asset_arch == arch,
+            None => self.supports_arch(arch),
+        }
+    }
+
     /// filter_asset executes a regex on the file name to determine if the asset found matches
     /// returns true if No filter defined, and false if the filter does not compile
     pub fn filter_asset(&self, path: &str) -> bool {
@@ -262,4 +448,87 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_version_from_installation_name() {
+        let tool = |name: &str| name.parse::<CompatTool>().unwrap();
+
+        let luxtorpeda = tool("Luxtorpeda");
+        assert_eq!(
+            luxtorpeda.version_from_installation_name(&luxtorpeda.installation_name("v72.0")),
+            Some("v72.0".to_owned())
+        );
+        assert_eq!(
+            luxtorpeda.version_from_installation_name("Boxtron-0.5.4"),
+            None
+        );
+
+        let lutris_vkd3d = tool("Lutris-VKD3D");
+        let folder = lutris_vkd3d.installation_name("v2.14-Proton");
+        assert_eq!(folder, "VKD3D-Lutris-v2.14-Wine");
+        assert_eq!(
+            lutris_vkd3d.version_from_installation_name(&folder),
+            Some("v2.14-Proton".to_owned())
+        );
+
+        let ge_proton = tool("GEProton");
+        assert_eq!(
+            ge_proton.version_from_installation_name("GE-Proton9-20"),
+            Some("GE-Proton9-20".to_owned())
+        );
+        assert_eq!(
+            ge_proton.version_from_installation_name("GE-Proton9-20-rtsp17"),
+            None
+        );
+
+        // no template and no asset filter: can't tell
+        assert_eq!(
+            tool("WineGE (Deprecated)").version_from_installation_name("lutris-GE-Proton8-26"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_asset_variants() {
+        for tool in CompatTools.iter() {
+            for variant in &tool.variants {
+                assert!(
+                    regex::Regex::new(&variant.pattern).is_ok(),
+                    "invalid pattern of {} {}",
+                    tool.name,
+                    variant.name
+                );
+            }
+        }
+
+        let cachyos = CompatTool::from_str("Proton CachyOS").unwrap();
+        assert!(cachyos.has_variants());
+        for (file_name, variant) in [
+            ("proton-cachyos-10.0-20250714-slr-x86_64.tar.xz", "x86_64"),
+            (
+                "proton-cachyos-10.0-20250714-slr-x86_64_v2.tar.xz",
+                "x86_64_v2",
+            ),
+            (
+                "proton-cachyos-10.0-20250714-slr-x86_64_v3.tar.xz",
+                "x86_64_v3",
+            ),
+            (
+                "proton-cachyos-10.0-20250714-slr-x86_64_v4.tar.xz",
+                "x86_64_v4",
+            ),
+        ] {
+            assert!(cachyos.filter_asset(file_name));
+            assert_eq!(
+                cachyos.variant_of(file_name).map(|v| v.name.as_str()),
+                Some(variant)
+            );
+        }
+        assert!(
+            cachyos
+                .variant_of("proton-cachyos-10.0-20250714-slr.tar.xz")
+                .is_none()
+        );
+        assert!(!CompatTool::from_str("GEProton").unwrap().has_variants());
+    }
 }
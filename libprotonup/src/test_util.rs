@@ -0,0 +1,58 @@
+//! Fixtures shared by the tests of libprotonup and of the crates using it,
+//! enabled with the `test-util` feature.
+
+use std::path::PathBuf;
+
+use async_compression::tokio::write::GzipEncoder;
+use tokio::io::AsyncWriteExt;
+
+use crate::apps::AppInstallations;
+use crate::hashing::HashSumType;
+use crate::manifest::{Checksum, InstallRecord};
+
+/// A gzipped tar archive holding `files`, as (path, content), in the folder `top_level`
+pub async fn make_tar_gz(top_level: &str, files: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tokio_tar::Builder::new(Vec::new());
+    for (name, content) in files {
+        let mut header = tokio_tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(
+                &mut header,
+                format!("{top_level}/{name}"),
+                content.as_bytes(),
+            )
+            .await
+            .unwrap();
+    }
+    let tar = builder.into_inner().await.unwrap();
+
+    let mut encoder = GzipEncoder::new(Vec::new());
+    encoder.write_all(&tar).await.unwrap();
+    encoder.shutdown().await.unwrap();
+    encoder.into_inner()
+}
+
+/// Manifest record of GEProton `version`, installed into `path` of `target` from the asset
+/// `<version>.tar.gz` with a verified SHA-512 checksum, and without file list
+pub fn install_record(target: AppInstallations, version: &str, path: PathBuf) -> InstallRecord {
+    InstallRecord {
+        tool: "GEProton".to_owned(),
+        version: version.to_owned(),
+        asset: format!("{version}.tar.gz"),
+        asset_url: format!("https://example.com/{version}.tar.gz"),
+        variant: None,
+        checksum: Some(Checksum {
+            algorithm: HashSumType::Sha512,
+            value: "abcd".to_owned(),
+            verified: true,
+        }),
+        signature: None,
+        installed_at: "2026-01-01T00:00:00Z".to_owned(),
+        target,
+        path,
+        file_list: None,
+    }
+}
@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
@@ -18,7 +19,7 @@ fn build_fallback_dir(base_dir: &Path) -> PathBuf {
 /// .local/state/protonup-rs/tmp. Creates the fallback directory if it doesn't exist.
 pub fn get_temp_dir() -> std::io::Result<PathBuf> {
     // Check available space on tmp dir
-    let tmp_path = TempDir::with_prefix("protonup-rs-").map(|dir| dir.keep());
+    let tmp_path = TempDir::with_prefix(constants::TEMP_DIR_PREFIX).map(|dir| dir.keep());
 
     if let Ok(temp_dir) = tmp_path {
         let available_space = fs4::available_space(&temp_dir).unwrap_or(0);
@@ -169,6 +170,95 @@ pub fn match_version(version_str: &str, tag_name: &str) -> bool {
     false
 }
 
+/// A run of digits or of other characters in a version string
+#[derive(Debug, PartialEq, Eq)]
+enum VersionChunk<'a> {
+    Number(u64),
+    Text(&'a str),
+}
+
+fn version_chunks(version: &str) -> Vec<VersionChunk<'_>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let bytes = version.as_bytes();
+    while start < bytes.len() {
+        let is_digit = bytes[start].is_ascii_digit();
+        let end = bytes[start..]
+            .iter()
+            .position(|b| b.is_ascii_digit() != is_digit)
+            .map_or(bytes.len(), |len| start + len);
+        let chunk = &version[start..end];
+        chunks.push(match chunk.parse() {
+            Ok(number) if is_digit => VersionChunk::Number(number),
+            _ => VersionChunk::Text(chunk),
+        });
+        start = end;
+    }
+    chunks
+}
+
+/// True for the text that starts a pre-release suffix, like "-rc" or ".beta"
+fn is_prerelease_marker(text: &str) -> bool {
+    let marker = text
+        .trim_start_matches(['-', '.', '_', '+', '~'])
+        .to_lowercase();
+    ["rc", "alpha", "beta", "pre"]
+        .iter()
+        .any(|prefix| marker.starts_with(prefix))
+}
+
+/// Compares two release tags of the same tool.
+///
+/// Digit runs are compared as numbers and the text between them as text,
+/// so "GE-Proton10-1" is newer than "GE-Proton9-27", and "v2.10" newer than "v2.9".
+/// A tag followed by a pre-release suffix (rc, alpha, beta, pre) is older than the plain tag.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let a_chunks = version_chunks(a);
+    let b_chunks = version_chunks(b);
+
+    for (a_chunk, b_chunk) in a_chunks.iter().zip(&b_chunks) {
+        let ordering = match (a_chunk, b_chunk) {
+            (VersionChunk::Number(a), VersionChunk::Number(b)) => a.cmp(b),
+            (VersionChunk::Text(a), VersionChunk::Text(b)) if a != b => {
+                match (is_prerelease_marker(a), is_prerelease_marker(b)) {
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    _ => a.cmp(b),
+                }
+            }
+            (VersionChunk::Text(_), VersionChunk::Text(_)) => Ordering::Equal,
+            // a number where the other has text, as in "1.0.1" against "1.0-rc1"
+            (VersionChunk::Number(_), VersionChunk::Text(b)) => {
+                if is_prerelease_marker(b) {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            }
+            (VersionChunk::Text(a), VersionChunk::Number(_)) => {
+                if is_prerelease_marker(a) {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    // one is a prefix of the other: the longer one is newer, unless it adds a pre-release suffix
+    let longer_is_prerelease = |rest: &[VersionChunk]| matches!(rest.first(), Some(VersionChunk::Text(text)) if is_prerelease_marker(text));
+    match a_chunks.len().cmp(&b_chunks.len()) {
+        Ordering::Equal => Ordering::Equal,
+        Ordering::Greater if longer_is_prerelease(&a_chunks[b_chunks.len()..]) => Ordering::Less,
+        Ordering::Greater => Ordering::Greater,
+        Ordering::Less if longer_is_prerelease(&b_chunks[a_chunks.len()..]) => Ordering::Greater,
+        Ordering::Less => Ordering::Less,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,4 +374,41 @@ mod tests {
         // consecutive numeric components
         assert!(match_version("26.20", "GE-Proton10-26-rtsp20"));
     }
+
+    // Format: (older, newer)
+    const ORDERING_TEST_CASES: &[(&str, &str)] = &[
+        ("GE-Proton9-27", "GE-Proton10-1"),
+        ("GE-Proton10-9", "GE-Proton10-10"),
+        ("GE-Proton8-26", "GE-Proton8-27-LoL"),
+        ("v2.9", "v2.10"),
+        ("v2.7", "v2.7.1"),
+        ("0.5.4", "0.5.10"),
+        ("cachyos-10.0-20250714-slr", "cachyos-10.0-20260228-slr"),
+        ("cachyos-9.0-20260228-slr", "cachyos-10.0-20250714-slr"),
+        ("GE-Proton10-1-rc1", "GE-Proton10-1"),
+        ("GE-Proton10-1-rc1", "GE-Proton10-1-rc2"),
+        ("1.0-rc1", "1.0.1"),
+        ("v3.0-beta", "v3.0"),
+        ("8.7-GE-1-LoL", "8.7-GE-2-LoL"),
+    ];
+
+    #[test]
+    fn test_compare_versions() {
+        for (older, newer) in ORDERING_TEST_CASES {
+            assert_eq!(
+                compare_versions(older, newer),
+                Ordering::Less,
+                "expected {older} < {newer}"
+            );
+            assert_eq!(
+                compare_versions(newer, older),
+                Ordering::Greater,
+                "expected {newer} > {older}"
+            );
+        }
+        assert_eq!(
+            compare_versions("GE-Proton10-1", "GE-Proton10-1"),
+            Ordering::Equal
+        );
+    }
 }
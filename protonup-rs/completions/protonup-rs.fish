@@ -1,7 +1,222 @@
-complete -c protonup-rs -l tool -d 'Compatibility tool to install (e.g., GEProton, Luxtorpeda)' -r
-complete -c protonup-rs -l version -d 'Version to install (use "latest" for the latest version)' -r
-complete -c protonup-rs -l for -d 'Target for installation. Use "steam", "lutris", or a custom path. If omitted, auto-detects Steam or Lutris' -r
-complete -c protonup-rs -s q -l quick-download -d 'Skip Menu, auto detect apps and download using default parameters'
-complete -c protonup-rs -s f -l force -d 'Force install for existing apps during quick downloads'
-complete -c protonup-rs -s w -l whats-new -d 'Show release notes for latest versions of default tools'
-complete -c protonup-rs -s h -l help -d 'Print help'
+# Print an optspec for argparse to handle cmd's options that are independent of any subcommand.
+function __fish_protonup_rs_global_optspecs
+    string join \n q/quick-download f/force tool= version= for= w/whats-new stream require-checksum include-prereleases arch= variant= non-interactive output= h/help
+end
+
+function __fish_protonup_rs_needs_command
+    # Figure out if the current invocation already has a command.
+    set -l cmd (commandline -opc)
+    set -e cmd[1]
+    argparse -s (__fish_protonup_rs_global_optspecs) -- $cmd 2>/dev/null
+    or return
+    if set -q argv[1]
+        # Also print the command, so this can be used to figure out what it is.
+        echo $argv[1]
+        return 1
+    end
+    return 0
+end
+
+function __fish_protonup_rs_using_subcommand
+    set -l cmd (__fish_protonup_rs_needs_command)
+    test -z "$cmd"
+    and return 1
+    contains -- $cmd[1] $argv
+end
+
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l tool -d 'Compatibility tool to install (e.g., GEProton, Luxtorpeda)' -r
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l version -d 'Version to install (use "latest" for the latest version)' -r
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l for -d 'Target for installation. Use "steam", "lutris", a target name from config.toml, or a custom path. If omitted, auto-detects Steam or Lutris' -r
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -s q -l quick-download -d 'Skip Menu, auto detect apps and download using default parameters'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -s f -l force -d 'Force install for existing apps during quick downloads'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -s w -l whats-new -d 'Show release notes for latest versions of default tools'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "tui" -d 'Open the full-screen interface: detected apps, installed versions and available releases side by side, with their release notes and the progress of the installs'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "list" -d 'List the available releases of a compatibility tool'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "installed" -d 'Show every installed compatibility tool, in every detected app'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "outdated" -d 'Check whether newer releases of the installed tools are available. Exits with status 100 when at least one update is available'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "verify" -d 'Check the installed files against the hashes recorded at install time. Exits with status 1 when an installation is damaged'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "changelog" -d 'Show the release notes of every release newer than the installed version of a tool'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "lock" -d 'Write a lock file listing the release, asset and checksum of every installed tool, to install exactly the same builds on other machines with `sync`'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "sync" -d 'Install the releases listed in a lock file, checked against the locked checksums. Use `lock --update` to move the lock file to newer releases'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "config" -d 'Read or change the settings in ~/.config/protonup-rs/config.toml'
+complete -c protonup-rs -n "__fish_protonup_rs_needs_command" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand tui" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand tui" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand tui" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand tui" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand tui" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand tui" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand tui" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand tui" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -l tool -d 'Compatibility tool to list the releases of (e.g., GEProton, Luxtorpeda)' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -l limit -d 'Show at most this many releases, newest first' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand list" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand installed" -l path -d 'Also inspect this custom installation folder. Can be repeated' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand installed" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand installed" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand installed" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand installed" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand installed" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand installed" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand installed" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand installed" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand outdated" -l path -d 'Also check this custom installation folder. Can be repeated' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand outdated" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand outdated" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand outdated" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand outdated" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand outdated" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand outdated" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand outdated" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand outdated" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -l for -d 'Only verify the installations in this target: "steam", "lutris", a target name from config.toml, or a custom path' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -l repair -d 'Reinstall the damaged installations, reusing the kept archive when there is one'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand verify" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l tool -d 'Compatibility tool to show the release notes of (e.g., GEProton, Luxtorpeda)' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l since -d 'Show the releases newer than this version. Defaults to the newest installed version' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l for -d 'Only look for the installed version in this target: "steam", "lutris", a target name from config.toml, or a custom path' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l collapse -d 'Only show the first line of the notes of each release'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand changelog" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l file -d 'Lock file to write' -r -F
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l for -d 'Only lock the installations in this target: "steam", "lutris", a target name from config.toml, or a custom path' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l update -d 'Install the newest release of each locked tool and lock it in place of the newest locked release, keeping the other locked releases'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand lock" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l file -d 'Lock file to install the releases of' -r -F
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l locked -d 'Fail instead of reinstalling the installed folders that differ from the lock file, like modified files or another asset. Missing releases are still installed'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l prune -d 'Remove the installed tools that are not in the lock file, in the locked targets. Folders that don\'t match any known tool are kept'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand sync" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -f -a "get" -d 'Print the value of a setting, or the whole configuration if no key is given'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -f -a "set" -d 'Change a setting. The value is parsed as TOML, e.g. 4, true or \'["GEProton", "Luxtorpeda"]\''
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -f -a "edit" -d 'Open the configuration file in $VISUAL or $EDITOR'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and not __fish_seen_subcommand_from get set edit help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from get" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from get" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from get" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from get" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from get" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from get" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from get" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from get" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from set" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from set" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from set" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from set" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from set" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from set" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from set" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from set" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from edit" -l arch -d 'Architecture to install the tools for instead of the one of this system, to install onto the disk of another machine. Same as the arch setting' -r -f -a "x86_64\t''
+aarch64\t''"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from edit" -l variant -d 'Variant to install for tools publishing several builds, like "x86_64_v3" for Proton CachyOS. "auto" installs the default_variant setting, or the best variant for the system. Defaults to a menu, or to "auto" when not interactive' -r
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from edit" -l output -d 'Output format. "json" and "ndjson" print machine-readable records to stdout, and require a non-interactive mode' -r -f -a "text\t'Human readable text and progress bars'
+json\t'A single JSON document printed when the command finishes'
+ndjson\t'One JSON record per line, including progress events, as they happen'"
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from edit" -l stream -d 'Unpack the archives while they are downloaded, without a temporary file. Same as the stream_install setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from edit" -l require-checksum -d 'Refuse releases that publish no checksum and are not signed. Same as checksum_policy = "require"'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from edit" -l include-prereleases -d 'Install or list the releases marked as pre-releases as the latest version. Same as the include_prereleases setting'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from edit" -l non-interactive -d 'Never prompt. Choices that would need one use their default, or fail with an error. Implied by --output json/ndjson and when stdin is not a terminal'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from edit" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from help" -f -a "get" -d 'Print the value of a setting, or the whole configuration if no key is given'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from help" -f -a "set" -d 'Change a setting. The value is parsed as TOML, e.g. 4, true or \'["GEProton", "Luxtorpeda"]\''
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from help" -f -a "edit" -d 'Open the configuration file in $VISUAL or $EDITOR'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand config; and __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "tui" -d 'Open the full-screen interface: detected apps, installed versions and available releases side by side, with their release notes and the progress of the installs'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "list" -d 'List the available releases of a compatibility tool'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "installed" -d 'Show every installed compatibility tool, in every detected app'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "outdated" -d 'Check whether newer releases of the installed tools are available. Exits with status 100 when at least one update is available'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "verify" -d 'Check the installed files against the hashes recorded at install time. Exits with status 1 when an installation is damaged'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "changelog" -d 'Show the release notes of every release newer than the installed version of a tool'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "lock" -d 'Write a lock file listing the release, asset and checksum of every installed tool, to install exactly the same builds on other machines with `sync`'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "sync" -d 'Install the releases listed in a lock file, checked against the locked checksums. Use `lock --update` to move the lock file to newer releases'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "config" -d 'Read or change the settings in ~/.config/protonup-rs/config.toml'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and not __fish_seen_subcommand_from tui list installed outdated verify changelog lock sync config help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and __fish_seen_subcommand_from config" -f -a "get" -d 'Print the value of a setting, or the whole configuration if no key is given'
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and __fish_seen_subcommand_from config" -f -a "set" -d 'Change a setting. The value is parsed as TOML, e.g. 4, true or \'["GEProton", "Luxtorpeda"]\''
+complete -c protonup-rs -n "__fish_protonup_rs_using_subcommand help; and __fish_seen_subcommand_from config" -f -a "edit" -d 'Open the configuration file in $VISUAL or $EDITOR'
@@ -1,50 +1,347 @@
 //! TUI-specific architecture variant selection menu.
 //!
-//! This module provides the TUI selection menu using `inquire`.
+//! This module provides the TUI selection menu using `inquire`, and applies `--variant`.
 //! The core variant detection logic is in `libprotonup::architecture_variants`.
 
+use std::sync::OnceLock;
+
 use anyhow::{Result, anyhow};
 use inquire::Select;
 
-use libprotonup::architecture_variants;
+use libprotonup::architecture_variants::{self, ArchitectureVariant, Host};
+use libprotonup::config;
 use libprotonup::downloads::Download;
+use libprotonup::sources::{CompatTool, UnmetRequirement};
+
+use crate::output;
+
+/// Value of `--variant` that selects the default variant instead of prompting
+const AUTO_VARIANT: &str = "auto";
 
-/// Menu for selecting proton cachyos architecture variant.
+static REQUESTED_VARIANT: OnceLock<Option<String>> = OnceLock::new();
+
+/// Sets the variant given with `--variant`, used instead of the menu
+pub(crate) fn init(variant: Option<String>) {
+    let _ = REQUESTED_VARIANT.set(variant);
+}
+
+/// Menu for selecting the variant of a release, like the proton cachyos architectures.
 ///
-/// In quick mode, returns the `_v2` variant by default (or first available).
-/// In interactive mode, shows a selection menu with descriptions.
+/// Returns the variant given with `--variant`. With "auto", in quick mode or when not
+/// interactive, returns the best variant for the system, or the configured default.
+/// Otherwise, shows a selection menu with descriptions.
+/// Variants the system can't run are only installed when named with `--variant`
 pub fn select_architecture_variant(
+    compat_tool: &CompatTool,
     release_name: &str,
     variants: Vec<Download>,
     quick_mode: bool,
 ) -> Result<Download> {
+    let host = Host::current();
+    let requested = requested_variant();
+    let prompt = !quick_mode && requested.is_none() && output::is_interactive();
+    let sorted_variants = match select(
+        compat_tool,
+        release_name,
+        variants,
+        requested.as_deref(),
+        prompt,
+        &host,
+    )? {
+        Selection::Chosen(download, note) => {
+            if let Some(note) = note {
+                output::info(note);
+            }
+            return Ok(*download);
+        }
+        Selection::Menu(sorted_variants) => sorted_variants,
+    };
+
+    let selected = Select::new(
+        format!("Select the variant of release '{release_name}' for {host}:").as_str(),
+        sorted_variants,
+    )
+    .prompt()
+    .unwrap_or_else(|_| std::process::exit(0));
+
+    Ok(selected.download)
+}
+
+/// Variant of a release installed from the full-screen interface, which can't prompt nor
+/// print: the one given with `--variant`, or the default one. Returns it with the message
+/// about the choice, to show in the interface
+pub(crate) fn select_unprompted(
+    compat_tool: &CompatTool,
+    release_name: &str,
+    variants: Vec<Download>,
+) -> Result<(Download, Option<String>)> {
+    let host = Host::current();
+    let requested = requested_variant();
+    match select(
+        compat_tool,
+        release_name,
+        variants,
+        requested.as_deref(),
+        false,
+        &host,
+    )? {
+        Selection::Chosen(download, note) => Ok((*download, note)),
+        Selection::Menu(_) => unreachable!("the menu is only returned when prompting"),
+    }
+}
+
+fn requested_variant() -> Option<String> {
+    REQUESTED_VARIANT.get().cloned().flatten()
+}
+
+/// Outcome of [`select`]
+#[derive(Debug)]
+enum Selection {
+    /// The variant, with a message about the choice if any
+    Chosen(Box<Download>, Option<String>),
+    /// The user picks one of these variants, the host can run them all
+    Menu(Vec<ArchitectureVariant>),
+}
+
+/// Picks the `requested` variant, or the default one for `host` unless the user is prompted
+fn select(
+    compat_tool: &CompatTool,
+    release_name: &str,
+    variants: Vec<Download>,
+    requested: Option<&str>,
+    prompt: bool,
+    host: &Host,
+) -> Result<Selection> {
     if variants.is_empty() {
         return Err(anyhow!("No architecture variants available"));
     }
 
-    if quick_mode && let Some(default) = architecture_variants::select_default_variant(&variants) {
-        println!(
-            "Selected {} by default",
-            architecture_variants::get_variant_name(
-                architecture_variants::get_architecture_variant(&default.file_name)
-            )
-        );
-        return Ok(default);
+    // Extract and sort variants using libprotonup
+    let all_variants = architecture_variants::extract_variants(compat_tool, &variants);
+    if all_variants.is_empty() {
+        let download = variants.into_iter().next().unwrap();
+        return Ok(Selection::Chosen(Box::new(download), None));
     }
 
-    // Extract and sort variants using libprotonup
-    let sorted_variants = architecture_variants::extract_variants(&variants);
+    if let Some(name) = requested.filter(|name| *name != AUTO_VARIANT) {
+        let Some(selected) = all_variants.iter().find(|v| v.name == name) else {
+            let names: Vec<&str> = all_variants.iter().map(|v| v.name.as_str()).collect();
+            return Err(anyhow!(
+                "Variant '{name}' is not available for '{release_name}'. Available variants: {}, {AUTO_VARIANT}",
+                names.join(", ")
+            ));
+        };
+        let supported = compat_tool
+            .variant_of(&selected.download.file_name)
+            .is_some_and(|variant| variant.requires.is_met_by(host));
+        let note = (!supported).then(|| format!("Warning: {name} is not supported by {host}"));
+        return Ok(Selection::Chosen(Box::new(selected.download.clone()), note));
+    }
 
+    let sorted_variants = architecture_variants::supported_variants(compat_tool, &variants, host);
     if sorted_variants.is_empty() {
-        return Ok(variants.into_iter().next().unwrap());
+        return Err(no_supported_variant(
+            compat_tool,
+            release_name,
+            &all_variants,
+            host,
+        ));
+    }
+    if prompt {
+        return Ok(Selection::Menu(sorted_variants));
     }
 
-    let selected = Select::new(
-        format!("Select CPU architecture for release '{}':", release_name).as_str(),
+    let default = architecture_variants::select_variant(
         sorted_variants,
+        config::current().default_variant.as_deref(),
     )
-    .prompt()
-    .unwrap_or_else(|_| std::process::exit(0));
+    .ok_or_else(|| anyhow!("No architecture variants available"))?;
+    let name = compat_tool
+        .variant_of(&default.file_name)
+        .map(|variant| variant.name.as_str())
+        .unwrap_or_default();
+    let note = format!("Selected {name} for {host}");
+    Ok(Selection::Chosen(Box::new(default), Some(note)))
+}
 
-    Ok(selected.download)
+/// Error listing the requirements each variant misses on `host`. Setting `cpu_level` is only
+/// suggested when the CPU level is all that keeps a variant from running
+fn no_supported_variant(
+    compat_tool: &CompatTool,
+    release_name: &str,
+    all_variants: &[ArchitectureVariant],
+    host: &Host,
+) -> anyhow::Error {
+    let unmet: Vec<(&str, Vec<UnmetRequirement>)> = all_variants
+        .iter()
+        .filter_map(|v| {
+            let variant = compat_tool.variant_of(&v.download.file_name)?;
+            Some((v.name.as_str(), variant.requires.unmet_by(host)))
+        })
+        .collect();
+    let reasons: Vec<String> = unmet
+        .iter()
+        .map(|(name, unmet)| {
+            let unmet: Vec<String> = unmet.iter().map(ToString::to_string).collect();
+            format!("{name} requires {}", unmet.join(", "))
+        })
+        .collect();
+    let cpu_level_only = unmet.iter().any(|(_, unmet)| {
+        !unmet.is_empty()
+            && unmet
+                .iter()
+                .all(|requirement| matches!(requirement, UnmetRequirement::CpuLevel(_)))
+    });
+    let hint = match cpu_level_only {
+        true => "Choose one with --variant or set cpu_level in the configuration to override",
+        false => "Choose one with --variant",
+    };
+    anyhow!(
+        "No variant of '{release_name}' runs on {host}: {}. {hint}",
+        reasons.join("; ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    use libprotonup::apps::AppInstallations;
+
+    fn cachyos() -> CompatTool {
+        CompatTool::from_str("Proton CachyOS").unwrap()
+    }
+
+    fn downloads(names: &[&str]) -> Vec<Download> {
+        names
+            .iter()
+            .map(|name| Download {
+                file_name: format!("proton-cachyos-10.0-20250714-slr-{name}.tar.xz"),
+                for_app: AppInstallations::Steam,
+                version: "cachyos-10.0-20250714-slr".to_owned(),
+                hash_sum: None,
+                download_url: "https://example.com/test".to_owned(),
+                size: 1000,
+            })
+            .collect()
+    }
+
+    fn host(arch: &str, cpu_level: Option<u8>) -> Host {
+        Host {
+            arch: arch.to_owned(),
+            cpu_level,
+            glibc: None,
+        }
+    }
+
+    fn chosen(selection: Result<Selection>) -> String {
+        match selection.unwrap() {
+            Selection::Chosen(download, _) => download.file_name,
+            Selection::Menu(_) => panic!("the menu was shown"),
+        }
+    }
+
+    #[test]
+    fn test_select_explicit_variant() {
+        let variants = downloads(&["x86_64", "x86_64_v2", "x86_64_v3"]);
+        let select = |requested| {
+            select(
+                &cachyos(),
+                "10.0",
+                variants.clone(),
+                Some(requested),
+                false,
+                &host("x86_64", Some(2)),
+            )
+        };
+
+        // named variants are installed even when the host can't run them
+        assert!(chosen(select("x86_64_v3")).ends_with("-x86_64_v3.tar.xz"));
+        assert!(chosen(select("x86_64")).ends_with("-x86_64.tar.xz"));
+        let error = select("x86_64_v4").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Variant 'x86_64_v4' is not available for '10.0'. Available variants: x86_64, x86_64_v2, x86_64_v3, auto"
+        );
+        // "auto" selects the best variant
+        assert!(chosen(select(AUTO_VARIANT)).ends_with("-x86_64_v2.tar.xz"));
+
+        // the choice is returned with a note, printed or shown in the interface
+        let note = |selection: Result<Selection>| match selection.unwrap() {
+            Selection::Chosen(_, note) => note,
+            Selection::Menu(_) => panic!("the menu was shown"),
+        };
+        assert_eq!(note(select("x86_64_v2")), None);
+        assert_eq!(
+            note(select("x86_64_v3")).as_deref(),
+            Some("Warning: x86_64_v3 is not supported by x86_64 (x86-64-v2)")
+        );
+        assert_eq!(
+            note(select(AUTO_VARIANT)).as_deref(),
+            Some("Selected x86_64_v2 for x86_64 (x86-64-v2)")
+        );
+    }
+
+    #[test]
+    fn test_select_without_compatible_variant() {
+        let variants = downloads(&["x86_64_v3", "x86_64_v4"]);
+        for prompt in [true, false] {
+            let selection = select(
+                &cachyos(),
+                "10.0",
+                variants.clone(),
+                None,
+                prompt,
+                &host("x86_64", Some(2)),
+            );
+            assert!(
+                selection
+                    .unwrap_err()
+                    .to_string()
+                    .starts_with("No variant of '10.0' runs on x86_64 (x86-64-v2)")
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_non_interactive_default() {
+        let variants = downloads(&["x86_64", "x86_64_v2", "x86_64_v3", "x86_64_v4"]);
+        let host = host("x86_64", Some(3));
+
+        let selection = select(&cachyos(), "10.0", variants.clone(), None, false, &host);
+        assert!(chosen(selection).ends_with("-x86_64_v3.tar.xz"));
+
+        // prompted, the menu lists the variants the host can run
+        match select(&cachyos(), "10.0", variants, None, true, &host).unwrap() {
+            Selection::Menu(menu) => {
+                let names: Vec<&str> = menu.iter().map(|v| v.name.as_str()).collect();
+                assert_eq!(names, vec!["x86_64", "x86_64_v2", "x86_64_v3"]);
+            }
+            Selection::Chosen(download, _) => panic!("{} chosen", download.file_name),
+        }
+    }
+
+    #[test]
+    fn test_no_supported_variant_names_the_failed_requirement() {
+        let variants = architecture_variants::extract_variants(
+            &cachyos(),
+            &downloads(&["x86_64_v3", "x86_64_v4"]),
+        );
+        let error = no_supported_variant(&cachyos(), "10.0", &variants, &host("x86_64", Some(2)));
+        assert_eq!(
+            error.to_string(),
+            "No variant of '10.0' runs on x86_64 (x86-64-v2): x86_64_v3 requires cpu_level 3; \
+             x86_64_v4 requires cpu_level 4. Choose one with --variant or set cpu_level in the \
+             configuration to override"
+        );
+
+        // the CPU level doesn't help on another architecture
+        let variants = architecture_variants::extract_variants(&cachyos(), &downloads(&["x86_64"]));
+        let error = no_supported_variant(&cachyos(), "10.0", &variants, &host("aarch64", None));
+        assert_eq!(
+            error.to_string(),
+            "No variant of '10.0' runs on aarch64: x86_64 requires arch x86_64. Choose one with --variant"
+        );
+    }
 }
@@ -0,0 +1,148 @@
+//! Cancellation of in-flight installs on SIGINT/SIGTERM.
+//!
+//! The signal handler cancels the shared [`CancellationToken`] used by every
+//! libprotonup `Installer`, so partial downloads and installs get removed before exiting.
+//! SIGINT is ignored while a child process like the pager owns the terminal, and the
+//! full-screen interface restores the terminal before exiting.
+
+use std::process::exit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use libprotonup::installer::CancellationToken;
+use tokio::signal::unix::{SignalKind, signal};
+
+/// Exit status used when the program is interrupted by SIGINT or SIGTERM
+pub(crate) const EXIT_CANCELLED: i32 = 130;
+
+/// Time given to in-flight installs to clean up after a signal, before exiting anyway
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+static CANCEL_TOKEN: LazyLock<CancellationToken> = LazyLock::new(CancellationToken::new);
+
+/// Number of installs currently running
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of child processes currently owning the terminal
+static IN_FOREGROUND: AtomicUsize = AtomicUsize::new(0);
+
+/// Restores the terminal before exiting, set while the full-screen interface is open
+static RESTORE_TERMINAL: Mutex<Option<fn()>> = Mutex::new(None);
+
+/// Returns the token cancelled when the program receives SIGINT or SIGTERM
+pub(crate) fn token() -> CancellationToken {
+    CANCEL_TOKEN.clone()
+}
+
+pub(crate) fn is_cancelled() -> bool {
+    CANCEL_TOKEN.is_cancelled()
+}
+
+/// Marks an install as running until dropped,
+/// so a signal waits for its cleanup before exiting
+pub(crate) struct InFlightGuard;
+
+impl InFlightGuard {
+    pub(crate) fn new() -> InFlightGuard {
+        IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Marks a child process as owning the terminal until dropped. The child gets SIGINT
+/// (Ctrl+C) from the terminal too and handles it itself, so it is ignored meanwhile
+pub(crate) struct ForegroundGuard;
+
+impl ForegroundGuard {
+    pub(crate) fn new() -> ForegroundGuard {
+        IN_FOREGROUND.fetch_add(1, Ordering::SeqCst);
+        ForegroundGuard
+    }
+}
+
+impl Drop for ForegroundGuard {
+    fn drop(&mut self) {
+        IN_FOREGROUND.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Makes [`exit_cancelled`] call `restore` before exiting until dropped,
+/// to leave the raw mode and the alternate screen of the full-screen interface
+pub(crate) struct TerminalGuard;
+
+impl TerminalGuard {
+    pub(crate) fn new(restore: fn()) -> TerminalGuard {
+        *RESTORE_TERMINAL.lock().unwrap() = Some(restore);
+        TerminalGuard
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        *RESTORE_TERMINAL.lock().unwrap() = None;
+    }
+}
+
+/// Restores the terminal, removes the temporary files and exits with [`EXIT_CANCELLED`]
+pub(crate) fn exit_cancelled() -> ! {
+    if let Ok(restore) = RESTORE_TERMINAL.lock()
+        && let Some(restore) = *restore
+    {
+        restore();
+    }
+    let _ = libprotonup::utils::cleanup_fallback_temp_dir();
+    eprintln!("Cancelled");
+    exit(EXIT_CANCELLED)
+}
+
+/// Spawns the task handling SIGINT and SIGTERM.
+///
+/// The first signal cancels the in-flight installs. If none is running, or a second signal
+/// arrives, or the cleanup takes longer than the grace period, the program exits right away.
+/// SIGINT is ignored while a [`ForegroundGuard`] is held.
+pub(crate) fn install_signal_handler() {
+    tokio::spawn(async {
+        loop {
+            match wait_for_signal().await {
+                Ok(kind)
+                    if kind == SignalKind::interrupt()
+                        && IN_FOREGROUND.load(Ordering::SeqCst) > 0 => {}
+                Ok(_) => break,
+                Err(_) => return,
+            }
+        }
+        CANCEL_TOKEN.cancel();
+
+        if IN_FLIGHT.load(Ordering::SeqCst) == 0 {
+            exit_cancelled();
+        }
+        // the full-screen interface shows the cancelled installs itself
+        if RESTORE_TERMINAL
+            .lock()
+            .is_ok_and(|restore| restore.is_none())
+        {
+            eprintln!("Cancelling, removing partial installs...");
+        }
+
+        tokio::select! {
+            _ = wait_for_signal() => {},
+            _ = tokio::time::sleep(CANCEL_GRACE_PERIOD) => {},
+        }
+        exit_cancelled();
+    });
+}
+
+async fn wait_for_signal() -> std::io::Result<SignalKind> {
+    let mut sigterm = signal(SignalKind::terminate())?;
+    tokio::select! {
+        res = tokio::signal::ctrl_c() => res.map(|_| SignalKind::interrupt()),
+        _ = sigterm.recv() => Ok(SignalKind::terminate()),
+    }
+}
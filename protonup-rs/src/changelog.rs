@@ -0,0 +1,191 @@
+//! `changelog` subcommand, shows the release notes of every release newer than the installed version.
+
+use anyhow::{Result, anyhow};
+use libprotonup::apps::{self, App, AppInstallations};
+use libprotonup::downloads::{self, Release};
+use libprotonup::release_notes;
+use libprotonup::sources::CompatTool;
+use libprotonup::utils::compare_versions;
+
+use crate::cli::ChangelogArgs;
+use crate::download::{release_url, whatsnew_record};
+use crate::markdown::Renderer;
+use crate::output;
+use crate::{cli_mode, installed};
+
+/// The newest version of `compat_tool` installed in any of `app_insts`
+pub(crate) async fn newest_installed_version(
+    app_insts: &[AppInstallations],
+    compat_tool: &CompatTool,
+    r_list: &[Release],
+) -> Option<String> {
+    let manifest = installed::load_manifest();
+    let mut newest: Option<String> = None;
+    for app_inst in app_insts {
+        let version =
+            release_notes::installed_version(app_inst, compat_tool, r_list, &manifest).await;
+        newest = match (newest, version) {
+            (Some(a), Some(b)) => Some(std::cmp::max_by(a, b, |a, b| compare_versions(a, b))),
+            (a, b) => a.or(b),
+        };
+    }
+    newest
+}
+
+/// The notes of `releases`, each one with a header and the link to its release page,
+/// after a summary mentioning the installed version `since` if known.
+/// `collapse` only keeps the first line of each
+pub(crate) fn format_release_range(
+    compat_tool: &CompatTool,
+    since: Option<&str>,
+    releases: &[&Release],
+    collapse: bool,
+    renderer: &Renderer,
+) -> String {
+    let mut output = format!(
+        "\n  {}: {} release{}",
+        compat_tool.name,
+        releases.len(),
+        if releases.len() == 1 { "" } else { "s" }
+    );
+    if let Some(since) = since {
+        output.push_str(&format!(" since {since}"));
+    }
+    output.push('\n');
+
+    for release in releases {
+        let header = format!("── {release} ");
+        output.push_str(&format!(
+            "\n  {header}{}\n",
+            "─".repeat(60_usize.saturating_sub(header.chars().count()))
+        ));
+        output.push_str(&format!("  {}\n", release_url(release, compat_tool)));
+
+        let notes = renderer.render(release.body.as_deref().unwrap_or_default());
+        let lines: Vec<&str> = notes
+            .lines()
+            .filter(|line| !collapse || !line.trim().is_empty())
+            .collect();
+        match (lines.first(), collapse) {
+            (None, _) => output.push_str("\n  (no release notes)\n"),
+            (Some(first), true) if lines.len() > 1 => {
+                output.push_str(&format!("\n{first}\n  ⋯ {} more lines\n", lines.len() - 1))
+            }
+            _ => output.push_str(&format!("\n{}\n", lines.join("\n"))),
+        }
+    }
+
+    output.push('\n');
+    output
+}
+
+/// Installations of `compat_tool` to look for the installed version in:
+/// the one given with `--for`, or every detected compatible app
+async fn changelog_targets(
+    target: Option<&str>,
+    compat_tool: &CompatTool,
+) -> Vec<AppInstallations> {
+    match target.map(App::from_str_or_path) {
+        Some(app) => app.detect_installation_method().await,
+        None => apps::list_installed_apps()
+            .await
+            .into_iter()
+            .filter(|app_inst| app_inst.as_app().is_compatible(compat_tool))
+            .collect(),
+    }
+}
+
+/// Shows the notes of every release of `--tool` newer than `--since`, or than the newest
+/// installed version
+pub(crate) async fn run_changelog(args: ChangelogArgs) -> Result<()> {
+    let compat_tool = cli_mode::parse_compat_tool(&args.tool)?;
+    let r_list = downloads::list_releases(&compat_tool)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch releases for {}: {}", compat_tool.name, e))?;
+
+    let since = match args.since {
+        Some(since) => since,
+        None => {
+            let targets = changelog_targets(args.target.as_deref(), &compat_tool).await;
+            newest_installed_version(&targets, &compat_tool, &r_list)
+                .await
+                .ok_or_else(|| {
+                    anyhow!(
+                        "No installed version of {} found. Use --since to choose the version to start from",
+                        compat_tool.name
+                    )
+                })?
+        }
+    };
+
+    let releases = release_notes::releases_since(&r_list, &compat_tool, &since);
+    if output::is_machine() {
+        for release in releases {
+            output::emit(whatsnew_record(release, &compat_tool, None));
+        }
+        return Ok(());
+    }
+    if releases.is_empty() {
+        println!("{} is up to date ({since})", compat_tool.name);
+        return Ok(());
+    }
+    output::page(format_release_range(
+        &compat_tool,
+        Some(&since),
+        &releases,
+        args.collapse,
+        &Renderer::for_stdout(),
+    ))
+    .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn release(tag: &str, body: &str) -> Release {
+        serde_json::from_value(json!({
+            "tag_name": tag,
+            "name": tag,
+            "url": null,
+            "assets": [],
+            "body": body,
+            "published_at": "2025-03-04T12:00:00Z"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_format_release_range() {
+        let geproton = CompatTool::from_str("GEProton").unwrap();
+        let newest = release(
+            "GE-Proton9-22",
+            "- Fixed A\n- Fixed **B**\n\n  details\n- Fixed C",
+        );
+        let older = release("GE-Proton9-20", "");
+        let releases = vec![&newest, &older];
+
+        let output = format_release_range(
+            &geproton,
+            Some("GE-Proton9-15"),
+            &releases,
+            false,
+            &Renderer::PLAIN,
+        );
+        assert!(output.contains("GEProton: 2 releases since GE-Proton9-15"));
+        assert!(output.contains("── GE-Proton9-22 (2025-03-04) ──"));
+        assert!(output.contains("/releases/tag/GE-Proton9-20"));
+        assert!(output.contains("- Fixed A\n\n- Fixed B\n\n  details\n\n- Fixed C"));
+        assert!(output.contains("(no release notes)"));
+        // the newest release comes first
+        assert!(output.find("GE-Proton9-22").unwrap() < output.find("GE-Proton9-20").unwrap());
+
+        let output = format_release_range(&geproton, None, &releases, true, &Renderer::PLAIN);
+        assert!(output.contains("GEProton: 2 releases\n"));
+        assert!(output.contains("- Fixed A\n  ⋯ 3 more lines"));
+        assert!(!output.contains("Fixed B"));
+    }
+}
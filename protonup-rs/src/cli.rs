@@ -1,4 +1,18 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+/// Format of the program output
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text and progress bars
+    #[default]
+    Text,
+    /// A single JSON document printed when the command finishes
+    Json,
+    /// One JSON record per line, including progress events, as they happen
+    Ndjson,
+}
 
 #[derive(Debug, Parser)]
 #[command(
@@ -21,7 +35,8 @@ pub struct Opt {
     #[arg(long)]
     pub version: Option<String>,
 
-    /// Target for installation. Use "steam", "lutris", or a custom path.
+    /// Target for installation. Use "steam", "lutris", a target name from config.toml,
+    /// or a custom path.
     /// If omitted, auto-detects Steam or Lutris.
     #[arg(long)]
     pub r#for: Option<String>,
@@ -29,4 +44,183 @@ pub struct Opt {
     /// Show release notes for latest versions of default tools
     #[arg(short, long)]
     pub whats_new: bool,
+
+    /// Unpack the archives while they are downloaded, without a temporary file.
+    /// Same as the stream_install setting
+    #[arg(long, global = true)]
+    pub stream: bool,
+
+    /// Refuse releases that publish no checksum and are not signed.
+    /// Same as checksum_policy = "require"
+    #[arg(long, global = true)]
+    pub require_checksum: bool,
+
+    /// Install or list the releases marked as pre-releases as the latest version.
+    /// Same as the include_prereleases setting
+    #[arg(long, global = true)]
+    pub include_prereleases: bool,
+
+    /// Architecture to install the tools for instead of the one of this system, to install
+    /// onto the disk of another machine. Same as the arch setting
+    #[arg(long, global = true, value_name = "ARCH", value_parser = ["x86_64", "aarch64"])]
+    pub arch: Option<String>,
+
+    /// Variant to install for tools publishing several builds, like "x86_64_v3" for
+    /// Proton CachyOS. "auto" installs the default_variant setting, or the best variant
+    /// for the system. Defaults to a menu, or to "auto" when not interactive
+    #[arg(long, global = true, value_name = "NAME|auto")]
+    pub variant: Option<String>,
+
+    /// Never prompt. Choices that would need one use their default, or fail with an error.
+    /// Implied by --output json/ndjson and when stdin is not a terminal
+    #[arg(long, global = true)]
+    pub non_interactive: bool,
+
+    /// Output format. "json" and "ndjson" print machine-readable records to stdout,
+    /// and require a non-interactive mode
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    pub output: OutputFormat,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Open the full-screen interface: detected apps, installed versions and available
+    /// releases side by side, with their release notes and the progress of the installs
+    Tui,
+    /// List the available releases of a compatibility tool
+    List(ListArgs),
+    /// Show every installed compatibility tool, in every detected app
+    Installed(InstalledArgs),
+    /// Check whether newer releases of the installed tools are available.
+    /// Exits with status 100 when at least one update is available
+    Outdated(OutdatedArgs),
+    /// Check the installed files against the hashes recorded at install time.
+    /// Exits with status 1 when an installation is damaged
+    Verify(VerifyArgs),
+    /// Show the release notes of every release newer than the installed version of a tool
+    Changelog(ChangelogArgs),
+    /// Write a lock file listing the release, asset and checksum of every installed tool,
+    /// to install exactly the same builds on other machines with `sync`
+    Lock(LockArgs),
+    /// Install the releases listed in a lock file, checked against the locked checksums.
+    /// Use `lock --update` to move the lock file to newer releases
+    Sync(SyncArgs),
+    /// Read or change the settings in ~/.config/protonup-rs/config.toml
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the value of a setting, or the whole configuration if no key is given
+    Get {
+        /// Dotted key, e.g. "apps.steam.tools" or "cache.enabled"
+        key: Option<String>,
+    },
+    /// Change a setting. The value is parsed as TOML, e.g. 4, true or '["GEProton", "Luxtorpeda"]'
+    Set {
+        /// Dotted key, e.g. "apps.steam.tools" or "cache.enabled"
+        key: String,
+        value: String,
+    },
+    /// Open the configuration file in $VISUAL or $EDITOR
+    Edit,
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Compatibility tool to list the releases of (e.g., GEProton, Luxtorpeda)
+    #[arg(long)]
+    pub tool: String,
+
+    /// Show at most this many releases, newest first
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct InstalledArgs {
+    /// Also inspect this custom installation folder. Can be repeated
+    #[arg(long = "path", value_name = "PATH")]
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct OutdatedArgs {
+    /// Also check this custom installation folder. Can be repeated
+    #[arg(long = "path", value_name = "PATH")]
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ChangelogArgs {
+    /// Compatibility tool to show the release notes of (e.g., GEProton, Luxtorpeda)
+    #[arg(long)]
+    pub tool: String,
+
+    /// Show the releases newer than this version. Defaults to the newest installed version
+    #[arg(long, value_name = "VERSION")]
+    pub since: Option<String>,
+
+    /// Only look for the installed version in this target: "steam", "lutris",
+    /// a target name from config.toml, or a custom path
+    #[arg(long = "for", value_name = "TARGET")]
+    pub target: Option<String>,
+
+    /// Only show the first line of the notes of each release
+    #[arg(long)]
+    pub collapse: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Only verify the installations of this version
+    pub version: Option<String>,
+
+    /// Only verify the installations in this target: "steam", "lutris",
+    /// a target name from config.toml, or a custom path
+    #[arg(long = "for", value_name = "TARGET")]
+    pub target: Option<String>,
+
+    /// Reinstall the damaged installations, reusing the kept archive when there is one
+    #[arg(long)]
+    pub repair: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct LockArgs {
+    /// Lock file to write
+    #[arg(long, value_name = "PATH", default_value = "protonup.lock")]
+    pub file: PathBuf,
+
+    /// Only lock the installations in this target: "steam", "lutris",
+    /// a target name from config.toml, or a custom path
+    #[arg(long = "for", value_name = "TARGET")]
+    pub target: Option<String>,
+
+    /// Install the newest release of each locked tool and lock it in place of the newest
+    /// locked release, keeping the other locked releases
+    #[arg(long)]
+    pub update: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SyncArgs {
+    /// Lock file to install the releases of
+    #[arg(long, value_name = "PATH", default_value = "protonup.lock")]
+    pub file: PathBuf,
+
+    /// Fail instead of reinstalling the installed folders that differ from the lock file,
+    /// like modified files or another asset. Missing releases are still installed
+    #[arg(long)]
+    pub locked: bool,
+
+    /// Remove the installed tools that are not in the lock file, in the locked targets.
+    /// Folders that don't match any known tool are kept
+    #[arg(long)]
+    pub prune: bool,
 }
@@ -1,23 +1,41 @@
 use anyhow::Error;
 use libprotonup::apps::{App, AppInstallations};
+use libprotonup::cpu;
 use libprotonup::downloads::{self, Release};
 use libprotonup::sources::CompatTool;
 use libprotonup::utils::match_version;
-use tokio::fs::File;
-use tokio::io::BufReader;
 
 use crate::architecture_variants;
 use crate::download;
+use crate::output;
+
+/// The first detected installation of an app, the native one when there is also a Flatpak.
+/// Mentions the others, which can be chosen with `--for <path>`
+fn first_installation(app: &App, installations: Vec<AppInstallations>) -> AppInstallations {
+    if installations.len() > 1 {
+        output::info(format!(
+            "{app} is installed as {}, using {}. Pass its path with --for to choose another",
+            installations
+                .iter()
+                .map(|installation| installation.to_string())
+                .collect::<Vec<_>>()
+                .join(" and "),
+            installations[0]
+        ));
+    }
+    installations[0].clone()
+}
 
 /// Determines the target application based on the provided `--for` argument and the selected tool.
 ///
 /// - If the value is "steam" (case-insensitive), uses `App::Steam`
 /// - If the value is "lutris" (case-insensitive), uses `App::Lutris`
+/// - If the value is the name of a target from `config.toml`, uses `App::Target`
 /// - If the value is a file system path (relative or absolute), uses `App::Custom`
 /// - If the value is None, auto-detects based on:
 ///   1. Compatible applications for the selected tool
 ///   2. Installed apps (Steam/Lutris)
-async fn determine_app_installation(
+pub(crate) async fn determine_app_installation(
     for_target: Option<&str>,
     compat_tool: &CompatTool,
 ) -> Result<AppInstallations, Error> {
@@ -38,7 +56,7 @@ async fn determine_app_installation(
                     "Steam installation not found. Install location for Steam not found."
                 ));
             }
-            Ok(apps[0].clone())
+            Ok(first_installation(&App::Steam, apps))
         }
         App::Lutris => {
             let apps = App::Lutris.detect_installation_method().await;
@@ -47,9 +65,20 @@ async fn determine_app_installation(
                     "Lutris installation not found. Install location for Lutris not found."
                 ));
             }
-            Ok(apps[0].clone())
+            Ok(first_installation(&App::Lutris, apps))
         }
         App::Custom(path) => Ok(AppInstallations::new_custom_app_install(path)),
+        App::Target(target) => {
+            if !target.is_compatible(compat_tool) {
+                return Err(anyhow::anyhow!(
+                    "{} is not compatible with target '{}'. Compatible tools: {}",
+                    compat_tool.name,
+                    target.name,
+                    target.compatible_tools.join(", ")
+                ));
+            }
+            Ok(AppInstallations::Target(target))
+        }
     }
 }
 
@@ -58,37 +87,49 @@ async fn auto_detect_app(compatible_apps: &[App]) -> Result<AppInstallations, Er
     // Check compatible apps in order of preference
     for compat_app in compatible_apps {
         match compat_app {
-            App::Steam => {
-                let apps = App::Steam.detect_installation_method().await;
-                if !apps.is_empty() {
-                    return Ok(apps[0].clone());
-                }
-            }
-            App::Lutris => {
-                let apps = App::Lutris.detect_installation_method().await;
+            App::Steam | App::Lutris => {
+                let apps = compat_app.detect_installation_method().await;
                 if !apps.is_empty() {
-                    return Ok(apps[0].clone());
+                    return Ok(first_installation(compat_app, apps));
                 }
             }
             App::Custom(path) => {
                 return Ok(AppInstallations::new_custom_app_install(path.clone()));
             }
+            App::Target(target) => {
+                return Ok(AppInstallations::Target(target.clone()));
+            }
         }
     }
 
     // If no compatible apps are installed, provide a helpful error
     let compatible_names: Vec<String> = compatible_apps
         .iter()
-        .filter(|app| !matches!(app, App::Custom(_)))
+        .filter(|app| !matches!(app, App::Custom(_) | App::Target(_)))
         .map(|app| app.to_string())
         .collect();
 
     Err(anyhow::anyhow!(
-        "{} installation(s) not found. Use --for to specify 'steam', 'lutris', or a custom installation path.",
+        "{} installation(s) not found. Use --for to specify 'steam', 'lutris', a target name, or a custom installation path.",
         compatible_names.join(" and ")
     ))
 }
 
+/// Parses the `--tool` argument, listing the available tools on failure.
+pub(crate) fn parse_compat_tool(tool_name: &str) -> Result<CompatTool, Error> {
+    tool_name.parse::<CompatTool>().map_err(|_| {
+        anyhow::anyhow!(
+            "Unknown compatibility tool: '{}'. Available tools: {}",
+            tool_name,
+            libprotonup::sources::CompatTools
+                .iter()
+                .map(|t| t.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })
+}
+
 /// Finds a release matching the user-provided version string.
 fn find_release_by_version(
     release_list: Vec<Release>,
@@ -130,17 +171,7 @@ pub async fn run_cli_mode(
 ) -> Result<Vec<Release>, Error> {
     // Determine the compatibility tool first (needed for auto-detection)
     let compat_tool = match tool.as_deref() {
-        Some(tool_name) => tool_name.parse::<CompatTool>().map_err(|_| {
-            anyhow::anyhow!(
-                "Unknown compatibility tool: '{}'. Available tools: {}",
-                tool_name,
-                libprotonup::sources::CompatTools
-                    .iter()
-                    .map(|t| t.name.as_str())
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            )
-        })?,
+        Some(tool_name) => parse_compat_tool(tool_name)?,
         None => {
             // If no tool specified, determine app first, then use its default tool
             let temp_app = match for_target.as_deref() {
@@ -159,6 +190,14 @@ pub async fn run_cli_mode(
         }
     };
 
+    let arch = cpu::target_arch();
+    if !compat_tool.supports_arch(&arch) {
+        return Err(anyhow::anyhow!(
+            "{} is not built for {arch}. Use --arch to install it for another system",
+            compat_tool.name
+        ));
+    }
+
     // Determine the target app installation (uses compat_tool's compatible_applications for auto-detect)
     let app_inst = determine_app_installation(for_target.as_deref(), &compat_tool).await?;
 
@@ -175,23 +214,27 @@ pub async fn run_cli_mode(
     };
 
     // Select the version
-    let releases =
-        match version.as_deref() {
-            Some("latest") | None => {
-                // Use the latest version (first in the list)
-                vec![release_list.into_iter().next().ok_or_else(|| {
-                    anyhow::anyhow!("No releases available for {}", compat_tool.name)
+    let releases = match version.as_deref() {
+        Some("latest") | None => {
+            // Use the latest version, skipping pre-releases unless they are included
+            let latest = downloads::latest_release(&release_list, &compat_tool);
+            vec![latest.cloned().ok_or_else(|| match release_list.is_empty() {
+                    true => anyhow::anyhow!("No releases available for {}", compat_tool.name),
+                    false => anyhow::anyhow!(
+                        "No releases available for {}, only pre-releases. Use --include-prereleases to install one",
+                        compat_tool.name
+                    ),
                 })?]
-            }
-            Some(version_str) => {
-                // Find the matching version using flexible matching
-                vec![find_release_by_version(
-                    release_list,
-                    version_str,
-                    &compat_tool.name,
-                )?]
-            }
-        };
+        }
+        Some(version_str) => {
+            // Find the matching version using flexible matching
+            vec![find_release_by_version(
+                release_list,
+                version_str,
+                &compat_tool.name,
+            )?]
+        }
+    };
 
     // Show release notes if --whats-new was requested
     if whats_new {
@@ -206,22 +249,19 @@ pub async fn run_cli_mode(
     }
 
     // Handle tools with multiple asset variations
-    let downloads_vec: Vec<downloads::Download> = if compat_tool.has_multiple_asset_variations {
+    let downloads_vec: Vec<downloads::Download> = if compat_tool.has_variants() {
         releases
             .iter()
             .map(|release| {
                 let variants = release.get_all_download_variants(&app_inst, &compat_tool);
                 architecture_variants::select_architecture_variant(
+                    &compat_tool,
                     &release.tag_name,
                     variants,
                     false,
                 )
-                .unwrap_or_else(|e| {
-                    eprintln!("Error selecting architecture variant: {}", e);
-                    std::process::exit(1);
-                })
             })
-            .collect()
+            .collect::<Result<_, Error>>()?
     } else {
         releases
             .iter()
@@ -236,28 +276,6 @@ pub async fn run_cli_mode(
 
     for download_item in downloads_vec {
         let install_dir = app_inst.installation_dir(&compat_tool).unwrap();
-        let file = download::download_file(&download_item, multi_progress.clone())
-            .await
-            .map_err(|e| anyhow::anyhow!("Error downloading {}: {}", download_item.version, e))?;
-
-        // Validate hash if available
-        if let Some(ref git_hash_sum) = download_item.hash_sum {
-            let hash_content =
-                &downloads::download_file_into_memory(&git_hash_sum.sum_content).await?;
-            let hash_sum = libprotonup::hashing::HashSums {
-                sum_content: hash_content.to_owned(),
-                sum_type: git_hash_sum.sum_type.clone(),
-            };
-            download::validate_file(
-                &download_item.file_name,
-                &file,
-                hash_sum,
-                multi_progress.clone(),
-            )
-            .await?;
-        }
-
-        // Install
         let install_name = compat_tool.installation_name(&download_item.version);
         let install_path = install_dir.join(install_name.clone());
         if libprotonup::files::check_if_exists(&install_path).await && !force {
@@ -267,51 +285,14 @@ pub async fn run_cli_mode(
                 install_path.display()
             ));
         }
-        if libprotonup::files::check_if_exists(&install_path).await {
-            tokio::fs::remove_dir_all(&install_path).await?;
-        }
-
-        // Open the compressed file
-        let compressed_file = File::open(&file).await.map_err(|e| {
-            anyhow::anyhow!("Error opening compressed file {}: {}", file.display(), e)
-        })?;
-
-        // Wrap the file with progress tracking to read compressed bytes
-        let unpack_progress_bar =
-            download::init_unpack_progress(&install_dir.clone(), &file, multi_progress.clone())
-                .await?;
-
-        let progress_reader = unpack_progress_bar.wrap_async_read(compressed_file);
 
-        // Wrap with BufReader to provide AsyncBufRead for the decompressor
-        let buf_reader = BufReader::new(progress_reader);
-
-        // Create Decompressor from the BufReader
-        let path_str = file.to_string_lossy();
-        let decompressor = libprotonup::files::Decompressor::from_reader(buf_reader, &path_str)
-            .map_err(|e| {
-                anyhow::anyhow!("Error checking file type of {}: {}", file.display(), e)
-            })?;
-
-        libprotonup::files::unpack_file(&compat_tool, &download_item, decompressor, &install_dir)
-            .await?;
-
-        unpack_progress_bar.set_style(
-            indicatif::ProgressStyle::default_bar()
-                .template("{msg}")
-                .unwrap(),
-        );
-        unpack_progress_bar.finish_with_message(format!(
-            "Done! {} installed in {}/{}",
-            compat_tool,
-            download_item
-                .for_app
-                .installation_dir(&compat_tool)
-                .unwrap()
-                .to_str()
-                .unwrap(),
-            install_name
-        ));
+        download::install_with_progress(
+            compat_tool.clone(),
+            download_item,
+            vec![app_inst.clone()],
+            multi_progress.clone(),
+        )
+        .await?;
     }
 
     Ok(releases)
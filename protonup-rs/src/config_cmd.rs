@@ -0,0 +1,115 @@
+//! `config` subcommand, reads and changes the configuration file.
+
+use std::env;
+use std::path::Path;
+use std::process;
+
+use anyhow::{Context, Result, anyhow, bail};
+use libprotonup::config::Config;
+
+use crate::cli::ConfigCommand;
+use crate::output::{self, Record};
+
+/// Editor used by `config edit` when neither $VISUAL nor $EDITOR are set
+const FALLBACK_EDITOR: &str = "vi";
+
+/// Shown instead of the GitHub token when the whole configuration is printed
+const REDACTED: &str = "***";
+
+pub(crate) fn run_config(command: ConfigCommand) -> Result<()> {
+    let path = Config::path()?;
+    match command {
+        ConfigCommand::Get { key } => {
+            let config = Config::load_from(&path)?;
+            let value = match &key {
+                Some(key) => config.get(key)?,
+                None => Some(redacted(&config)?),
+            };
+            print_value(key, value)
+        }
+        ConfigCommand::Set { key, value } => {
+            let config = Config::set(&path, &key, &value)?;
+            output::info(format!("Updated {} in {}", key, path.display()));
+            print_value(Some(key.clone()), config.get(&key)?)
+        }
+        ConfigCommand::Edit => edit(&path),
+    }
+}
+
+/// The whole configuration, with the GitHub token hidden. It is only printed when asked
+/// for by its key
+fn redacted(config: &Config) -> Result<toml::Value> {
+    let mut value = toml::Value::try_from(config)?;
+    if let Some(token) = value.get_mut("github_token") {
+        *token = toml::Value::String(REDACTED.to_owned());
+    }
+    Ok(value)
+}
+
+fn print_value(key: Option<String>, value: Option<toml::Value>) -> Result<()> {
+    if output::is_machine() {
+        output::emit(Record::Config {
+            key,
+            value: serde_json::to_value(&value)?,
+        });
+        return Ok(());
+    }
+    match value {
+        Some(toml::Value::String(value)) => println!("{value}"),
+        Some(toml::Value::Table(table)) => print!("{}", toml::to_string_pretty(&table)?),
+        Some(value) => println!("{value}"),
+        None => println!("(not set)"),
+    }
+    Ok(())
+}
+
+/// Opens the file in the user editor, creating it with the defaults if needed,
+/// and checks that it is still valid
+fn edit(path: &Path) -> Result<()> {
+    if !path.exists() {
+        Config::default().save(path)?;
+    }
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| FALLBACK_EDITOR.to_owned());
+    let mut args = editor.split_whitespace();
+    let program = args
+        .next()
+        .ok_or_else(|| anyhow!("The editor command is empty"))?;
+
+    let status = process::Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start the editor '{editor}'"))?;
+    if !status.success() {
+        bail!("The editor '{editor}' exited with {status}");
+    }
+
+    Config::load_from(path)?;
+    output::info(format!("Saved {}", path.display()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted_hides_the_github_token() {
+        let config = Config {
+            github_token: Some("ghp_secret".to_owned()),
+            ..Config::default()
+        };
+        let value = redacted(&config).unwrap();
+        assert_eq!(value["github_token"].as_str(), Some(REDACTED));
+        assert_eq!(
+            config.get("github_token").unwrap(),
+            Some(toml::Value::String("ghp_secret".to_owned()))
+        );
+
+        let value = redacted(&Config::default()).unwrap();
+        assert!(value.get("github_token").is_none());
+    }
+}
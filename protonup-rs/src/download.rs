@@ -1,139 +1,302 @@
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Result, anyhow};
 use futures_util::stream::FuturesUnordered;
 use futures_util::{StreamExt, future, stream};
-use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use inquire::{Select, Text};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use tokio::fs;
-use tokio::fs::{File, OpenOptions};
-use tokio::io::BufReader;
+use std::sync::Mutex;
 use tokio::sync::OnceCell;
 
 use libprotonup::{
-    apps,
+    apps, config, cpu,
     downloads::{self, Download, Release, ReleaseList},
-    files, hashing,
+    files,
+    installer::{InstallEvent, Installer},
+    manifest::{self, Manifest},
+    release_notes,
     sources::{CompatTool, CompatTools},
+    utils,
 };
 
-use crate::{architecture_variants, file_path, helper_menus};
+use crate::markdown::Renderer;
+use crate::{architecture_variants, cancel, changelog, file_path, helper_menus, installed, output};
 
 static PROGRESS_BAR_STYLE: OnceCell<ProgressStyle> = OnceCell::const_new();
 static MESSAGE_BAR_STYLE: OnceCell<ProgressStyle> = OnceCell::const_new();
 
-pub(crate) async fn init_download_progress(
-    download: &Download,
-    tmp_dir: &Path,
+/// Renders the events of a libprotonup [`Installer`] with indicatif progress bars
+struct ProgressRenderer {
     multi_progress: MultiProgress,
-) -> ProgressBar {
-    let progress_bar = multi_progress.add(ProgressBar::new(download.size));
-    progress_bar.set_style(get_progress_style().await);
-    progress_bar.set_message(format!(
-        "Downloading {} to {}",
-        download.download_url.split('/').next_back().unwrap(),
-        tmp_dir.display()
-    ));
-
-    progress_bar
+    progress_style: ProgressStyle,
+    message_style: ProgressStyle,
+    compat_tool: String,
+    bars: Mutex<RendererBars>,
 }
 
-pub(crate) async fn init_hash_progress(
-    path: &Path,
-    multi_progress: MultiProgress,
-) -> Result<ProgressBar> {
-    let progress_bar = multi_progress.add(ProgressBar::new(fs::metadata(path).await?.len()));
-    progress_bar.set_style(get_progress_style().await);
-    progress_bar.set_message(format!("Validating {}", path.display()));
-    Ok(progress_bar)
-}
-
-pub(crate) async fn init_unpack_progress(
-    target_dir: &Path,
-    source_file: &Path,
-    multi_progress: MultiProgress,
-) -> Result<ProgressBar> {
-    let progress_bar = multi_progress.add(ProgressBar::new(fs::metadata(source_file).await?.len()));
-    progress_bar.set_style(get_progress_style().await);
-    progress_bar.set_message(format!(
-        "Unpacking {} to {}",
-        source_file.display(),
-        target_dir.display()
-    ));
-    Ok(progress_bar)
+#[derive(Default)]
+struct RendererBars {
+    file_name: String,
+    version: String,
+    download: Option<ProgressBar>,
+    hash: Option<ProgressBar>,
+    unpack: HashMap<String, ProgressBar>,
+    /// last percentage reported in the progress records, by stage and target
+    reported_percent: HashMap<String, u64>,
 }
 
-/// Downloads the requested file to the /tmp directory
-pub(crate) async fn download_file(
-    download: &Download,
-    multi_progress: MultiProgress,
-) -> Result<PathBuf> {
-    let output_dir = download.download_dir()?;
-
-    if files::check_if_exists(&output_dir).await {
-        fs::remove_dir_all(&output_dir).await?;
+impl ProgressRenderer {
+    async fn new(compat_tool: &CompatTool, multi_progress: MultiProgress) -> ProgressRenderer {
+        ProgressRenderer {
+            multi_progress,
+            progress_style: get_progress_style().await,
+            message_style: get_message_bar_style().await,
+            compat_tool: compat_tool.to_string(),
+            bars: Mutex::new(RendererBars::default()),
+        }
     }
 
-    let file = OpenOptions::new()
-        .create_new(true)
-        .write(true)
-        .open(&output_dir)
-        .await
-        .with_context(|| {
-            format!(
-                "[Download] Failed creating destination file: {}",
-                output_dir.display()
-            )
-        })?;
+    fn new_bar(&self, total: u64, message: String) -> ProgressBar {
+        let progress_bar = self.multi_progress.add(ProgressBar::new(total));
+        progress_bar.set_style(self.progress_style.clone());
+        progress_bar.set_message(message);
+        progress_bar
+    }
 
-    let download_progress_bar =
-        init_download_progress(download, &output_dir, multi_progress.clone()).await;
+    /// Replaces the progress of a bar by its message, with the first word changed
+    fn finish_bar(&self, progress_bar: &ProgressBar, from: &str, to: &str) {
+        if progress_bar.is_finished() {
+            return;
+        }
+        progress_bar.set_style(self.message_style.clone());
+        progress_bar.finish_with_message(progress_bar.message().replacen(from, to, 1));
+    }
 
-    downloads::download_to_async_write(
-        &download.download_url,
-        &mut download_progress_bar.wrap_async_write(file),
-    )
-    .await?;
+    /// Emits the output records for an event. Progress is throttled to one record per percent
+    fn record(&self, event: &InstallEvent, bars: &mut RendererBars) {
+        if let InstallEvent::Started { version, .. } = event {
+            bars.version = version.clone();
+        }
+        let mut progress = output::ProgressRecord {
+            tool: self.compat_tool.clone(),
+            version: bars.version.clone(),
+            stage: output::Stage::Started,
+            target: None,
+            done: None,
+            total: None,
+            message: None,
+        };
+        match event {
+            InstallEvent::Started { .. } => {}
+            InstallEvent::Bytes { done, total } => {
+                progress.stage = output::Stage::Download;
+                (progress.done, progress.total) = (Some(*done), Some(*total));
+            }
+            InstallEvent::Verifying { done, total } => {
+                progress.stage = output::Stage::Verify;
+                (progress.done, progress.total) = (Some(*done), Some(*total));
+            }
+            InstallEvent::Unpacking {
+                target,
+                done,
+                total,
+                ..
+            } => {
+                progress.stage = output::Stage::Unpack;
+                progress.target = Some(target.into());
+                (progress.done, progress.total) = (Some(*done), Some(*total));
+            }
+            InstallEvent::Retry { error, .. } => {
+                progress.stage = output::Stage::Retry;
+                progress.message = Some(error.clone());
+            }
+            InstallEvent::Warning { message } => {
+                progress.stage = output::Stage::Warning;
+                progress.message = Some(message.clone());
+            }
+            InstallEvent::Finished {
+                target,
+                install_path,
+            } => {
+                output::emit(output::Record::Install {
+                    tool: self.compat_tool.clone(),
+                    version: bars.version.clone(),
+                    target: target.into(),
+                    install_path: install_path.clone(),
+                });
+                progress.stage = output::Stage::Finished;
+                progress.target = Some(target.into());
+            }
+            InstallEvent::Failed { target, error } => {
+                progress.stage = output::Stage::Failed;
+                progress.target = target.as_ref().map(output::TargetRecord::from);
+                progress.message = Some(error.clone());
+            }
+            InstallEvent::Cancelled => progress.stage = output::Stage::Cancelled,
+        }
 
-    download_progress_bar.set_style(get_message_bar_style().await);
-    download_progress_bar.finish_with_message(download_progress_bar.message().replacen(
-        "Downloading",
-        "Downloaded",
-        1,
-    ));
+        if let (Some(done), Some(total)) = (progress.done, progress.total) {
+            let percent = (done * 100).checked_div(total).unwrap_or(100);
+            let key = format!(
+                "{:?}{}",
+                progress.stage,
+                progress
+                    .target
+                    .as_ref()
+                    .map(|t| t.name.as_str())
+                    .unwrap_or_default()
+            );
+            if bars.reported_percent.insert(key, percent) == Some(percent) {
+                return;
+            }
+        }
+        output::emit(output::Record::Progress(progress));
+    }
 
-    Ok(output_dir)
+    fn handle(&self, event: InstallEvent) {
+        let mut bars = self.bars.lock().unwrap();
+        if output::is_machine() {
+            self.record(&event, &mut bars);
+            return;
+        }
+        match event {
+            InstallEvent::Started {
+                file_name,
+                total,
+                targets,
+                ..
+            } => {
+                let app_names: Vec<String> = targets.iter().map(|a| a.to_string()).collect();
+                bars.download = Some(self.new_bar(
+                    total,
+                    format!("Downloading {} for {}", file_name, app_names.join(", ")),
+                ));
+                bars.file_name = file_name;
+            }
+            InstallEvent::Bytes { done, .. } => {
+                if let Some(progress_bar) = &bars.download {
+                    progress_bar.set_position(done);
+                }
+            }
+            InstallEvent::Retry { attempt, error } => {
+                if let Some(progress_bar) = &bars.download {
+                    progress_bar.reset();
+                }
+                let _ = self.multi_progress.println(format!(
+                    "Download of {} failed, retrying (attempt {attempt}): {error}",
+                    bars.file_name
+                ));
+            }
+            InstallEvent::Warning { message } => {
+                let _ = self.multi_progress.println(format!("Warning: {message}"));
+            }
+            InstallEvent::Verifying { done, total } => {
+                if let Some(progress_bar) = &bars.download {
+                    self.finish_bar(progress_bar, "Downloading", "Downloaded");
+                }
+                if bars.hash.is_none() {
+                    bars.hash = Some(self.new_bar(total, format!("Validating {}", bars.file_name)));
+                }
+                if let Some(progress_bar) = &bars.hash {
+                    progress_bar.set_position(done);
+                }
+            }
+            InstallEvent::Unpacking {
+                target,
+                install_dir,
+                entries,
+                done,
+                total,
+            } => {
+                if let Some(progress_bar) = &bars.download {
+                    self.finish_bar(progress_bar, "Downloading", "Downloaded");
+                }
+                if let Some(progress_bar) = &bars.hash {
+                    self.finish_bar(progress_bar, "Validating", "Validated");
+                }
+                let message = format!(
+                    "Unpacking {} to {} ({} entries)",
+                    bars.file_name,
+                    install_dir.display(),
+                    entries
+                );
+                match bars.unpack.get(&target.to_string()) {
+                    Some(progress_bar) => {
+                        progress_bar.set_message(message);
+                        progress_bar.set_position(done);
+                    }
+                    None => {
+                        let progress_bar = self.new_bar(total, message);
+                        progress_bar.set_position(done);
+                        bars.unpack.insert(target.to_string(), progress_bar);
+                    }
+                }
+            }
+            InstallEvent::Finished {
+                target,
+                install_path,
+            } => {
+                if let Some(progress_bar) = bars.unpack.get(&target.to_string()) {
+                    progress_bar.set_style(self.message_style.clone());
+                    progress_bar.finish_with_message(format!(
+                        "Done! {} installed in {}\nYour app might require a restart to detect {}",
+                        self.compat_tool,
+                        install_path.display(),
+                        self.compat_tool
+                    ));
+                }
+            }
+            InstallEvent::Cancelled => {
+                let in_flight = [&bars.download, &bars.hash]
+                    .into_iter()
+                    .flatten()
+                    .chain(bars.unpack.values());
+                for progress_bar in in_flight {
+                    if !progress_bar.is_finished() {
+                        progress_bar.abandon_with_message("Cancelled");
+                    }
+                }
+            }
+            InstallEvent::Failed { target, .. } => {
+                let failed = match target {
+                    Some(target) => bars.unpack.get(&target.to_string()).into_iter().collect(),
+                    None => vec![&bars.download, &bars.hash]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>(),
+                };
+                for progress_bar in failed {
+                    progress_bar.abandon();
+                }
+            }
+        }
+    }
 }
 
-pub(crate) async fn validate_file(
-    file_name: &str,
-    path: &Path,
-    hash: hashing::HashSums,
+/// Downloads, validates and unpacks `download` into every target, rendering the progress
+pub(crate) async fn install_with_progress(
+    compat_tool: CompatTool,
+    download: Download,
+    targets: Vec<apps::AppInstallations>,
     multi_progress: MultiProgress,
 ) -> Result<()> {
-    let file = File::open(path)
-        .await
-        .context("[Hash Check] Failed opening download file for checking. Was the file moved?")?;
-
-    let hash_progress_bar = init_hash_progress(path, multi_progress).await?;
-
-    if !hashing::hash_check_file(
-        file_name,
-        &mut hash_progress_bar.wrap_async_read(BufReader::new(file)),
-        hash,
-    )
-    .await?
-    {
-        bail!("{} failed validation", path.display());
-    }
-
-    hash_progress_bar.set_style(get_message_bar_style().await);
-    hash_progress_bar.finish_with_message(hash_progress_bar.message().replacen(
-        "Validating",
-        "Validated",
-        1,
-    ));
+    let installer = Installer::new(compat_tool.clone(), download, targets);
+    run_with_progress(&compat_tool, installer, multi_progress).await
+}
 
+/// Runs an installer of `compat_tool`, rendering the progress
+pub(crate) async fn run_with_progress(
+    compat_tool: &CompatTool,
+    installer: Installer,
+    multi_progress: MultiProgress,
+) -> Result<()> {
+    let _in_flight = cancel::InFlightGuard::new();
+    let renderer = ProgressRenderer::new(compat_tool, multi_progress).await;
+    installer
+        .with_cancellation(cancel::token())
+        .on_event(move |event| renderer.handle(event))
+        .run()
+        .await?;
     Ok(())
 }
 
@@ -141,23 +304,26 @@ type DedupKey = (String, String, u64);
 type DownloadGroup = (Download, Release, CompatTool, Vec<apps::AppInstallations>);
 
 /// Groups download entries by their dedup key and returns a map of
-/// (download, release, compat_tool, targets) plus uniquely tagged releases.
+/// (download, release, compat_tool, targets) plus the unique releases,
+/// one per tool and tag.
 fn group_and_dedup_releases(
     entries: Vec<(Download, Release, apps::AppInstallations, CompatTool)>,
 ) -> (HashMap<DedupKey, DownloadGroup>, Vec<Release>) {
     let mut groups: HashMap<DedupKey, DownloadGroup> = HashMap::new();
-    let mut seen_releases: HashSet<String> = HashSet::new();
+    let mut seen_releases: HashSet<(String, String)> = HashSet::new();
     let mut unique_releases: Vec<Release> = vec![];
 
     for (download, release, app_inst, compat_tool) in entries {
         let key = download.dedup_key();
 
+        let release_key = (compat_tool.name.clone(), release.tag_name.clone());
+
         groups
             .entry(key)
             .and_modify(|(_, _, _, targets)| targets.push(app_inst.clone()))
             .or_insert_with(|| (download, release.clone(), compat_tool, vec![app_inst]));
 
-        if seen_releases.insert(release.tag_name.clone()) {
+        if seen_releases.insert(release_key) {
             unique_releases.push(release);
         }
     }
@@ -165,78 +331,83 @@ fn group_and_dedup_releases(
     (groups, unique_releases)
 }
 
-/// Downloads the latest wine version for all the apps found
+/// Downloads the latest version of every default tool for all the apps found
 pub async fn run_quick_downloads(force: bool, whats_new: bool) -> Result<Vec<Release>> {
     let found_apps = apps::list_installed_apps().await;
     if found_apps.is_empty() {
-        println!("No apps found. Please install at least one app before using this feature.");
+        output::info("No apps found. Please install at least one app before using this feature.");
         return Err(anyhow!(
             "No apps found. Please install at least one app before using this feature."
         ));
     }
-    println!(
-        "Found the following apps: {}",
-        found_apps
-            .iter()
-            .map(|app| app.to_string())
-            .collect::<Vec<String>>()
-            .join(", ")
-    );
+    report_found_apps(&found_apps);
 
-    let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(20));
+    let multi_progress = MultiProgress::with_draw_target(output::draw_target());
 
     // Cache list_releases results to avoid redundant API calls
     let mut releases_cache: HashMap<String, ReleaseList> = HashMap::new();
     let mut entries: Vec<(Download, Release, apps::AppInstallations, CompatTool)> = vec![];
     let mut release_and_compat_refs: Vec<(Release, CompatTool)> = vec![];
+    let mut updated_tools: Vec<(apps::AppInstallations, CompatTool)> = vec![];
+    let arch = cpu::target_arch();
 
     for app_inst in found_apps {
-        let compat_tool = app_inst.as_app().default_compatibility_tool();
-
-        let release_list = match releases_cache.get(&compat_tool.name) {
-            Some(list) => list.clone(),
-            None => {
-                let list = match downloads::list_releases(&compat_tool).await {
-                    Ok(list) => list,
-                    Err(e) => {
-                        eprintln!(
-                            "Failed to fetch data, make sure you're connected to the internet.\nError: {e}"
-                        );
-                        std::process::exit(1)
-                    }
-                };
-                releases_cache.insert(compat_tool.name.clone(), list.clone());
-                list
+        for compat_tool in app_inst.as_app().default_compatibility_tools() {
+            if !compat_tool.supports_arch(&arch) {
+                output::info(format!(
+                    "Skipping {compat_tool} for {app_inst}, it is not built for {arch}"
+                ));
+                continue;
             }
-        };
+            updated_tools.push((app_inst.clone(), compat_tool.clone()));
 
-        if release_list.is_empty() {
-            continue;
-        }
+            let release_list = match releases_cache.get(&compat_tool.name) {
+                Some(list) => list.clone(),
+                None => {
+                    let list = downloads::list_releases(&compat_tool).await.map_err(|e| {
+                        anyhow!(
+                            "Failed to fetch data, make sure you're connected to the internet.\nError: {e}"
+                        )
+                    })?;
+                    releases_cache.insert(compat_tool.name.clone(), list.clone());
+                    list
+                }
+            };
 
-        let release = release_list[0].clone();
+            let Some(release) = downloads::latest_release(&release_list, &compat_tool).cloned()
+            else {
+                continue;
+            };
 
-        // Handle tools with multiple architecture variants
-        let download = if compat_tool.has_multiple_asset_variations {
-            let variants = release.get_all_download_variants(&app_inst, &compat_tool);
-            architecture_variants::select_architecture_variant(&release.tag_name, variants, true)?
-        } else {
-            release.get_download_info(&app_inst, &compat_tool)
-        };
+            // Handle tools with multiple architecture variants
+            let download = if compat_tool.has_variants() {
+                let variants = release.get_all_download_variants(&app_inst, &compat_tool);
+                architecture_variants::select_architecture_variant(
+                    &compat_tool,
+                    &release.tag_name,
+                    variants,
+                    true,
+                )?
+            } else {
+                release.get_download_info(&app_inst, &compat_tool)
+            };
 
-        // Check if already installed
-        let mut download_path = PathBuf::from(&app_inst.default_install_dir().as_str());
-        download_path.push(compat_tool.installation_name(&download.version));
-        if files::check_if_exists(&download_path.clone()).await && !force {
-            continue;
-        }
+            // Check if already installed
+            if let Some(install_dir) = app_inst.installation_dir(&compat_tool) {
+                let download_path =
+                    install_dir.join(compat_tool.installation_name(&download.version));
+                if files::check_if_exists(&download_path).await && !force {
+                    continue;
+                }
+            }
 
-        // if should show release notes, populate this list with the tools to show
-        if whats_new {
-            release_and_compat_refs.push((release.clone(), compat_tool.clone()));
-        }
+            // if should show release notes, populate this list with the tools to show
+            if whats_new {
+                release_and_compat_refs.push((release.clone(), compat_tool.clone()));
+            }
 
-        entries.push((download, release, app_inst, compat_tool));
+            entries.push((download, release, app_inst.clone(), compat_tool));
+        }
     }
 
     let (groups, unique_releases) = group_and_dedup_releases(entries);
@@ -247,7 +418,9 @@ pub async fn run_quick_downloads(force: bool, whats_new: bool) -> Result<Vec<Rel
 
         let release_and_compat_refs: Vec<(Release, CompatTool)> = release_and_compat_refs
             .into_iter()
-            .filter(|(release, _compat)| seen_tags.insert(release.tag_name.clone()))
+            .filter(|(release, compat)| {
+                seen_tags.insert((compat.name.clone(), release.tag_name.clone()))
+            })
             .collect();
 
         for (release, compat_tool) in release_and_compat_refs {
@@ -261,63 +434,200 @@ pub async fn run_quick_downloads(force: bool, whats_new: bool) -> Result<Vec<Rel
         .map(|(_, release, compat_tool, _)| (release.clone(), compat_tool.clone()))
         .collect();
 
-    // Queue one download task per group
-    let joins = FuturesUnordered::new();
-
-    for (_, (_download, _release, compat_tool, targets)) in groups {
-        joins.push(download_validate_unpack_to_targets(
-            _download,
-            compat_tool,
-            targets,
-            multi_progress.clone(),
-        ));
-    }
-
-    joins
+    // One download task per group, running at most `concurrency` at the same time
+    let total = groups.len();
+    let mut failed = 0;
+    stream::iter(groups.into_values())
+        .map(|(download, _release, compat_tool, targets)| {
+            install_with_progress(compat_tool, download, targets, multi_progress.clone())
+        })
+        .buffer_unordered(config::current().concurrency)
         .for_each(|res| {
             if let Err(e) = res {
-                multi_progress.println(format!("{e}")).unwrap();
+                failed += 1;
+                if output::is_machine() {
+                    output::emit(output::Record::Error {
+                        message: format!("{e:#}"),
+                    });
+                } else {
+                    multi_progress.println(format!("{e}")).unwrap();
+                }
             }
             future::ready(())
         })
         .await;
     multi_progress.clear().unwrap();
 
+    if let Some(retention) = config::current().retention
+        && !cancel::is_cancelled()
+    {
+        for (app_inst, compat_tool) in &updated_tools {
+            let releases = releases_cache
+                .get(&compat_tool.name)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if let Err(e) = prune_old_versions(app_inst, compat_tool, releases, retention).await {
+                output::info(format!("{e:#}"));
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(anyhow!("{failed} of {total} downloads failed"));
+    }
+
     // Prompt to view changelogs after downloads complete
-    prompt_changelogs(&release_tool_pairs).await;
+    if output::is_interactive() {
+        prompt_changelogs(&release_tool_pairs).await;
+    }
 
     Ok(unique_releases)
 }
 
-pub(crate) fn format_whatsnew(release: &Release, compat_tool: &CompatTool) -> String {
-    const WHATS_NEW_LINES: usize = 40;
-    let mut output = String::new();
+/// Removes the oldest installed versions of a tool, keeping the newest `retention` ones.
+/// Versions are read from the install manifest, or guessed from the folder names.
+/// Folders that can't be matched to a version of the tool are never removed
+async fn prune_old_versions(
+    app_inst: &apps::AppInstallations,
+    compat_tool: &CompatTool,
+    releases: &[Release],
+    retention: usize,
+) -> Result<()> {
+    let Some(install_dir) = app_inst.installation_dir(compat_tool) else {
+        return Ok(());
+    };
+    if !files::check_if_exists(&install_dir).await {
+        return Ok(());
+    }
+
+    let manifest = installed::load_manifest();
+    let mut installed: Vec<(String, String)> = files::list_folders_in_path(&install_dir)
+        .await?
+        .into_iter()
+        .filter_map(|folder| {
+            if let Some(record) = manifest.find(&install_dir.join(&folder)) {
+                return (record.tool == compat_tool.name).then(|| (folder, record.version.clone()));
+            }
+            let version = compat_tool
+                .version_from_installation_name(&folder)
+                .or_else(|| {
+                    releases
+                        .iter()
+                        .find(|r| compat_tool.installation_name(&r.tag_name) == folder)
+                        .map(|r| r.tag_name.clone())
+                })?;
+            Some((folder, version))
+        })
+        .collect();
+    // newest first
+    installed.sort_by(|(_, a), (_, b)| utils::compare_versions(b, a));
 
-    let url = format!(
+    for (folder, version) in installed.into_iter().skip(retention) {
+        remove_installation(
+            app_inst,
+            compat_tool,
+            version,
+            install_dir.join(&folder),
+            &Manifest::path()?,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Deletes the installed folder of a version of `compat_tool` with its record in the
+/// manifest at `manifest_path`, and reports it
+pub(crate) async fn remove_installation(
+    app_inst: &apps::AppInstallations,
+    compat_tool: &CompatTool,
+    version: String,
+    path: PathBuf,
+    manifest_path: &Path,
+) -> Result<()> {
+    files::remove_dir_all(&path).await?;
+    if let Err(e) = manifest::record_removal_in(manifest_path, &path).await {
+        output::info(format!("{e:#}"));
+    }
+    if output::is_machine() {
+        output::emit(output::Record::Removed {
+            tool: compat_tool.name.clone(),
+            version,
+            target: output::TargetRecord::from(app_inst),
+            path,
+        });
+    } else {
+        let folder = path.file_name().unwrap_or_default().to_string_lossy();
+        println!("Removed {} from {}", folder, app_inst);
+    }
+    Ok(())
+}
+
+/// Prints the detected apps, or records them in machine-readable mode
+fn report_found_apps(found_apps: &[apps::AppInstallations]) {
+    if output::is_machine() {
+        output::emit(output::Record::DetectedApps {
+            apps: found_apps.iter().map(output::TargetRecord::from).collect(),
+        });
+    } else {
+        println!(
+            "Found the following apps: {}",
+            found_apps
+                .iter()
+                .map(|app| app.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+    }
+}
+
+/// User-facing URL of the release page on its forge
+pub(crate) fn release_url(release: &Release, compat_tool: &CompatTool) -> String {
+    format!(
         "{}{}/{}/releases/tag/{}",
         compat_tool.forge.get_user_url(),
         compat_tool.repository_account,
         compat_tool.repository_name,
         release.tag_name
-    );
+    )
+}
+
+/// Builds the what's new record of a release for machine-readable output
+pub(crate) fn whatsnew_record(
+    release: &Release,
+    compat_tool: &CompatTool,
+    update_available: Option<bool>,
+) -> output::Record {
+    output::Record::WhatsNew {
+        tool: compat_tool.name.clone(),
+        version: release.tag_name.clone(),
+        published_at: release.published_date().map(str::to_owned),
+        prerelease: release.prerelease,
+        url: release_url(release, compat_tool),
+        notes: release.body.clone(),
+        update_available,
+    }
+}
+
+/// The notes of `release` with a header and the link to its release page
+pub(crate) fn format_whatsnew(
+    release: &Release,
+    compat_tool: &CompatTool,
+    renderer: &Renderer,
+) -> String {
+    let mut output = String::new();
+
+    let url = release_url(release, compat_tool);
 
     output.push('\n');
     output.push_str(&format!("  ┌{}┐\n", "─".repeat(50)));
     output.push_str(&format!("  │ {:^48} │\n", "Release Notes"));
     output.push_str(&format!("  └{}┘\n", "─".repeat(50)));
-    output.push_str(&format!("\n  {}: {}\n", release.tag_name, url));
-
-    match &release.body {
-        Some(body) => {
-            let all_lines: Vec<&str> = body.lines().collect();
-            let notes = all_lines[..all_lines.len().min(WHATS_NEW_LINES)].join("\n");
-            if all_lines.len() > WHATS_NEW_LINES {
-                output.push_str(&format!("\n{}\n  ⋯ [truncated]\n", notes));
-            } else {
-                output.push_str(&format!("\n{}\n", notes));
-            }
-        }
-        None => output.push_str("\n  (no release notes)\n"),
+    output.push_str(&format!("\n  {release}: {url}\n"));
+
+    let notes = renderer.render(release.body.as_deref().unwrap_or_default());
+    if notes.is_empty() {
+        output.push_str("\n  (no release notes)\n");
+    } else {
+        output.push_str(&format!("\n{notes}\n"));
     }
 
     output.push('\n');
@@ -325,7 +635,16 @@ pub(crate) fn format_whatsnew(release: &Release, compat_tool: &CompatTool) -> St
 }
 
 pub(crate) async fn show_whatsnew(release: &Release, compat_tool: &CompatTool) {
-    print!("{}", format_whatsnew(release, compat_tool));
+    if output::is_machine() {
+        output::emit(whatsnew_record(release, compat_tool, None));
+        return;
+    }
+    output::page(format_whatsnew(
+        release,
+        compat_tool,
+        &Renderer::for_stdout(),
+    ))
+    .await;
 }
 
 async fn prompt_changelogs(releases: &[(Release, CompatTool)]) {
@@ -345,65 +664,111 @@ async fn prompt_changelogs(releases: &[(Release, CompatTool)]) {
 
 /// Standalone mode: check installed apps, fetch latest releases, show changelogs
 /// and print "Update available to version $v" for each tool with a newer release.
+/// Each default tool is checked once, against every app it is a default of.
+/// When an older version is installed, the notes of every release since then are shown.
 pub(crate) async fn check_whats_new() -> Result<()> {
     let found_apps = apps::list_installed_apps().await;
     if found_apps.is_empty() {
-        println!("No apps found. Please install at least one app before using this feature.");
+        output::info("No apps found. Please install at least one app before using this feature.");
         return Ok(());
     }
-    println!(
-        "Found the following apps: {}",
-        found_apps
-            .iter()
-            .map(|app| app.to_string())
-            .collect::<Vec<String>>()
-            .join(", ")
-    );
-
-    let mut releases_cache: HashMap<String, ReleaseList> = HashMap::new();
-    let mut tool_entries: Vec<(apps::AppInstallations, CompatTool)> = vec![];
+    report_found_apps(&found_apps);
 
+    // Default tools in the order first found, with the apps using them
+    let mut tool_entries: Vec<(CompatTool, Vec<apps::AppInstallations>)> = vec![];
     for app_inst in found_apps {
-        let compat_tool = app_inst.as_app().default_compatibility_tool();
-        tool_entries.push((app_inst, compat_tool));
-    }
-
-    let mut seen_tools: HashSet<String> = HashSet::new();
-
-    for (app_inst, compat_tool) in tool_entries {
-        if !seen_tools.insert(compat_tool.name.clone()) {
-            continue;
+        for compat_tool in app_inst.as_app().default_compatibility_tools() {
+            match tool_entries
+                .iter_mut()
+                .find(|(tool, _)| tool.name == compat_tool.name)
+            {
+                Some((_, app_insts)) => app_insts.push(app_inst.clone()),
+                None => tool_entries.push((compat_tool, vec![app_inst.clone()])),
+            }
         }
+    }
 
-        let release_list = match releases_cache.get(&compat_tool.name) {
-            Some(list) => list.clone(),
-            None => match downloads::list_releases(&compat_tool).await {
-                Ok(list) => {
-                    releases_cache.insert(compat_tool.name.clone(), list.clone());
-                    list
-                }
-                Err(e) => {
-                    eprintln!("Failed to fetch releases for {}: {}", compat_tool.name, e);
-                    continue;
+    for (compat_tool, app_insts) in tool_entries {
+        let release_list = match downloads::list_releases(&compat_tool).await {
+            Ok(list) => list,
+            Err(e) => {
+                let message = format!("Failed to fetch releases for {}: {}", compat_tool.name, e);
+                if output::is_machine() {
+                    output::emit(output::Record::Error { message });
+                } else {
+                    eprintln!("{message}");
                 }
-            },
+                continue;
+            }
         };
 
-        if release_list.is_empty() {
+        let Some(latest) = downloads::latest_release(&release_list, &compat_tool) else {
             continue;
+        };
+
+        let installed_version =
+            changelog::newest_installed_version(&app_insts, &compat_tool, &release_list).await;
+        let install_name = compat_tool.installation_name(&latest.tag_name);
+        let mut outdated_apps = vec![];
+        for app_inst in app_insts {
+            let installed = match app_inst.installation_dir(&compat_tool) {
+                Some(install_dir) => files::check_if_exists(&install_dir.join(&install_name)).await,
+                None => false,
+            };
+            if !installed {
+                outdated_apps.push(app_inst);
+            }
         }
 
-        let latest = &release_list[0];
-        show_whatsnew(latest, &compat_tool).await;
+        // every release since the installed version, or only the latest one
+        let since = installed_version
+            .filter(|_| !outdated_apps.is_empty())
+            .map(|version| {
+                let releases = release_notes::releases_since(&release_list, &compat_tool, &version);
+                (version, releases)
+            })
+            .filter(|(_, releases)| !releases.is_empty());
 
-        let install_name = compat_tool.installation_name(&latest.tag_name);
-        let install_dir = app_inst.default_install_dir();
-        let install_path = PathBuf::from(install_dir.as_str()).join(&install_name);
+        if output::is_machine() {
+            let releases = match &since {
+                Some((_, releases)) => releases.clone(),
+                None => vec![latest],
+            };
+            for release in releases {
+                output::emit(whatsnew_record(
+                    release,
+                    &compat_tool,
+                    Some(!outdated_apps.is_empty()),
+                ));
+            }
+            continue;
+        }
+        match &since {
+            Some((version, releases)) => {
+                output::page(changelog::format_release_range(
+                    &compat_tool,
+                    Some(version),
+                    releases,
+                    false,
+                    &Renderer::for_stdout(),
+                ))
+                .await
+            }
+            None => show_whatsnew(latest, &compat_tool).await,
+        }
 
-        if files::check_if_exists(&install_path).await {
+        if outdated_apps.is_empty() {
             println!("Already up to date ({})\n", latest.tag_name);
         } else {
-            println!("Update available to version {}\n", latest.tag_name);
+            println!(
+                "Update available to version {} for {}\n",
+                latest.tag_name,
+                outdated_apps
+                    .iter()
+                    .map(|app_inst| app_inst.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
         }
     }
 
@@ -431,18 +796,60 @@ pub(crate) async fn check_changelog_menu() -> Result<Vec<Release>> {
         }
     };
 
+    // preselect the releases since the installed version
+    let installed_apps: Vec<apps::AppInstallations> = apps::list_installed_apps()
+        .await
+        .into_iter()
+        .filter(|app_inst| app_inst.as_app().is_compatible(&selected_tool))
+        .collect();
+    let installed_version =
+        changelog::newest_installed_version(&installed_apps, &selected_tool, &release_list).await;
+    let newer: Vec<usize> = match &installed_version {
+        Some(version) => {
+            let since = release_notes::releases_since(&release_list, &selected_tool, version);
+            release_list
+                .iter()
+                .enumerate()
+                .filter(|(_, release)| since.iter().any(|r| r.tag_name == release.tag_name))
+                .map(|(i, _)| i)
+                .collect()
+        }
+        None => vec![],
+    };
+    let defaults = if newer.is_empty() { vec![0] } else { newer };
+    let message = match &installed_version {
+        Some(version) => format!("Select the versions to view changelog (installed: {version}):"),
+        None => "Select the versions to view changelog:".to_owned(),
+    };
+
     let selected_releases = stream::iter(
-        helper_menus::multiple_select_menu("Select the versions to view changelog:", release_list)
-            .unwrap_or_else(|e| {
-                eprintln!("The tag list could not be processed.\nError: {e}");
-                vec![]
-            }),
+        helper_menus::multiple_select_menu(&message, release_list, &defaults).unwrap_or_else(|e| {
+            eprintln!("The tag list could not be processed.\nError: {e}");
+            vec![]
+        }),
     )
     .collect::<Vec<_>>()
     .await;
 
-    for release in &selected_releases {
-        show_whatsnew(release, &selected_tool).await;
+    match selected_releases.len() {
+        0 => {}
+        1 => show_whatsnew(&selected_releases[0], &selected_tool).await,
+        count => {
+            let collapse = helper_menus::confirm_menu(
+                format!("Only show the first line of the notes of the {count} releases?"),
+                String::from("If you choose no, the full notes of every release are shown."),
+                false,
+            );
+            let releases: Vec<&Release> = selected_releases.iter().collect();
+            output::page(changelog::format_release_range(
+                &selected_tool,
+                None,
+                &releases,
+                collapse,
+                &Renderer::for_stdout(),
+            ))
+            .await;
+        }
     }
 
     if !selected_releases.is_empty() {
@@ -508,7 +915,14 @@ pub async fn download_to_selected_app(app: Option<apps::App>) -> Result<Vec<Rele
         // Use the default for the app
         Some(app) => CompatTool::sources_for_app(&app),
         // Or have the user select which one
-        None => CompatTools.clone(),
+        None => {
+            let arch = cpu::target_arch();
+            CompatTools
+                .iter()
+                .filter(|compat_tool| compat_tool.supports_arch(&arch))
+                .cloned()
+                .collect()
+        }
     };
 
     // TODO: maybe change to multi-select ?
@@ -530,11 +944,19 @@ pub async fn download_to_selected_app(app: Option<apps::App>) -> Result<Vec<Rele
             }
         };
 
-        // Let the user choose which releases they want to use
+        // Let the user choose which releases they want to use, the latest one is preselected
+        let latest = downloads::latest_release(&release_list, &selected_tool)
+            .and_then(|latest| {
+                release_list
+                    .iter()
+                    .position(|release| release.tag_name == latest.tag_name)
+            })
+            .unwrap_or(0);
         stream::iter(
             helper_menus::multiple_select_menu(
                 "Select the versions you want to download:",
                 release_list,
+                &[latest],
             )
             .unwrap_or_else(|e| {
                 eprintln!("The tag list could not be processed.\nError: {e}");
@@ -562,13 +984,14 @@ pub async fn download_to_selected_app(app: Option<apps::App>) -> Result<Vec<Rele
 
     // let tool = selected_tool.clone();
     // Check if the selected tool has multiple asset variations
-    let downloads: Vec<Download> = if selected_tool.has_multiple_asset_variations {
+    let downloads: Vec<Download> = if selected_tool.has_variants() {
         releases
             .iter()
             .map(|release| {
                 let variants = release.get_all_download_variants(&app_inst, &selected_tool);
 
                 architecture_variants::select_architecture_variant(
+                    &selected_tool,
                     &release.tag_name,
                     variants,
                     false,
@@ -583,7 +1006,7 @@ pub async fn download_to_selected_app(app: Option<apps::App>) -> Result<Vec<Rele
             .collect()
     };
 
-    let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(20));
+    let multi_progress = MultiProgress::with_draw_target(output::draw_target());
 
     let tasks = downloads.into_iter().map(|download| {
         // let release = release.clone();
@@ -591,9 +1014,8 @@ pub async fn download_to_selected_app(app: Option<apps::App>) -> Result<Vec<Rele
         let tool = selected_tool.clone();
         let app_inst = app_inst.clone();
 
-        // Handle tools with multiple architecture variants
         tokio::spawn(async move {
-            download_validate_unpack_with_download(download.clone(), app_inst, tool, progress).await
+            install_with_progress(tool, download, vec![app_inst], progress).await
         })
     });
 
@@ -623,249 +1045,6 @@ pub async fn download_to_selected_app(app: Option<apps::App>) -> Result<Vec<Rele
     Ok(releases)
 }
 
-async fn download_validate_unpack_with_download(
-    download: Download,
-    for_app: apps::AppInstallations,
-    compat_tool: CompatTool,
-    multi_progress: MultiProgress,
-) -> Result<()> {
-    let install_dir = for_app.installation_dir(&compat_tool).unwrap();
-    let file = download_file(&download, multi_progress.clone())
-        .await
-        .with_context(|| {
-            format!(
-                "Error downloading {}, make sure you're connected to the internet",
-                download.version
-            )
-        })?;
-    match download.hash_sum {
-        Some(ref git_hash_sum) => {
-            let hash_content = &downloads::download_file_into_memory(&git_hash_sum.sum_content)
-                .await
-                .with_context(|| {
-                    format!(
-                        "Error getting expected download hash for {}",
-                        download.version
-                    )
-                })?;
-            let hash_sum = hashing::HashSums {
-                sum_content: hash_content.to_owned(),
-                sum_type: git_hash_sum.sum_type.clone(),
-            };
-
-            validate_file(&download.file_name, &file, hash_sum, multi_progress.clone()).await?;
-        }
-        None => {
-            println!("No sum files available, skipping");
-        }
-    }
-
-    let install_name = compat_tool.installation_name(&download.version);
-    let install_path = install_dir.join(install_name.clone());
-    if files::check_if_exists(&install_path).await {
-        fs::remove_dir_all(&install_path).await.with_context(|| {
-            format!(
-                "Error removing existing install at {}",
-                install_path.display()
-            )
-        })?;
-    }
-
-    // Open the compressed file
-    let compressed_file = File::open(&file)
-        .await
-        .with_context(|| format!("Error opening compressed file {}", file.display()))?;
-
-    // Wrap the file with progress tracking to read compressed bytes
-    let unpack_progress_bar = init_unpack_progress(&install_dir.clone(), &file, multi_progress)
-        .await
-        .with_context(|| format!("Error unpacking {}", file.display()))?;
-
-    let progress_reader = unpack_progress_bar.wrap_async_read(compressed_file);
-
-    // Wrap with BufReader to provide AsyncBufRead for the decompressor
-    let buf_reader = BufReader::new(progress_reader);
-
-    // Create Decompressor from the BufReader
-    let path_str = file.to_string_lossy();
-    let decompressor = files::Decompressor::from_reader(buf_reader, &path_str)
-        .with_context(|| format!("Error checking file type of {}", file.display()))?;
-
-    files::unpack_file(&compat_tool, &download, decompressor, &install_dir)
-        .await
-        .with_context(|| format!("Error unpacking {}", file.display()))?;
-
-    unpack_progress_bar.set_style(get_message_bar_style().await);
-    unpack_progress_bar.finish_with_message(format!(
-        "Done! {} installed in {}/{}\nYour app might require a restart to detect {}",
-        compat_tool,
-        download
-            .for_app
-            .installation_dir(&compat_tool)
-            .unwrap()
-            .to_str()
-            .unwrap(),
-        install_name,
-        compat_tool
-    ));
-    Ok(())
-}
-
-pub(crate) async fn init_download_progress_for_targets(
-    download: &Download,
-    targets: &[apps::AppInstallations],
-    multi_progress: MultiProgress,
-) -> ProgressBar {
-    let progress_bar = multi_progress.add(ProgressBar::new(download.size));
-    progress_bar.set_style(get_progress_style().await);
-    let app_names: Vec<String> = targets.iter().map(|a| a.to_string()).collect();
-    progress_bar.set_message(format!(
-        "Downloading {} for {}",
-        download.download_url.split('/').next_back().unwrap(),
-        app_names.join(", ")
-    ));
-    progress_bar
-}
-
-async fn download_validate_unpack_to_targets(
-    download: Download,
-    compat_tool: CompatTool,
-    targets: Vec<apps::AppInstallations>,
-    multi_progress: MultiProgress,
-) -> Result<()> {
-    if targets.is_empty() {
-        return Ok(());
-    }
-
-    let output_dir = download.download_dir()?;
-
-    if files::check_if_exists(&output_dir).await {
-        fs::remove_dir_all(&output_dir).await?;
-    }
-
-    let file = OpenOptions::new()
-        .create_new(true)
-        .write(true)
-        .open(&output_dir)
-        .await
-        .with_context(|| {
-            format!(
-                "[Download] Failed creating destination file: {}",
-                output_dir.display()
-            )
-        })?;
-
-    let download_progress_bar =
-        init_download_progress_for_targets(&download, &targets, multi_progress.clone()).await;
-
-    downloads::download_to_async_write(
-        &download.download_url,
-        &mut download_progress_bar.wrap_async_write(file),
-    )
-    .await?;
-
-    download_progress_bar.set_style(get_message_bar_style().await);
-    download_progress_bar.finish_with_message(download_progress_bar.message().replacen(
-        "Downloading",
-        "Downloaded",
-        1,
-    ));
-
-    if let Some(ref git_hash_sum) = download.hash_sum {
-        let hash_content = &downloads::download_file_into_memory(&git_hash_sum.sum_content)
-            .await
-            .with_context(|| {
-                format!(
-                    "Error getting expected download hash for {}",
-                    download.version
-                )
-            })?;
-        let hash_sum = hashing::HashSums {
-            sum_content: hash_content.to_owned(),
-            sum_type: git_hash_sum.sum_type.clone(),
-        };
-
-        validate_file(
-            &download.file_name,
-            &output_dir,
-            hash_sum,
-            multi_progress.clone(),
-        )
-        .await?;
-    }
-
-    let install_name = compat_tool.installation_name(&download.version);
-
-    let unpack_joins = FuturesUnordered::new();
-
-    for app_inst in targets {
-        let install_dir = app_inst.installation_dir(&compat_tool).unwrap();
-        let install_path = install_dir.join(&install_name);
-        let output_dir = output_dir.clone();
-        let download = download.clone();
-        let compat_tool = compat_tool.clone();
-        let multi_progress = multi_progress.clone();
-        let install_name = install_name.clone();
-
-        unpack_joins.push(async move {
-            if files::check_if_exists(&install_path).await {
-                fs::remove_dir_all(&install_path).await.with_context(|| {
-                    format!(
-                        "Error removing existing install at {}",
-                        install_path.display()
-                    )
-                })?;
-            }
-
-            // Open the compressed file
-            let compressed_file = File::open(&output_dir).await.with_context(|| {
-                format!("Error opening compressed file {}", output_dir.display())
-            })?;
-
-            // Wrap the file with progress tracking to read compressed bytes
-            let unpack_progress_bar =
-                init_unpack_progress(&install_dir, &output_dir, multi_progress.clone())
-                    .await
-                    .with_context(|| format!("Error unpacking {}", output_dir.display()))?;
-
-            let progress_reader = unpack_progress_bar.wrap_async_read(compressed_file);
-
-            // Wrap with BufReader to provide AsyncBufRead for the decompressor
-            let buf_reader = BufReader::new(progress_reader);
-
-            // Create Decompressor from the BufReader
-            let path_str = output_dir.to_string_lossy();
-            let decompressor = files::Decompressor::from_reader(buf_reader, &path_str)
-                .with_context(|| format!("Error checking file type of {}", output_dir.display()))?;
-
-            files::unpack_file(&compat_tool, &download, decompressor, &install_dir)
-                .await
-                .with_context(|| format!("Error unpacking {}", output_dir.display()))?;
-
-            unpack_progress_bar.set_style(get_message_bar_style().await);
-            unpack_progress_bar.finish_with_message(format!(
-                "Done! {} installed in {}/{}",
-                compat_tool,
-                install_dir.display(),
-                install_name,
-            ));
-
-            Ok::<_, anyhow::Error>(())
-        });
-    }
-
-    unpack_joins
-        .for_each(|res| {
-            if let Err(e) = res {
-                multi_progress.println(format!("{e}")).unwrap();
-            }
-            future::ready(())
-        })
-        .await;
-
-    Ok(())
-}
-
 /// Checks if the selected Release/version is already installed.
 ///
 /// Will prompt the user to overwrite existing files
@@ -1053,6 +1232,45 @@ mod tests {
         assert_eq!(releases[0].tag_name, "GE-Proton9-10");
     }
 
+    #[test]
+    fn test_group_and_dedup_same_tag_different_tools_kept() {
+        let d1 = make_download("https://example.com/a.tar.gz", "a.tar.gz", 100);
+        let d2 = make_download("https://example.com/b.tar.gz", "b.tar.gz", 200);
+        let other_tool = CompatTool::new_custom(
+            "OtherTool".into(),
+            Forge::GitHub,
+            "owner".into(),
+            "other".into(),
+            ToolType::WineBased,
+            None,
+            None,
+            None,
+        );
+        let entries = vec![
+            (
+                d1,
+                make_release("v1.0"),
+                AppInstallations::Steam,
+                make_compat_tool(),
+            ),
+            (
+                d2,
+                make_release("v1.0"),
+                AppInstallations::Steam,
+                other_tool,
+            ),
+        ];
+
+        let (groups, releases) = group_and_dedup_releases(entries);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            releases.len(),
+            2,
+            "every tool keeps its release, even with the same tag_name"
+        );
+    }
+
     #[test]
     fn test_group_and_dedup_single_entry_passes_through() {
         let d = make_download("https://example.com/a.tar.gz", "a.tar.gz", 100);
@@ -1098,7 +1316,7 @@ mod tests {
     fn test_format_whatsnew_with_body() {
         let release = make_release_with_body("GE-Proton9-10", Some("Fixed bug\nAdded feature"));
         let ct = make_compat_tool();
-        let output = format_whatsnew(&release, &ct);
+        let output = format_whatsnew(&release, &ct, &Renderer::PLAIN);
         assert!(output.contains("Release Notes"));
         assert!(output.contains("GE-Proton9-10"));
         assert!(output.contains("github.com"));
@@ -1110,26 +1328,37 @@ mod tests {
     fn test_format_whatsnew_without_body() {
         let release = make_release_with_body("GE-Proton9-10", None);
         let ct = make_compat_tool();
-        let output = format_whatsnew(&release, &ct);
+        let output = format_whatsnew(&release, &ct, &Renderer::PLAIN);
         assert!(output.contains("no release notes"));
     }
 
     #[test]
-    fn test_format_whatsnew_truncated() {
+    fn test_format_whatsnew_prerelease() {
+        let release: Release = serde_json::from_value(json!({
+            "tag_name": "GE-Proton10-1-rc1",
+            "name": "GE-Proton10-1-rc1",
+            "url": null,
+            "assets": [],
+            "published_at": "2025-03-04T12:00:00Z",
+            "prerelease": true
+        }))
+        .unwrap();
+        let output = format_whatsnew(&release, &make_compat_tool(), &Renderer::PLAIN);
+        assert!(output.contains("GE-Proton10-1-rc1 [pre-release] (2025-03-04): https://"));
+    }
+
+    #[test]
+    fn test_format_whatsnew_markdown() {
         let long_body = (0..50)
-            .map(|i| format!("line {}", i))
+            .map(|i| format!("- line {i}"))
             .collect::<Vec<_>>()
             .join("\n");
-        let release = make_release_with_body("GE-Proton9-10", Some(&long_body));
-        let ct = make_compat_tool();
-        let output = format_whatsnew(&release, &ct);
-        assert!(
-            output.contains("[truncated]"),
-            "body with >40 lines should show [truncated]"
-        );
-        assert!(
-            !output.contains("line 41"),
-            "line 41 (42nd line) should be beyond the 40-line cutoff"
-        );
+        let body = format!("## Fixes\n<!-- hidden -->\n\n{long_body}");
+        let release = make_release_with_body("GE-Proton9-10", Some(&body));
+        let output = format_whatsnew(&release, &make_compat_tool(), &Renderer::PLAIN);
+        assert!(output.contains("\nFixes\n\n- line 0\n"));
+        assert!(!output.contains("hidden"));
+        // long notes are paged instead of truncated
+        assert!(output.contains("- line 49\n"));
     }
 }
@@ -2,16 +2,17 @@ use inquire::{Confirm, InquireError, MultiSelect};
 
 /// Prompt the user to select multiple possible options with an inquire::MultiSelect menu
 ///
-/// The first option is selected
+/// The options at the indexes in `defaults` are selected
 pub(crate) fn multiple_select_menu<T>(
     message: &str,
     options: Vec<T>,
+    defaults: &[usize],
 ) -> Result<Vec<T>, InquireError>
 where
     T: std::fmt::Display,
 {
     MultiSelect::new(message, options)
-        .with_default(&[0])
+        .with_default(defaults)
         .prompt()
 }
 
@@ -0,0 +1,313 @@
+//! `installed` subcommand, an inventory of the tools installed in every app.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use indicatif::HumanBytes;
+use libprotonup::apps::{self, AppInstallations};
+use libprotonup::downloads::{self, Release};
+use libprotonup::files;
+use libprotonup::manifest::Manifest;
+use libprotonup::sources::{CompatTool, CompatTools};
+use libprotonup::utils;
+
+use crate::cli::InstalledArgs;
+use crate::output::{self, Record, TargetRecord, format_table};
+
+/// Release lists fetched so far, by tool name.
+/// A failed fetch is stored as an empty list, so it is only attempted once
+#[derive(Default)]
+pub(crate) struct ReleaseCache(HashMap<String, Vec<Release>>);
+
+impl ReleaseCache {
+    pub(crate) async fn releases(&mut self, compat_tool: &CompatTool) -> &[Release] {
+        if !self.0.contains_key(&compat_tool.name) {
+            let releases = match downloads::list_releases(compat_tool).await {
+                Ok(releases) => releases,
+                Err(e) => {
+                    eprintln!("Failed to fetch releases for {}: {}", compat_tool.name, e);
+                    vec![]
+                }
+            };
+            self.0.insert(compat_tool.name.clone(), releases);
+        }
+        &self.0[&compat_tool.name]
+    }
+
+    /// Newest release, see [`downloads::latest_release`]
+    pub(crate) async fn latest(&mut self, compat_tool: &CompatTool) -> Option<String> {
+        let releases = self.releases(compat_tool).await;
+        downloads::latest_release(releases, compat_tool).map(|release| release.tag_name.clone())
+    }
+}
+
+/// A folder found in an app installation
+struct InstalledEntry {
+    target: AppInstallations,
+    folder: String,
+    path: PathBuf,
+    tool: Option<String>,
+    version: Option<String>,
+    size: Option<u64>,
+    modified: Option<SystemTime>,
+    latest: Option<String>,
+}
+
+impl InstalledEntry {
+    fn is_newest(&self) -> Option<bool> {
+        Some(self.version.as_ref()? == self.latest.as_ref()?)
+    }
+
+    fn modified(&self) -> Option<String> {
+        self.modified
+            .map(|time| humantime::format_rfc3339_seconds(time).to_string())
+    }
+
+    fn record(&self) -> Record {
+        Record::Installed {
+            target: TargetRecord::from(&self.target),
+            folder: self.folder.clone(),
+            path: self.path.clone(),
+            tool: self.tool.clone(),
+            version: self.version.clone(),
+            size: self.size,
+            modified: self.modified(),
+            latest: self.latest.clone(),
+            newest: self.is_newest(),
+        }
+    }
+
+    fn columns(&self) -> [String; 7] {
+        let dash = || "-".to_owned();
+        let newest = match (self.is_newest(), &self.latest) {
+            (Some(true), _) => "yes".to_owned(),
+            (Some(false), Some(latest)) => format!("no ({latest})"),
+            _ => dash(),
+        };
+        [
+            self.target.to_string(),
+            self.folder.clone(),
+            self.tool.clone().unwrap_or_else(dash),
+            self.version.clone().unwrap_or_else(dash),
+            self.size
+                .map(|size| HumanBytes(size).to_string())
+                .unwrap_or_else(dash),
+            self.modified().unwrap_or_else(dash),
+            newest,
+        ]
+    }
+}
+
+/// Tools that could have been installed into `parent` for this app
+pub(crate) fn candidate_tools(target: &AppInstallations, parent: &Path) -> Vec<CompatTool> {
+    let parent = utils::expand_tilde(parent);
+    CompatTools
+        .iter()
+        .filter(|tool| match target {
+            // custom folders hold any tool, extracted in place
+            AppInstallations::Custom(_) => true,
+            _ => target.as_app().is_compatible(tool) && target.installation_dir(tool) == parent,
+        })
+        .cloned()
+        .collect()
+}
+
+/// The install manifest, or an empty one if it can't be read
+pub(crate) fn load_manifest() -> Manifest {
+    Manifest::load().unwrap_or_else(|e| {
+        eprintln!("{e:#}");
+        Manifest::default()
+    })
+}
+
+/// Full path of a folder listed in a target
+pub(crate) fn folder_path(folder: &files::Folder) -> PathBuf {
+    let (parent, name) = &folder.0;
+    utils::expand_tilde(parent.join(name)).unwrap_or_else(|| parent.join(name))
+}
+
+/// Maps a folder back to the tool and version it was installed from.
+/// The install manifest is used first. Otherwise, the folder name is matched against the
+/// tool templates, and then against the installation name of each release, which requires
+/// fetching them. Releases are not fetched for custom folders, which could hold any tool.
+pub(crate) async fn identify(
+    target: &AppInstallations,
+    folder: &files::Folder,
+    manifest: &Manifest,
+    cache: &mut ReleaseCache,
+) -> Option<(CompatTool, String)> {
+    let candidates = candidate_tools(target, &folder.0.0);
+    if let Some(record) = manifest.find(&folder_path(folder))
+        && let Some(compat_tool) = candidates.iter().find(|tool| tool.name == record.tool)
+    {
+        return Some((compat_tool.clone(), record.version.clone()));
+    }
+
+    let folder = folder.0.1.as_str();
+    for compat_tool in &candidates {
+        if let Some(version) = compat_tool.version_from_installation_name(folder) {
+            return Some((compat_tool.clone(), version));
+        }
+    }
+    if matches!(target, AppInstallations::Custom(_)) {
+        return None;
+    }
+    for compat_tool in &candidates {
+        let release = cache
+            .releases(compat_tool)
+            .await
+            .iter()
+            .find(|release| compat_tool.installation_name(&release.tag_name) == folder);
+        if let Some(release) = release {
+            return Some((compat_tool.clone(), release.tag_name.clone()));
+        }
+    }
+    None
+}
+
+async fn inspect(
+    target: &AppInstallations,
+    folder: files::Folder,
+    manifest: &Manifest,
+    cache: &mut ReleaseCache,
+) -> InstalledEntry {
+    let path = folder_path(&folder);
+    let identified = identify(target, &folder, manifest, cache).await;
+    let files::Folder((_, name)) = folder;
+    let (tool, version, latest) = match identified {
+        Some((compat_tool, version)) => {
+            let latest = cache.latest(&compat_tool).await;
+            (Some(compat_tool.name), Some(version), latest)
+        }
+        None => (None, None, None),
+    };
+
+    let size = match files::dir_size(&path).await {
+        Ok(size) => Some(size),
+        Err(e) => {
+            eprintln!("{e:#}");
+            None
+        }
+    };
+    let modified = tokio::fs::metadata(&path)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    InstalledEntry {
+        target: target.clone(),
+        folder: name,
+        path,
+        tool,
+        version,
+        size,
+        modified,
+        latest,
+    }
+}
+
+/// The detected apps, followed by the custom folders
+pub(crate) async fn inventory_targets(paths: Vec<String>) -> Vec<AppInstallations> {
+    let mut targets = apps::list_installed_apps().await;
+    targets.extend(
+        paths
+            .into_iter()
+            .map(AppInstallations::new_custom_app_install),
+    );
+    targets
+}
+
+/// Folders installed in the target, sorted by path.
+/// Errors are reported on stderr, and an empty list is returned
+pub(crate) async fn installed_folders(target: &AppInstallations) -> Vec<files::Folder> {
+    match target.list_installed_versions().await {
+        Ok(mut folders) => {
+            folders.sort_by(|a, b| a.0.cmp(&b.0));
+            folders
+        }
+        Err(e) => {
+            eprintln!("Failed to list installed versions for {target}: {e}");
+            vec![]
+        }
+    }
+}
+
+/// Lists every folder installed in the detected apps and in the custom `--path` folders
+pub(crate) async fn run_installed(args: InstalledArgs) -> Result<()> {
+    let targets = inventory_targets(args.paths).await;
+
+    let manifest = load_manifest();
+    let mut cache = ReleaseCache::default();
+    let mut entries = vec![];
+    for target in &targets {
+        for folder in installed_folders(target).await {
+            entries.push(inspect(target, folder, &manifest, &mut cache).await);
+        }
+    }
+
+    if output::is_machine() {
+        for entry in &entries {
+            output::emit(entry.record());
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No installed tools found");
+        return Ok(());
+    }
+    let rows: Vec<[String; 7]> = entries.iter().map(InstalledEntry::columns).collect();
+    print!(
+        "{}",
+        format_table(
+            [
+                "APP", "FOLDER", "TOOL", "VERSION", "SIZE", "MODIFIED", "NEWEST"
+            ],
+            &rows
+        )
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libprotonup::config::TargetConfig;
+
+    #[test]
+    fn test_candidate_tools() {
+        let names = |target: &AppInstallations, parent: &str| {
+            candidate_tools(target, Path::new(parent))
+                .into_iter()
+                .map(|tool| tool.name)
+                .collect::<Vec<_>>()
+        };
+
+        let lutris_runtime = names(&AppInstallations::Lutris, "~/.local/share/lutris/runtime");
+        assert!(lutris_runtime.contains(&"DXVK".to_owned()));
+        assert!(!lutris_runtime.contains(&"GEProton".to_owned()));
+
+        let lutris_wine = names(
+            &AppInstallations::Lutris,
+            "~/.local/share/lutris/runners/wine",
+        );
+        assert!(lutris_wine.contains(&"GEProton".to_owned()));
+        assert!(!lutris_wine.contains(&"DXVK".to_owned()));
+
+        let steam = names(
+            &AppInstallations::Steam,
+            "~/.steam/steam/compatibilitytools.d/",
+        );
+        assert!(steam.contains(&"Luxtorpeda".to_owned()));
+        assert!(!steam.contains(&"DXVK".to_owned()));
+
+        let mut target = TargetConfig::new("bottles".to_owned(), "/games/bottles".to_owned());
+        target.compatible_tools = vec!["GEProton".to_owned(), "DXVK".to_owned()];
+        target.layout.runtime = "dxvk".to_owned();
+        let target = AppInstallations::Target(target);
+        assert_eq!(names(&target, "/games/bottles/dxvk"), vec!["DXVK"]);
+        assert_eq!(names(&target, "/games/bottles"), vec!["GEProton"]);
+    }
+}
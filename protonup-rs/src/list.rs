@@ -0,0 +1,209 @@
+//! `list` subcommand, prints the available releases of a compatibility tool.
+
+use anyhow::{Result, anyhow};
+use indicatif::HumanBytes;
+use libprotonup::apps::{self, AppInstallations};
+use libprotonup::architecture_variants;
+use libprotonup::config;
+use libprotonup::downloads::{self, Release};
+use libprotonup::files;
+use libprotonup::sources::CompatTool;
+
+use crate::cli::ListArgs;
+use crate::cli_mode;
+use crate::output::{self, Record, TargetRecord, format_table};
+
+/// A release with the details shown by `list`
+struct ReleaseEntry {
+    tag: String,
+    published: Option<String>,
+    prerelease: bool,
+    size: u64,
+    variants: Vec<String>,
+    installed_in: Vec<AppInstallations>,
+}
+
+impl ReleaseEntry {
+    async fn new(
+        release: &Release,
+        compat_tool: &CompatTool,
+        targets: &[AppInstallations],
+    ) -> ReleaseEntry {
+        let for_app = targets.first().cloned().unwrap_or_default();
+        let variants = if compat_tool.has_variants() {
+            let downloads = release.get_all_download_variants(&for_app, compat_tool);
+            architecture_variants::extract_variants(compat_tool, &downloads)
+                .into_iter()
+                .map(|variant| variant.name)
+                .collect()
+        } else {
+            vec![]
+        };
+
+        let mut installed_in = vec![];
+        for target in targets {
+            if let Some(dir) = target.installation_dir(compat_tool) {
+                let path = dir.join(compat_tool.installation_name(&release.tag_name));
+                if files::check_if_exists(&path).await {
+                    installed_in.push(target.clone());
+                }
+            }
+        }
+
+        ReleaseEntry {
+            tag: release.tag_name.clone(),
+            published: release.published_date().map(str::to_owned),
+            prerelease: release.prerelease,
+            size: release.get_download_info(&for_app, compat_tool).size,
+            variants,
+            installed_in,
+        }
+    }
+
+    fn record(&self, compat_tool: &CompatTool) -> Record {
+        Record::Release {
+            tool: compat_tool.name.clone(),
+            version: self.tag.clone(),
+            published_at: self.published.clone(),
+            prerelease: self.prerelease,
+            size: self.size,
+            variants: self.variants.clone(),
+            installed_in: self.installed_in.iter().map(TargetRecord::from).collect(),
+        }
+    }
+
+    fn columns(&self) -> [String; 5] {
+        let tag = if self.prerelease {
+            format!("{} (pre-release)", self.tag)
+        } else {
+            self.tag.clone()
+        };
+        let size = if self.size > 0 {
+            HumanBytes(self.size).to_string()
+        } else {
+            "-".to_owned()
+        };
+        [
+            tag,
+            self.published.clone().unwrap_or_else(|| "-".to_owned()),
+            size,
+            or_dash(self.variants.join(", ")),
+            or_dash(
+                self.installed_in
+                    .iter()
+                    .map(|target| target.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+        ]
+    }
+}
+
+fn or_dash(value: String) -> String {
+    if value.is_empty() {
+        "-".to_owned()
+    } else {
+        value
+    }
+}
+
+/// Keeps the releases to show, newest first as returned by the forge
+fn select_releases(
+    releases: &[Release],
+    limit: Option<usize>,
+    include_prereleases: bool,
+) -> Vec<&Release> {
+    releases
+        .iter()
+        .filter(|release| include_prereleases || !release.prerelease)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+/// Lists the releases of `--tool`, with the apps each one is already installed in
+pub(crate) async fn run_list(args: ListArgs) -> Result<()> {
+    let compat_tool = cli_mode::parse_compat_tool(&args.tool)?;
+    let releases = downloads::list_releases(&compat_tool)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch releases for {}: {}", compat_tool.name, e))?;
+
+    let targets: Vec<AppInstallations> = apps::list_installed_apps()
+        .await
+        .into_iter()
+        .filter(|app_inst| {
+            compat_tool
+                .compatible_applications
+                .contains(&app_inst.as_app())
+        })
+        .collect();
+
+    let mut entries = vec![];
+    let include_prereleases = config::current().includes_prereleases(&compat_tool);
+    for release in select_releases(&releases, args.limit, include_prereleases) {
+        entries.push(ReleaseEntry::new(release, &compat_tool, &targets).await);
+    }
+
+    if output::is_machine() {
+        for entry in &entries {
+            output::emit(entry.record(&compat_tool));
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No releases found for {}", compat_tool.name);
+        return Ok(());
+    }
+    let rows: Vec<[String; 5]> = entries.iter().map(ReleaseEntry::columns).collect();
+    print!(
+        "{}",
+        format_table(
+            ["TAG", "PUBLISHED", "SIZE", "VARIANTS", "INSTALLED IN"],
+            &rows
+        )
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool) -> Release {
+        serde_json::from_value(serde_json::json!({
+            "url": null,
+            "tag_name": tag,
+            "name": tag,
+            "prerelease": prerelease,
+            "assets": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_select_releases() {
+        let releases = vec![
+            release("GE-Proton10-2-rc", true),
+            release("GE-Proton10-1", false),
+            release("GE-Proton9-27", false),
+            release("GE-Proton9-26", false),
+        ];
+
+        let tags = |selected: Vec<&Release>| {
+            selected
+                .iter()
+                .map(|r| r.tag_name.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(
+            tags(select_releases(&releases, Some(2), false)),
+            "GE-Proton10-1 GE-Proton9-27"
+        );
+        assert_eq!(
+            tags(select_releases(&releases, Some(2), true)),
+            "GE-Proton10-2-rc GE-Proton10-1"
+        );
+        assert_eq!(select_releases(&releases, None, false).len(), 3);
+    }
+}
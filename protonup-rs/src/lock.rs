@@ -0,0 +1,660 @@
+//! `lock` and `sync` subcommands, pin the installed releases in a lock file and install
+//! exactly them on other machines. `lock --update` moves the lock file to newer releases.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use indicatif::MultiProgress;
+use libprotonup::apps::{App, AppInstallations};
+use libprotonup::downloads::{self, Download, Release};
+use libprotonup::files;
+use libprotonup::installer::Installer;
+use libprotonup::lockfile::{LockedInstall, Lockfile};
+use libprotonup::manifest::{InstallRecord, Manifest};
+use libprotonup::sources::CompatTool;
+
+use crate::cli::{LockArgs, SyncArgs};
+use crate::installed::{self, ReleaseCache};
+use crate::output::{self, Record};
+use crate::{architecture_variants, cancel, cli_mode, download, verify};
+
+/// Size of the asset `record` was installed from, read from its release.
+/// None if the release doesn't publish it anymore
+async fn asset_size(
+    record: &InstallRecord,
+    compat_tool: &CompatTool,
+    cache: &mut ReleaseCache,
+) -> Option<u64> {
+    cache
+        .releases(compat_tool)
+        .await
+        .iter()
+        .find(|release| release.tag_name == record.version)
+        .and_then(|release| {
+            release
+                .get_all_download_variants(&record.target, compat_tool)
+                .into_iter()
+                .find(|download| download.file_name == record.asset)
+        })
+        .map(|download| download.size)
+}
+
+/// Writes the releases installed in every detected app, or in the `--for` target, to the
+/// lock file. With `--update`, moves the lock file to the newest releases instead
+pub(crate) async fn run_lock(args: LockArgs) -> Result<()> {
+    let lockfile = match args.update {
+        true => {
+            let lockfile = load(&args.file)?;
+            update(
+                &lockfile,
+                args.target.as_deref(),
+                &mut Targets::default(),
+                &Manifest::path()?,
+            )
+            .await?
+        }
+        false => lock_installed(args.target.as_deref()).await?,
+    };
+
+    lockfile.save(&args.file)?;
+    if output::is_machine() {
+        output::emit(Record::Lockfile {
+            path: args.file,
+            installs: lockfile.installs,
+        });
+        return Ok(());
+    }
+    for install in &lockfile.installs {
+        println!("  {}: {} {}", install.target, install.tool, install.tag);
+    }
+    println!(
+        "Locked {} release{} in {}",
+        lockfile.installs.len(),
+        if lockfile.installs.len() == 1 {
+            ""
+        } else {
+            "s"
+        },
+        args.file.display()
+    );
+    Ok(())
+}
+
+/// Locks the folders recorded in the install manifest, in every app or in `target`.
+/// Folders installed without a checksum, or from an asset that is not published anymore,
+/// are skipped
+async fn lock_installed(target: Option<&str>) -> Result<Lockfile> {
+    let app = target.map(App::from_str_or_path);
+    let manifest = installed::load_manifest();
+    let mut cache = ReleaseCache::default();
+
+    let mut installs = vec![];
+    for record in manifest.installs.iter().filter(|record| {
+        app.as_ref()
+            .is_none_or(|app| verify::matches_target(record, app))
+    }) {
+        if !files::check_if_exists(&record.path).await {
+            continue;
+        }
+        if record.checksum.is_none() {
+            output::info(format!(
+                "Skipping {} {} in {}: no checksum was recorded, reinstall it to lock it",
+                record.tool, record.version, record.target
+            ));
+            continue;
+        }
+        let compat_tool = cli_mode::parse_compat_tool(&record.tool)?;
+        let Some(size) = asset_size(record, &compat_tool, &mut cache).await else {
+            output::info(format!(
+                "Skipping {} {} in {}: the asset {} was not found in the published releases",
+                record.tool, record.version, record.target, record.asset
+            ));
+            continue;
+        };
+        installs.extend(LockedInstall::from_record(record, size));
+    }
+    if installs.is_empty() {
+        return Err(anyhow!(
+            "No installation recorded by protonup-rs matches, there is nothing to lock"
+        ));
+    }
+    Ok(Lockfile::new(installs))
+}
+
+/// Reads the lock file at `path`, which must exist
+fn load(path: &Path) -> Result<Lockfile> {
+    if !path.exists() {
+        return Err(anyhow!(
+            "No lock file at {}, write one with `protonup-rs lock`",
+            path.display()
+        ));
+    }
+    Lockfile::load(path)
+}
+
+/// Installations of the lock file targets on this system, resolved once per target and tool
+#[derive(Default)]
+struct Targets(HashMap<(String, String), (CompatTool, AppInstallations)>);
+
+impl Targets {
+    /// The tool and app installation of `install`, the native one when there is also a Flatpak
+    async fn resolve(&mut self, install: &LockedInstall) -> Result<(CompatTool, AppInstallations)> {
+        let key = (install.target.clone(), install.tool.clone());
+        if !self.0.contains_key(&key) {
+            let compat_tool = cli_mode::parse_compat_tool(&install.tool)?;
+            let app_inst =
+                cli_mode::determine_app_installation(Some(&install.target), &compat_tool).await?;
+            self.0.insert(key.clone(), (compat_tool, app_inst));
+        }
+        Ok(self.0[&key].clone())
+    }
+}
+
+/// Folder `tag` of `compat_tool` is installed into
+fn install_path(
+    compat_tool: &CompatTool,
+    app_inst: &AppInstallations,
+    tag: &str,
+) -> Result<PathBuf> {
+    let install_dir = app_inst
+        .installation_dir(compat_tool)
+        .ok_or_else(|| anyhow!("Could not resolve the installation directory for {app_inst}"))?;
+    Ok(install_dir.join(compat_tool.installation_name(tag)))
+}
+
+/// Whether the folder at `path` was installed from the locked asset and its files are
+/// unchanged since. Folders installed without a file list are only checked by their record
+async fn is_synced(install: &LockedInstall, path: &Path, manifest: &Manifest) -> Result<bool> {
+    let Some(record) = manifest.find(path).filter(|record| install.matches(record)) else {
+        return Ok(false);
+    };
+    if !files::check_if_exists(&path.to_path_buf()).await {
+        return Ok(false);
+    }
+    Ok(verify::check(record)
+        .await?
+        .is_none_or(|tree| tree.is_intact()))
+}
+
+/// The asset of `release` to update a locked install to: the same variant, or the default
+/// one when the release doesn't publish it anymore
+fn newest_download(
+    release: &Release,
+    compat_tool: &CompatTool,
+    app_inst: &AppInstallations,
+    variant: Option<&str>,
+) -> Result<Download> {
+    if !compat_tool.has_variants() {
+        let download = release.get_download_info(app_inst, compat_tool);
+        if download.file_name.is_empty() {
+            return Err(anyhow!(
+                "No asset of {} {} can be installed",
+                compat_tool.name,
+                release.tag_name
+            ));
+        }
+        return Ok(download);
+    }
+    let variants = release.get_all_download_variants(app_inst, compat_tool);
+    let same_variant = variants.iter().find(|download| {
+        compat_tool
+            .variant_of(&download.file_name)
+            .is_some_and(|asset_variant| Some(asset_variant.name.as_str()) == variant)
+    });
+    match same_variant {
+        Some(download) => Ok(download.clone()),
+        None => architecture_variants::select_architecture_variant(
+            compat_tool,
+            &release.tag_name,
+            variants,
+            true,
+        ),
+    }
+}
+
+/// Installs the newest release of each locked tool, once per target or only in `target`,
+/// and returns the lock file listing it instead of the newest locked release of the tool.
+/// The older locked releases are kept
+async fn update(
+    lockfile: &Lockfile,
+    target: Option<&str>,
+    targets: &mut Targets,
+    manifest_path: &Path,
+) -> Result<Lockfile> {
+    let mut installs = vec![];
+    let mut updated = HashSet::new();
+    // the newest release of each tool comes first in the lock file
+    for locked in &lockfile.installs {
+        if target.is_some_and(|target| target != locked.target)
+            || !updated.insert((&locked.target, &locked.tool))
+        {
+            installs.push(locked.clone());
+            continue;
+        }
+        let (compat_tool, app_inst) = targets.resolve(locked).await?;
+        let r_list = downloads::list_releases(&compat_tool)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch releases for {}: {}", compat_tool.name, e))?;
+        let release = downloads::latest_release(&r_list, &compat_tool)
+            .ok_or_else(|| anyhow!("No release found for {}", compat_tool.name))?;
+        let download =
+            newest_download(release, &compat_tool, &app_inst, locked.variant.as_deref())?;
+        let path = install_path(&compat_tool, &app_inst, &release.tag_name)?;
+        let size = download.size;
+
+        let installed = Manifest::load_from(manifest_path)?
+            .find(&path)
+            .filter(|record| record.asset == download.file_name && record.checksum.is_some())
+            .is_some();
+        if !installed {
+            let installer = Installer::new(compat_tool.clone(), download, vec![app_inst.clone()])
+                .with_manifest_path(Some(manifest_path.to_path_buf()));
+            let multi_progress = MultiProgress::with_draw_target(output::draw_target());
+            download::run_with_progress(&compat_tool, installer, multi_progress).await?;
+        }
+        let manifest = Manifest::load_from(manifest_path)?;
+        let install = manifest
+            .find(&path)
+            .and_then(|record| LockedInstall::from_record(record, size))
+            .ok_or_else(|| {
+                anyhow!(
+                    "No checksum of {} {} was recorded, it can't be locked",
+                    compat_tool.name,
+                    release.tag_name
+                )
+            })?;
+        installs.push(LockedInstall {
+            target: locked.target.clone(),
+            ..install
+        });
+    }
+    Ok(Lockfile::new(installs))
+}
+
+/// Installs the locked asset into `app_inst`, rejecting an archive with another checksum
+async fn install_locked(
+    install: &LockedInstall,
+    compat_tool: &CompatTool,
+    app_inst: &AppInstallations,
+    manifest_path: &Path,
+) -> Result<()> {
+    let installer = Installer::new(
+        compat_tool.clone(),
+        install.download(app_inst),
+        vec![app_inst.clone()],
+    )
+    .with_manifest_path(Some(manifest_path.to_path_buf()))
+    .with_expected_checksum(install.checksum.clone());
+    let multi_progress = MultiProgress::with_draw_target(output::draw_target());
+    download::run_with_progress(compat_tool, installer, multi_progress).await
+}
+
+/// Removes the folders of known tools installed in `app_inst` that are not in `kept`.
+/// Folders that don't match any known tool are never removed
+async fn prune(
+    app_inst: &AppInstallations,
+    kept: &HashSet<PathBuf>,
+    manifest_path: &Path,
+) -> Result<()> {
+    let manifest = Manifest::load_from(manifest_path)?;
+    let mut cache = ReleaseCache::default();
+    for folder in installed::installed_folders(app_inst).await {
+        let path = installed::folder_path(&folder);
+        if kept.contains(&path) {
+            continue;
+        }
+        if let Some((compat_tool, version)) =
+            installed::identify(app_inst, &folder, &manifest, &mut cache).await
+        {
+            download::remove_installation(app_inst, &compat_tool, version, path, manifest_path)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Installs the releases of the lock file, and removes the other tools of the locked
+/// targets with `--prune`. The lock file is never changed, see `lock --update`
+pub(crate) async fn run_sync(args: SyncArgs) -> Result<()> {
+    let lockfile = load(&args.file)?;
+    sync(
+        &lockfile,
+        &mut Targets::default(),
+        &Manifest::path()?,
+        args.locked,
+        args.prune,
+    )
+    .await
+}
+
+/// Installs the locked releases that are missing or differ from the lock file, checked
+/// against the locked checksums. With `locked`, an installed folder that differs is an error
+/// instead. With `prune`, then removes the other tools of the targets
+async fn sync(
+    lockfile: &Lockfile,
+    targets: &mut Targets,
+    manifest_path: &Path,
+    locked: bool,
+    prune: bool,
+) -> Result<()> {
+    let manifest = Manifest::load_from(manifest_path)?;
+    let mut kept: Vec<(AppInstallations, HashSet<PathBuf>)> = vec![];
+    let mut failed = 0;
+    for install in &lockfile.installs {
+        let result = async {
+            let (compat_tool, app_inst) = targets.resolve(install).await?;
+            let path = install_path(&compat_tool, &app_inst, &install.tag)?;
+            if is_synced(install, &path, &manifest).await? {
+                output::info(format!(
+                    "{} {} is up to date in {app_inst}",
+                    install.tool, install.tag
+                ));
+            } else if locked && files::check_if_exists(&path).await {
+                return Err(anyhow!(
+                    "{} differs from the lock file, sync without --locked to reinstall it",
+                    path.display()
+                ));
+            } else {
+                install_locked(install, &compat_tool, &app_inst, manifest_path).await?;
+            }
+            Ok::<_, anyhow::Error>((app_inst, path))
+        }
+        .await;
+        if cancel::is_cancelled() {
+            cancel::exit_cancelled();
+        }
+        match result {
+            Ok((app_inst, path)) => match kept.iter_mut().find(|(kept, _)| *kept == app_inst) {
+                Some((_, paths)) => {
+                    paths.insert(path);
+                }
+                None => kept.push((app_inst, HashSet::from([path]))),
+            },
+            Err(e) => {
+                output::info(format!("{} {}: {e:#}", install.tool, install.tag));
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(anyhow!(
+            "{failed} of {} locked releases could not be installed",
+            lockfile.installs.len()
+        ));
+    }
+
+    if prune {
+        for (app_inst, paths) in &kept {
+            self::prune(app_inst, paths, manifest_path).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libprotonup::hashing::{HashSumType, Hasher};
+    use libprotonup::manifest::Checksum;
+    use libprotonup::sources::{Forge, ToolType};
+    use libprotonup::test_util::{self, make_tar_gz};
+    use serde_json::json;
+    use tempfile::{TempDir, tempdir};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn digest(sum_type: &HashSumType, data: &[u8]) -> String {
+        let mut hasher = Hasher::new(sum_type);
+        hasher.update(data);
+        hasher.finalize_hex()
+    }
+
+    fn sha512(data: &[u8]) -> String {
+        digest(&HashSumType::Sha512, data)
+    }
+
+    /// A custom target in a new temporary folder, with the install manifest next to it, and
+    /// a test tool whose releases are served by `mock_server`
+    struct Setup {
+        temp_dir: TempDir,
+        app_inst: AppInstallations,
+        compat_tool: CompatTool,
+        targets: Targets,
+    }
+
+    impl Setup {
+        fn new(mock_server: &MockServer) -> Setup {
+            let temp_dir = tempdir().unwrap();
+            let install_dir = temp_dir.path().join("target");
+            std::fs::create_dir(&install_dir).unwrap();
+            let app_inst =
+                AppInstallations::new_custom_app_install(install_dir.to_string_lossy().to_string());
+            let compat_tool = CompatTool::new_custom(
+                "TestTool".to_owned(),
+                Forge::Custom(mock_server.uri()),
+                "owner".to_owned(),
+                "repo".to_owned(),
+                ToolType::WineBased,
+                None,
+                None,
+                None,
+            );
+            let mut targets = Targets::default();
+            targets.0.insert(
+                (Self::target(&app_inst), compat_tool.name.clone()),
+                (compat_tool.clone(), app_inst.clone()),
+            );
+            Setup {
+                temp_dir,
+                app_inst,
+                compat_tool,
+                targets,
+            }
+        }
+
+        fn target(app_inst: &AppInstallations) -> String {
+            libprotonup::lockfile::target_name(app_inst)
+        }
+
+        fn manifest_path(&self) -> PathBuf {
+            self.temp_dir.path().join("state").join("installed.json")
+        }
+
+        fn install_path(&self, tag: &str) -> PathBuf {
+            install_path(&self.compat_tool, &self.app_inst, tag).unwrap()
+        }
+
+        /// Lock entry of the asset `tag.tar.gz` with the checksum `checksum`
+        fn locked(&self, mock_server: &MockServer, tag: &str, checksum: &str) -> LockedInstall {
+            LockedInstall {
+                target: Self::target(&self.app_inst),
+                tool: self.compat_tool.name.clone(),
+                tag: tag.to_owned(),
+                variant: None,
+                asset: format!("{tag}.tar.gz"),
+                url: format!("{}/{tag}.tar.gz", mock_server.uri()),
+                size: 0,
+                checksum: Checksum {
+                    algorithm: HashSumType::Sha512,
+                    value: checksum.to_owned(),
+                    verified: false,
+                },
+            }
+        }
+    }
+
+    /// Serves the archive `tag.tar.gz`, expecting `downloads` requests for it
+    async fn mock_archive(mock_server: &MockServer, tag: &str, archive: Vec<u8>, downloads: u64) {
+        Mock::given(method("GET"))
+            .and(path(format!("/{tag}.tar.gz")))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive))
+            .expect(downloads)
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_installs_the_locked_release() {
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("top", &[("file1.txt", "locked")]).await;
+        let checksum = sha512(&archive);
+        mock_archive(&mock_server, "v1", archive, 2).await;
+        let mut setup = Setup::new(&mock_server);
+        let manifest_path = setup.manifest_path();
+        let lockfile = Lockfile::new(vec![setup.locked(&mock_server, "v1", &checksum)]);
+
+        // missing releases are installed even with --locked
+        sync(&lockfile, &mut setup.targets, &manifest_path, true, false)
+            .await
+            .unwrap();
+        let install_path = setup.install_path("v1");
+        assert_eq!(
+            std::fs::read_to_string(install_path.join("file1.txt")).unwrap(),
+            "locked"
+        );
+        let manifest = Manifest::load_from(&manifest_path).unwrap();
+        let record = manifest.find(&install_path).unwrap();
+        assert!(lockfile.installs[0].matches(record));
+
+        // an installation matching the lock is kept as it is
+        sync(&lockfile, &mut setup.targets, &manifest_path, false, false)
+            .await
+            .unwrap();
+
+        // a modified one is reinstalled, or rejected with --locked
+        std::fs::write(install_path.join("file1.txt"), "modified").unwrap();
+        let error = sync(&lockfile, &mut setup.targets, &manifest_path, true, false)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("could not be installed"));
+        assert_eq!(
+            std::fs::read_to_string(install_path.join("file1.txt")).unwrap(),
+            "modified"
+        );
+        sync(&lockfile, &mut setup.targets, &manifest_path, false, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(install_path.join("file1.txt")).unwrap(),
+            "locked"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_rejects_a_different_checksum() {
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("top", &[("file1.txt", "replaced")]).await;
+        mock_archive(&mock_server, "v1", archive, 1).await;
+        let mut setup = Setup::new(&mock_server);
+        let manifest_path = setup.manifest_path();
+        let locked = setup.locked(&mock_server, "v1", &sha512(b"the locked archive"));
+        let lockfile = Lockfile::new(vec![locked]);
+
+        let result = sync(&lockfile, &mut setup.targets, &manifest_path, false, false).await;
+        assert!(result.is_err());
+        assert!(!setup.install_path("v1").exists());
+        assert!(
+            Manifest::load_from(&manifest_path)
+                .unwrap()
+                .installs
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_prunes_the_other_known_tools() {
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("top", &[("file1.txt", "locked")]).await;
+        let checksum = sha512(&archive);
+        mock_archive(&mock_server, "v2", archive, 1).await;
+        let mut setup = Setup::new(&mock_server);
+        let manifest_path = setup.manifest_path();
+
+        // an older release installed by protonup-rs, and a folder of an unknown tool
+        let old = setup.install_path("GE-Proton9-1");
+        std::fs::create_dir_all(&old).unwrap();
+        let unknown = setup.install_path("my-own-build");
+        std::fs::create_dir_all(&unknown).unwrap();
+        let mut manifest = Manifest::default();
+        manifest.insert(test_util::install_record(
+            setup.app_inst.clone(),
+            "GE-Proton9-1",
+            old.clone(),
+        ));
+        std::fs::create_dir_all(manifest_path.parent().unwrap()).unwrap();
+        manifest.save_to(&manifest_path).unwrap();
+
+        let lockfile = Lockfile::new(vec![setup.locked(&mock_server, "v2", &checksum)]);
+        sync(&lockfile, &mut setup.targets, &manifest_path, false, true)
+            .await
+            .unwrap();
+
+        assert!(setup.install_path("v2").exists());
+        assert!(!old.exists());
+        assert!(unknown.exists());
+        let manifest = Manifest::load_from(&manifest_path).unwrap();
+        assert!(manifest.find(&old).is_none());
+        assert!(manifest.find(&setup.install_path("v2")).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_update_locks_the_newest_release() {
+        let mock_server = MockServer::start().await;
+        let archive = make_tar_gz("top", &[("file1.txt", "newest")]).await;
+        let newest = archive.clone();
+        let size = archive.len();
+        mock_archive(&mock_server, "v3", archive, 1).await;
+        Mock::given(method("GET"))
+            .and(path("/owner/repo/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "tag_name": "v3",
+                "name": "v3",
+                "assets": [{
+                    "url": format!("{}/assets/3", mock_server.uri()),
+                    "id": 3,
+                    "name": "v3.tar.gz",
+                    "size": size,
+                    "updated_at": "2025-01-01T00:00:00Z",
+                    "browser_download_url": format!("{}/v3.tar.gz", mock_server.uri())
+                }]
+            }])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let mut setup = Setup::new(&mock_server);
+        let manifest_path = setup.manifest_path();
+
+        let v2 = setup.locked(&mock_server, "v2", &sha512(b"v2"));
+        let v1 = setup.locked(&mock_server, "v1", &sha512(b"v1"));
+        // another target is not updated with --for
+        let other = LockedInstall {
+            target: "steam".to_owned(),
+            ..v1.clone()
+        };
+        let lockfile = Lockfile::new(vec![v1.clone(), v2, other.clone()]);
+
+        let target = Setup::target(&setup.app_inst);
+        let updated = update(&lockfile, Some(&target), &mut setup.targets, &manifest_path)
+            .await
+            .unwrap();
+
+        let tags: Vec<_> = updated
+            .installs
+            .iter()
+            .filter(|install| install.target == target)
+            .map(|install| install.tag.as_str())
+            .collect();
+        // the newest locked release is replaced, the older ones are kept
+        assert_eq!(tags, ["v3", "v1"]);
+        let v3 = &updated.installs[0];
+        assert_eq!(v3.checksum.value, digest(&v3.checksum.algorithm, &newest));
+        assert_eq!(v3.size, size as u64);
+        assert!(updated.installs.contains(&v1));
+        assert!(updated.installs.contains(&other));
+        assert!(setup.install_path("v3").join("file1.txt").exists());
+
+        // the lock file itself is only written by the caller
+        assert_eq!(lockfile.installs.len(), 3);
+    }
+}
@@ -5,16 +5,29 @@ use inquire::Select;
 use std::{fmt, process::exit};
 
 use libprotonup::apps::App;
+use libprotonup::config::{self, ChecksumPolicy, Config};
+use libprotonup::downloads::Release;
 
 mod architecture_variants;
+mod cancel;
+mod changelog;
 mod cli;
 mod cli_mode;
+mod config_cmd;
 mod download;
 mod file_path;
 mod helper_menus;
+mod installed;
+mod list;
+mod lock;
 mod manage_apps;
+mod markdown;
+mod outdated;
+mod output;
+mod tui;
+mod verify;
 
-use cli::Opt;
+use cli::{Command, Opt};
 use manage_apps::manage_apps_routine;
 
 /// Guard struct that cleans up temp directory when dropped
@@ -29,6 +42,7 @@ impl Drop for TempDirCleanupGuard {
 #[derive(Debug, Copy, Clone)]
 #[allow(clippy::upper_case_acronyms)]
 enum InitialMenu {
+    FullScreen,
     QuickUpdate,
     DownloadForSteam,
     DownloadForLutris,
@@ -40,6 +54,7 @@ enum InitialMenu {
 impl InitialMenu {
     // could be generated by macro
     const VARIANTS: &'static [InitialMenu] = &[
+        Self::FullScreen,
         Self::QuickUpdate,
         Self::DownloadForSteam,
         Self::DownloadForLutris,
@@ -52,6 +67,10 @@ impl InitialMenu {
 impl fmt::Display for InitialMenu {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            Self::FullScreen => write!(
+                f,
+                "Full-screen interface (installed versions and releases side by side)"
+            ),
             Self::QuickUpdate => write!(
                 f,
                 "Quick Update (detect apps and update compatibility tools)"
@@ -71,6 +90,8 @@ impl fmt::Display for InitialMenu {
 async fn main() {
     // Register temp directory cleanup guard
     let _cleanup_guard = TempDirCleanupGuard;
+    // Cancel in-flight installs on SIGINT/SIGTERM
+    cancel::install_signal_handler();
 
     let Opt {
         quick_download,
@@ -79,35 +100,104 @@ async fn main() {
         version,
         r#for: for_target,
         whats_new,
+        stream,
+        require_checksum,
+        include_prereleases,
+        arch,
+        variant,
+        non_interactive,
+        output,
+        command,
     } = Opt::parse();
 
+    output::init(output, non_interactive);
+    architecture_variants::init(variant);
+
+    match Config::load() {
+        Ok(mut loaded) => {
+            loaded.stream_install |= stream;
+            if require_checksum {
+                loaded.checksum_policy = ChecksumPolicy::Require;
+            }
+            loaded.include_prereleases |= include_prereleases;
+            if arch.is_some() {
+                loaded.arch = arch;
+            }
+            config::init(loaded)
+        }
+        // the config commands report it themselves, and must be usable to fix the file
+        Err(_) if matches!(command, Some(Command::Config(_))) => {}
+        Err(e) => exit_with_error(e),
+    }
+
+    if let Some(command) = command {
+        let result = match command {
+            Command::Tui => tui::run_tui().await.map(|_| 0),
+            Command::List(args) => list::run_list(args).await.map(|_| 0),
+            Command::Installed(args) => installed::run_installed(args).await.map(|_| 0),
+            Command::Config(command) => config_cmd::run_config(command).map(|_| 0),
+            Command::Verify(args) => verify::run_verify(args).await.map(|_| 0),
+            Command::Changelog(args) => changelog::run_changelog(args).await.map(|_| 0),
+            Command::Lock(args) => lock::run_lock(args).await.map(|_| 0),
+            Command::Sync(args) => lock::run_sync(args).await.map(|_| 0),
+            Command::Outdated(args) => outdated::run_outdated(args).await.map(|updates| {
+                if updates {
+                    outdated::EXIT_UPDATES_AVAILABLE
+                } else {
+                    0
+                }
+            }),
+        };
+        match result {
+            Ok(status) => {
+                output::finish();
+                if status != 0 {
+                    exit(status);
+                }
+                return;
+            }
+            Err(e) => exit_with_error(e),
+        }
+    }
+
     // If --whats-new is passed alone (no --tool, no --quick-download),
     // run standalone check-for-updates mode and exit
     if whats_new && !quick_download && tool.is_none() && version.is_none() && for_target.is_none() {
         if let Err(e) = download::check_whats_new().await {
-            eprintln!("{e}");
-            exit(1);
+            exit_with_error(e);
         }
+        output::finish();
         return;
     }
 
     // If any CLI argument is provided, run in CLI mode (non-interactive)
     if tool.is_some() || version.is_some() || for_target.is_some() {
         let releases = cli_mode::run_cli_mode(tool, version, for_target, force, whats_new).await;
+        if cancel::is_cancelled() {
+            cancel::exit_cancelled();
+        }
         match releases {
-            Ok(releases) => {
-                for release in releases {
-                    println!("Installed {}", release.tag_name);
-                }
-            }
-            Err(e) => {
-                eprintln!("{e}");
-                exit(1);
-            }
+            Ok(releases) => print_installed(&releases),
+            Err(e) => exit_with_error(e),
         }
         return;
     }
 
+    if output::is_machine() && !quick_download {
+        exit_with_error(anyhow::anyhow!(
+            "--output {} requires a non-interactive mode: --quick-download, --whats-new, or --tool/--version/--for",
+            match output {
+                cli::OutputFormat::Json => "json",
+                _ => "ndjson",
+            }
+        ));
+    }
+    if !output::is_interactive() && !quick_download {
+        exit_with_error(anyhow::anyhow!(
+            "The interactive menu needs a terminal, and is disabled by --non-interactive. Use --quick-download, --whats-new, or --tool/--version/--for"
+        ));
+    }
+
     // run quick downloads and skip InitialMenu
     let releases = if quick_download {
         download::run_quick_downloads(force, whats_new).await
@@ -123,6 +213,14 @@ async fn main() {
 
             // Download actions exit the loop; other actions return to menu
             match answer {
+                InitialMenu::FullScreen => {
+                    if let Err(e) = tui::run_tui().await {
+                        eprintln!("{e}");
+                    }
+                    if cancel::is_cancelled() {
+                        cancel::exit_cancelled();
+                    }
+                }
                 InitialMenu::QuickUpdate => {
                     break download::run_quick_downloads(force, whats_new).await;
                 }
@@ -145,15 +243,36 @@ async fn main() {
         }
     };
 
+    if cancel::is_cancelled() {
+        cancel::exit_cancelled();
+    }
+
     match releases {
-        Ok(releases) => {
-            for release in releases {
-                println!("Installed {}", release.tag_name);
-            }
-        }
-        Err(e) => {
-            eprintln!("{e}");
-            exit(1);
-        }
+        Ok(releases) => print_installed(&releases),
+        Err(e) => exit_with_error(e),
+    }
+}
+
+/// Prints the installed releases, or the JSON document in machine-readable mode
+fn print_installed(releases: &[Release]) {
+    if output::is_machine() {
+        output::finish();
+        return;
+    }
+    for release in releases {
+        println!("Installed {}", release.tag_name);
+    }
+}
+
+/// Reports the error in the selected output format and exits
+fn exit_with_error(e: anyhow::Error) -> ! {
+    if output::is_machine() {
+        output::emit(output::Record::Error {
+            message: format!("{e:#}"),
+        });
+        output::finish();
+    } else {
+        eprintln!("{e}");
     }
+    exit(1)
 }
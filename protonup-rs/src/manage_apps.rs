@@ -2,6 +2,7 @@ use inquire::MultiSelect;
 use libprotonup::{
     apps::{self},
     files::{self, Folders},
+    manifest,
 };
 use std::fmt;
 
@@ -31,14 +32,17 @@ impl fmt::Display for ManageAppsMenuOptions {
     }
 }
 
-/// Prompt the user for which App they want to manage
+/// Prompt the user for which App they want to manage, including the configured targets
 fn manage_menu() -> Vec<ManageAppsMenuOptions> {
-    let answer = MultiSelect::new(
-        "Select the Applications you want to manage:",
-        APP_VARIANTS_WITH_DETECT.to_vec(),
-    )
-    .with_default(&[0_usize])
-    .prompt();
+    let mut options = APP_VARIANTS_WITH_DETECT.to_vec();
+    options.extend(
+        apps::configured_targets()
+            .into_iter()
+            .map(ManageAppsMenuOptions::AppInstallations),
+    );
+    let answer = MultiSelect::new("Select the Applications you want to manage:", options)
+        .with_default(&[0_usize])
+        .prompt();
 
     answer.unwrap_or_else(|_| {
         println!("The tag list could not be processed");
@@ -52,8 +56,9 @@ fn manage_menu() -> Vec<ManageAppsMenuOptions> {
 pub(crate) async fn manage_apps_routine() {
     let choices = manage_menu();
 
-    // default to all apps
+    // default to all apps and targets
     let mut selected_apps = apps::APP_INSTALLATIONS_VARIANTS.to_vec();
+    selected_apps.extend(apps::configured_targets());
     if !choices.contains(&ManageAppsMenuOptions::DetectAll) {
         selected_apps = choices
             .iter()
@@ -94,16 +99,21 @@ pub(crate) async fn manage_apps_routine() {
             for version in delete_versions.0 {
                 let version = version.0;
                 let version_path = version.0.join(&version.1);
-                files::remove_dir_all(&version_path).await.map_or_else(
-                    |e| eprintln!("Error deleting {}: {}", version_path.as_path().display(), e),
-                    |_| {
+                match files::remove_dir_all(&version_path).await {
+                    Err(e) => {
+                        eprintln!("Error deleting {}: {}", version_path.as_path().display(), e)
+                    }
+                    Ok(()) => {
                         println!(
                             "{} {} deleted successfully",
                             app,
                             version_path.as_path().display()
                         );
-                    },
-                );
+                        if let Err(e) = manifest::record_removal(&version_path).await {
+                            eprintln!("{e:#}");
+                        }
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,661 @@
+//! Terminal rendering of the Markdown release notes.
+//!
+//! Headings, emphasis, code and lists are styled, links become OSC 8 hyperlinks and
+//! paragraphs are wrapped to the terminal width. HTML, comments included, and images are
+//! dropped, and so are the control characters of the text and the links, which could send
+//! escape sequences to the terminal. When stdout is not a terminal the notes are rendered as
+//! plain, unwrapped text.
+
+use console::{Style, Term, measure_text_width};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+use crate::output;
+
+/// Narrowest column paragraphs are wrapped to, however deeply nested they are
+const MIN_WRAP_WIDTH: usize = 20;
+
+/// Widest horizontal rule
+const RULE_WIDTH: usize = 60;
+
+/// Indent of the lines of a code block
+const CODE_INDENT: &str = "    ";
+
+/// How the notes are rendered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Renderer {
+    /// Styles and hyperlinks with escape codes
+    pub styled: bool,
+    /// Column paragraphs are wrapped at, None to leave them unwrapped
+    pub width: Option<usize>,
+}
+
+impl Renderer {
+    /// Plain text, without wrapping
+    pub(crate) const PLAIN: Renderer = Renderer {
+        styled: false,
+        width: None,
+    };
+
+    /// Renderer for text printed to stdout: wrapped to the terminal width and styled unless
+    /// colors are disabled with `NO_COLOR`. Plain text when stdout is not a terminal
+    pub(crate) fn for_stdout() -> Renderer {
+        let term = Term::stdout();
+        if output::is_machine() || !term.is_term() {
+            return Renderer::PLAIN;
+        }
+        Renderer {
+            styled: console::colors_enabled(),
+            width: term.size_checked().map(|(_, cols)| cols as usize),
+        }
+    }
+
+    /// Renders `markdown`, without trailing newlines
+    pub(crate) fn render(&self, markdown: &str) -> String {
+        let options =
+            Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS;
+        let mut writer = Writer::new(*self);
+        for event in Parser::new_ext(markdown, options) {
+            writer.event(event);
+        }
+        writer.finish()
+    }
+}
+
+/// Text printed as a whole, with its width on the terminal
+#[derive(Debug, Clone, Default)]
+struct Word {
+    text: String,
+    width: usize,
+}
+
+impl Word {
+    fn plain(text: impl Into<String>) -> Word {
+        let text = text.into();
+        Word {
+            width: measure_text_width(&text),
+            text,
+        }
+    }
+}
+
+/// Content of the block being written
+enum Piece {
+    Word(Word),
+    /// Hard line break
+    Break,
+}
+
+/// Styles of the inline text being written
+#[derive(Default)]
+struct Inline {
+    strong: bool,
+    emphasis: bool,
+    strikethrough: bool,
+    code: bool,
+    table_head: bool,
+    heading: Option<HeadingLevel>,
+    /// Destination of the link being written
+    link: Option<String>,
+}
+
+struct Writer {
+    renderer: Renderer,
+    out: String,
+    pieces: Vec<Piece>,
+    /// Text is appended to the last word until some whitespace is written
+    joining: bool,
+    /// Prefix of every line for each nested list item and quote
+    indents: Vec<Word>,
+    /// Marker of the list item being written, printed on its first line instead of its indent
+    marker: Option<Word>,
+    /// Next number of each nested list, None for bullet lists
+    lists: Vec<Option<u64>>,
+    inline: Inline,
+    /// Text of the link being written, to tell if its destination must be printed
+    link_text: String,
+    /// Depth of the images being skipped
+    images: usize,
+    /// Text of the code block being written
+    code_block: Option<String>,
+    /// Rows of the table being written
+    table: Option<Vec<Vec<Word>>>,
+    /// A blank line is due before the next block
+    blank: bool,
+    /// Blank line written before the next line, once a block has started
+    pending_blank: Option<String>,
+}
+
+impl Writer {
+    fn new(renderer: Renderer) -> Writer {
+        Writer {
+            renderer,
+            out: String::new(),
+            pieces: vec![],
+            joining: false,
+            indents: vec![],
+            marker: None,
+            lists: vec![],
+            inline: Inline::default(),
+            link_text: String::new(),
+            images: 0,
+            code_block: None,
+            table: None,
+            blank: false,
+            pending_blank: None,
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        if self.images > 0 {
+            match event {
+                Event::Start(Tag::Image { .. }) => self.images += 1,
+                Event::End(TagEnd::Image) => self.images -= 1,
+                _ => {}
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match &mut self.code_block {
+                Some(code) => code.push_str(&strip_controls(&text, "\n\t")),
+                None => self.text(&strip_controls(&text, "")),
+            },
+            Event::Code(code) => {
+                self.inline.code = true;
+                self.text(&strip_controls(&code, ""));
+                self.inline.code = false;
+            }
+            Event::SoftBreak => self.joining = false,
+            Event::HardBreak => {
+                self.pieces.push(Piece::Break);
+                self.joining = false;
+            }
+            Event::Rule => {
+                self.start_block();
+                let width = self.renderer.width.unwrap_or(RULE_WIDTH).min(RULE_WIDTH);
+                let rule = "─".repeat(width.saturating_sub(self.indent_width()));
+                let line = format!("{}{}", self.prefix(), self.style(Style::new().dim(), &rule));
+                self.push_line(&line);
+                self.blank = true;
+            }
+            Event::TaskListMarker(checked) => {
+                self.joining = false;
+                self.push_word(Word::plain(if checked { "[x]" } else { "[ ]" }));
+                self.joining = false;
+            }
+            // HTML and comments are not shown
+            Event::Html(_) | Event::InlineHtml(_) => {}
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.start_block(),
+            Tag::Heading { level, .. } => {
+                self.start_block();
+                self.inline.heading = Some(level);
+            }
+            Tag::BlockQuote(_) => {
+                self.start_block();
+                let bar = self.style(Style::new().dim(), "│");
+                self.indents.push(Word {
+                    text: format!("{bar} "),
+                    width: 2,
+                });
+            }
+            Tag::CodeBlock(_) => {
+                self.start_block();
+                self.code_block = Some(String::new());
+            }
+            Tag::List(first) => {
+                if self.lists.is_empty() {
+                    self.start_block();
+                } else {
+                    // the text of the parent item
+                    self.flush();
+                }
+                self.lists.push(first);
+            }
+            Tag::Item => {
+                self.flush();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ if self.renderer.styled => "• ".to_owned(),
+                    _ => "- ".to_owned(),
+                };
+                let marker = Word::plain(marker);
+                self.indents.push(Word {
+                    text: " ".repeat(marker.width),
+                    width: marker.width,
+                });
+                self.marker = Some(marker);
+            }
+            Tag::Emphasis => self.inline.emphasis = true,
+            Tag::Strong => self.inline.strong = true,
+            Tag::Strikethrough => self.inline.strikethrough = true,
+            Tag::Link { dest_url, .. } => {
+                self.inline.link = Some(strip_controls(&dest_url, ""));
+                self.link_text.clear();
+            }
+            Tag::Image { .. } => self.images += 1,
+            Tag::Table(_) => {
+                self.start_block();
+                self.table = Some(vec![]);
+            }
+            Tag::TableHead | Tag::TableRow => {
+                self.inline.table_head = tag == Tag::TableHead;
+                if let Some(rows) = &mut self.table {
+                    rows.push(vec![]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => {
+                self.flush();
+                self.blank = true;
+            }
+            TagEnd::Heading(_) => {
+                self.flush();
+                self.inline.heading = None;
+                self.blank = true;
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.indents.pop();
+                self.blank = true;
+            }
+            TagEnd::CodeBlock => {
+                let code = self.code_block.take().unwrap_or_default();
+                let style = Style::new().yellow();
+                for line in code.lines() {
+                    let line = format!(
+                        "{}{CODE_INDENT}{}",
+                        self.prefix(),
+                        self.style(style.clone(), line)
+                    );
+                    self.push_line(&line);
+                }
+                self.blank = true;
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank = true;
+                }
+            }
+            TagEnd::Item => {
+                self.flush();
+                // an empty item
+                if let Some(marker) = self.marker.take() {
+                    let line = format!("{}{}", self.outer_prefix(), marker.text);
+                    self.push_line(&line);
+                }
+                self.indents.pop();
+            }
+            TagEnd::Emphasis => self.inline.emphasis = false,
+            TagEnd::Strong => self.inline.strong = false,
+            TagEnd::Strikethrough => self.inline.strikethrough = false,
+            TagEnd::Link => {
+                let url = self.inline.link.take().unwrap_or_default();
+                // plain text has no hyperlinks, so the destination is printed after the text
+                if !self.renderer.styled && !url.is_empty() && url != self.link_text {
+                    self.joining = false;
+                    self.push_word(Word::plain(format!("({url})")));
+                }
+            }
+            TagEnd::TableCell => {
+                let cell = self.take_words();
+                if let Some(row) = self.table.as_mut().and_then(|rows| rows.last_mut()) {
+                    row.push(cell);
+                }
+            }
+            TagEnd::TableHead => self.inline.table_head = false,
+            TagEnd::Table => {
+                let rows = self.table.take().unwrap_or_default();
+                self.write_table(&rows);
+                self.blank = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.out.truncate(self.out.trim_end().len());
+        self.out
+    }
+
+    /// Appends inline text, split in words at whitespace
+    fn text(&mut self, text: &str) {
+        if self.inline.link.is_some() {
+            self.link_text.push_str(text);
+        }
+        for (i, chunk) in text.split(char::is_whitespace).enumerate() {
+            if i > 0 {
+                self.joining = false;
+            }
+            if !chunk.is_empty() {
+                let word = Word {
+                    text: self.style_inline(chunk),
+                    width: measure_text_width(chunk),
+                };
+                self.push_word(word);
+            }
+        }
+    }
+
+    /// Appends a word, joined to the previous one if no whitespace was written in between
+    fn push_word(&mut self, word: Word) {
+        match self.pieces.last_mut() {
+            Some(Piece::Word(last)) if self.joining => {
+                last.text.push_str(&word.text);
+                last.width += word.width;
+            }
+            _ => self.pieces.push(Piece::Word(word)),
+        }
+        self.joining = true;
+    }
+
+    /// The text of the block being written, in a single line
+    fn take_words(&mut self) -> Word {
+        let words: Vec<Word> = std::mem::take(&mut self.pieces)
+            .into_iter()
+            .filter_map(|piece| match piece {
+                Piece::Word(word) => Some(word),
+                Piece::Break => None,
+            })
+            .collect();
+        self.joining = false;
+        Word {
+            text: words
+                .iter()
+                .map(|word| word.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            width: words.iter().map(|word| word.width).sum::<usize>()
+                + words.len().saturating_sub(1),
+        }
+    }
+
+    /// Starts a new block, after a blank line if one is due.
+    /// The blank line is only written with the first line of the block, so empty blocks
+    /// don't leave several blank lines behind
+    fn start_block(&mut self) {
+        self.flush();
+        if self.blank {
+            self.pending_blank = Some(self.prefix());
+            self.blank = false;
+        }
+    }
+
+    /// Writes the text of the block being written, wrapped to the terminal width
+    fn flush(&mut self) {
+        if self.pieces.is_empty() {
+            return;
+        }
+        let available = self.renderer.width.map(|width| {
+            width
+                .saturating_sub(self.indent_width())
+                .max(MIN_WRAP_WIDTH)
+        });
+
+        let mut lines = vec![];
+        let mut line = String::new();
+        let mut line_width = 0;
+        for piece in std::mem::take(&mut self.pieces) {
+            match piece {
+                Piece::Break => {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+                Piece::Word(word) => {
+                    if line_width > 0
+                        && available
+                            .is_some_and(|available| line_width + 1 + word.width > available)
+                    {
+                        lines.push(std::mem::take(&mut line));
+                        line_width = 0;
+                    }
+                    if line_width > 0 {
+                        line.push(' ');
+                        line_width += 1;
+                    }
+                    line.push_str(&word.text);
+                    line_width += word.width;
+                }
+            }
+        }
+        lines.push(line);
+
+        for line in lines {
+            let prefix = match self.marker.take() {
+                Some(marker) => format!("{}{}", self.outer_prefix(), marker.text),
+                None => self.prefix(),
+            };
+            self.push_line(&format!("{prefix}{line}"));
+        }
+        self.joining = false;
+    }
+
+    fn write_table(&mut self, rows: &[Vec<Word>]) {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| {
+                rows.iter()
+                    .filter_map(|row| row.get(column))
+                    .map(|cell| cell.width)
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        for (i, row) in rows.iter().enumerate() {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{}{}", cell.text, " ".repeat(width - cell.width)))
+                .collect();
+            let line = format!("{}{}", self.prefix(), cells.join("  "));
+            self.push_line(&line);
+            if i == 0 {
+                let separator: Vec<String> =
+                    widths.iter().map(|width| "─".repeat(*width)).collect();
+                let line = format!(
+                    "{}{}",
+                    self.prefix(),
+                    self.style(Style::new().dim(), &separator.join("  "))
+                );
+                self.push_line(&line);
+            }
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        if let Some(blank) = self.pending_blank.take()
+            && !self.out.is_empty()
+        {
+            self.out.push_str(blank.trim_end());
+            self.out.push('\n');
+        }
+        self.out.push_str(line.trim_end());
+        self.out.push('\n');
+    }
+
+    /// Prefix of the lines of the block being written
+    fn prefix(&self) -> String {
+        self.indents
+            .iter()
+            .map(|indent| indent.text.as_str())
+            .collect()
+    }
+
+    /// Prefix of the first line of a list item, before its marker
+    fn outer_prefix(&self) -> String {
+        let outer = self.indents.len().saturating_sub(1);
+        self.indents[..outer]
+            .iter()
+            .map(|indent| indent.text.as_str())
+            .collect()
+    }
+
+    fn indent_width(&self) -> usize {
+        self.indents.iter().map(|indent| indent.width).sum()
+    }
+
+    fn style(&self, style: Style, text: &str) -> String {
+        if self.renderer.styled {
+            style.force_styling(true).apply_to(text).to_string()
+        } else {
+            text.to_owned()
+        }
+    }
+
+    /// Styles inline text, making it a hyperlink inside links
+    fn style_inline(&self, text: &str) -> String {
+        if !self.renderer.styled {
+            return text.to_owned();
+        }
+        let inline = &self.inline;
+        let mut style = Style::new();
+        if inline.strong || inline.table_head || inline.heading.is_some() {
+            style = style.bold();
+        }
+        if inline.emphasis {
+            style = style.italic();
+        }
+        if inline.strikethrough {
+            style = style.strikethrough();
+        }
+        if inline.code {
+            style = style.yellow();
+        }
+        match inline.heading {
+            Some(HeadingLevel::H1) => style = style.cyan().underlined(),
+            Some(HeadingLevel::H2) => style = style.cyan(),
+            _ => {}
+        }
+        let text = match &inline.link {
+            Some(_) => self.style(style.blue().underlined(), text),
+            None => self.style(style, text),
+        };
+        match &inline.link {
+            Some(url) => format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\"),
+            None => text,
+        }
+    }
+}
+
+/// Removes the C0 and C1 control characters except the `kept` ones, so the notes can't
+/// send their own escape sequences to the terminal
+fn strip_controls(text: &str, kept: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || kept.contains(*c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTES: &str = "\
+# GE-Proton9-22
+
+<!-- generated by the release script -->
+![banner](https://example.com/banner.png)
+
+This release **fixes** the `dxvk` cache, see [the wiki](https://example.com/wiki).
+
+- Fixed A
+- Fixed B
+  1. nested
+  2. list
+";
+
+    #[test]
+    fn test_render_plain() {
+        let output = Renderer::PLAIN.render(NOTES);
+        assert_eq!(
+            output,
+            "GE-Proton9-22\n\
+             \n\
+             This release fixes the dxvk cache, see the wiki (https://example.com/wiki).\n\
+             \n\
+             - Fixed A\n\
+             - Fixed B\n\
+             \x20 1. nested\n\
+             \x20 2. list"
+        );
+    }
+
+    #[test]
+    fn test_render_styled() {
+        let renderer = Renderer {
+            styled: true,
+            width: None,
+        };
+        let output = renderer.render(NOTES);
+        assert!(output.contains("\x1b]8;;https://example.com/wiki\x1b\\"));
+        assert!(!output.contains("(https://example.com/wiki)"));
+        assert!(output.contains("• Fixed A"));
+        assert!(!output.contains("generated by"));
+        assert!(!output.contains("banner"));
+        assert_eq!(
+            console::strip_ansi_codes(&output).lines().next(),
+            Some("GE-Proton9-22")
+        );
+    }
+
+    #[test]
+    fn test_render_strips_control_characters() {
+        let renderer = Renderer {
+            styled: true,
+            width: None,
+        };
+        let output = renderer.render(
+            "[click](<https://example.com/\x1b]8;;https://evil.example\x07>) \
+             text\x1b[2J\u{9b}31m `code\x07`\n\n```\nline\x1b[1A\tcode\n```",
+        );
+        // only the hyperlink and the styles of the renderer are left
+        assert_eq!(
+            console::strip_ansi_codes(&output),
+            "\x1b]8;;https://example.com/]8;;https://evil.example\x1b\\click\x1b]8;;\x1b\\ \
+             text[2J31m code\n\n    line[1A\tcode"
+        );
+
+        let output = Renderer::PLAIN.render("[a](https://example.com/\u{9b}2J) b\x1b[31m");
+        assert_eq!(output, "a (https://example.com/2J) b[31m");
+    }
+
+    #[test]
+    fn test_render_wrapped() {
+        let renderer = Renderer {
+            styled: false,
+            width: Some(24),
+        };
+        let output = renderer.render(
+            "- a list item long enough to be wrapped\n\n\
+             > quoted\n\n\
+             ```\nlet code = \"never wrapped at all\";\n```",
+        );
+        assert_eq!(
+            output,
+            "- a list item long\n\
+             \x20 enough to be wrapped\n\
+             \n\
+             │ quoted\n\
+             \n\
+             \x20   let code = \"never wrapped at all\";"
+        );
+    }
+}
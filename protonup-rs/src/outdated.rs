@@ -0,0 +1,166 @@
+//! `outdated` subcommand, compares the newest installed version of each tool against its latest release.
+
+use std::cmp::Ordering;
+
+use anyhow::Result;
+use libprotonup::apps::AppInstallations;
+use libprotonup::sources::CompatTool;
+use libprotonup::utils::compare_versions;
+
+use crate::cli::OutdatedArgs;
+use crate::installed::{self, ReleaseCache};
+use crate::output::{self, Record, TargetRecord, format_table};
+
+/// Exit status of `outdated` when at least one update is available
+pub(crate) const EXIT_UPDATES_AVAILABLE: i32 = 100;
+
+/// The newest version of a tool installed in an app
+struct OutdatedEntry {
+    target: AppInstallations,
+    compat_tool: CompatTool,
+    installed: String,
+    latest: Option<String>,
+}
+
+impl OutdatedEntry {
+    fn update_available(&self) -> bool {
+        self.latest
+            .as_ref()
+            .is_some_and(|latest| compare_versions(&self.installed, latest) == Ordering::Less)
+    }
+
+    fn record(&self) -> Record {
+        Record::Outdated {
+            tool: self.compat_tool.name.clone(),
+            target: TargetRecord::from(&self.target),
+            installed: self.installed.clone(),
+            latest: self.latest.clone(),
+            update_available: self.update_available(),
+        }
+    }
+
+    fn columns(&self) -> [String; 5] {
+        let status = match &self.latest {
+            None => "unknown",
+            Some(_) if self.update_available() => "update available",
+            Some(_) => "up to date",
+        };
+        [
+            self.compat_tool.name.clone(),
+            self.target.to_string(),
+            self.installed.clone(),
+            self.latest.clone().unwrap_or_else(|| "-".to_owned()),
+            status.to_owned(),
+        ]
+    }
+}
+
+/// Keeps only the newest installed version of each tool in each app, in the order found
+fn newest_per_tool(found: Vec<(AppInstallations, CompatTool, String)>) -> Vec<OutdatedEntry> {
+    let mut entries: Vec<OutdatedEntry> = vec![];
+    for (target, compat_tool, version) in found {
+        let existing = entries
+            .iter_mut()
+            .find(|entry| entry.target == target && entry.compat_tool.name == compat_tool.name);
+        match existing {
+            Some(entry) => {
+                if compare_versions(&version, &entry.installed) == Ordering::Greater {
+                    entry.installed = version;
+                }
+            }
+            None => entries.push(OutdatedEntry {
+                target,
+                compat_tool,
+                installed: version,
+                latest: None,
+            }),
+        }
+    }
+    entries
+}
+
+/// Reports the tools with a newer release than the newest installed version.
+/// Returns true if any update is available
+pub(crate) async fn run_outdated(args: OutdatedArgs) -> Result<bool> {
+    let targets = installed::inventory_targets(args.paths).await;
+
+    let manifest = installed::load_manifest();
+    let mut cache = ReleaseCache::default();
+    let mut found = vec![];
+    for target in &targets {
+        for folder in installed::installed_folders(target).await {
+            if let Some((compat_tool, version)) =
+                installed::identify(target, &folder, &manifest, &mut cache).await
+            {
+                found.push((target.clone(), compat_tool, version));
+            }
+        }
+    }
+
+    let mut entries = newest_per_tool(found);
+    for entry in &mut entries {
+        entry.latest = cache.latest(&entry.compat_tool).await;
+    }
+    let updates_available = entries.iter().any(OutdatedEntry::update_available);
+
+    if output::is_machine() {
+        for entry in &entries {
+            output::emit(entry.record());
+        }
+        return Ok(updates_available);
+    }
+
+    if entries.is_empty() {
+        println!("No installed tools found");
+        return Ok(false);
+    }
+    let rows: Vec<[String; 5]> = entries.iter().map(OutdatedEntry::columns).collect();
+    print!(
+        "{}",
+        format_table(["TOOL", "APP", "INSTALLED", "LATEST", "STATUS"], &rows)
+    );
+    Ok(updates_available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newest_per_tool() {
+        let ge_proton = "GEProton".parse::<CompatTool>().unwrap();
+        let found = vec![
+            (
+                AppInstallations::Steam,
+                ge_proton.clone(),
+                "GE-Proton9-27".to_owned(),
+            ),
+            (
+                AppInstallations::Steam,
+                ge_proton.clone(),
+                "GE-Proton10-1".to_owned(),
+            ),
+            (
+                AppInstallations::Steam,
+                ge_proton.clone(),
+                "GE-Proton10-1-rc1".to_owned(),
+            ),
+            (
+                AppInstallations::Lutris,
+                ge_proton.clone(),
+                "GE-Proton8-26".to_owned(),
+            ),
+        ];
+
+        let mut entries = newest_per_tool(found);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].installed, "GE-Proton10-1");
+        assert_eq!(entries[1].installed, "GE-Proton8-26");
+
+        for entry in &mut entries {
+            entry.latest = Some("GE-Proton10-1".to_owned());
+        }
+        assert!(!entries[0].update_available());
+        assert!(entries[1].update_available());
+    }
+}
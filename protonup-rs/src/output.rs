@@ -0,0 +1,409 @@
+//! Machine-readable output for `--output json` and `--output ndjson`.
+//!
+//! Every record is tagged with its `type` and the [`SCHEMA_VERSION`].
+//! The schema is documented in `docs/docs.md`, and must be bumped on breaking changes.
+
+use std::fmt::Display;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::{self, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::{env, io};
+
+use indicatif::ProgressDrawTarget;
+use libprotonup::apps::AppInstallations;
+use libprotonup::lockfile::LockedInstall;
+use libprotonup::utils;
+use serde::Serialize;
+
+use crate::cancel;
+use crate::cli::OutputFormat;
+use crate::verify::VerifyStatus;
+
+/// Version of the JSON output schema
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+/// Pager used when `$PAGER` is not set
+const FALLBACK_PAGER: &str = "less";
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static NON_INTERACTIVE: OnceLock<bool> = OnceLock::new();
+static RECORDS: Mutex<Vec<Record>> = Mutex::new(Vec::new());
+
+/// An app installation as presented in the output records
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct TargetRecord {
+    /// Stable identifier: steam, steam_flatpak, lutris, lutris_flatpak, custom or target
+    pub kind: &'static str,
+    /// Human readable name
+    pub name: String,
+    /// Base installation folder of the app, with `~` expanded
+    pub path: String,
+}
+
+impl From<&AppInstallations> for TargetRecord {
+    fn from(app_inst: &AppInstallations) -> Self {
+        let kind = match app_inst {
+            AppInstallations::Steam => "steam",
+            AppInstallations::SteamFlatpak => "steam_flatpak",
+            AppInstallations::Lutris => "lutris",
+            AppInstallations::LutrisFlatpak => "lutris_flatpak",
+            AppInstallations::Custom(_) => "custom",
+            AppInstallations::Target(_) => "target",
+        };
+        let path = app_inst.default_install_dir();
+        TargetRecord {
+            kind,
+            name: app_inst.to_string(),
+            path: utils::expand_tilde(path.as_str())
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string()),
+        }
+    }
+}
+
+/// Stage of an installation reported by progress records
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Stage {
+    Started,
+    Download,
+    Verify,
+    Unpack,
+    Retry,
+    Warning,
+    Finished,
+    Failed,
+    Cancelled,
+}
+
+/// Progress of an installation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct ProgressRecord {
+    pub tool: String,
+    pub version: String,
+    pub stage: Stage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<TargetRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// A single output record
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Record {
+    /// Apps found on this system
+    DetectedApps { apps: Vec<TargetRecord> },
+    /// A tool installed into a target
+    Install {
+        tool: String,
+        version: String,
+        target: TargetRecord,
+        install_path: PathBuf,
+    },
+    /// An old version removed to honor the configured retention, or a folder missing from
+    /// the lock file removed by `sync --prune`
+    Removed {
+        tool: String,
+        version: String,
+        target: TargetRecord,
+        path: PathBuf,
+    },
+    /// Release notes for a release.
+    /// `update_available` is only set when the installed versions were checked
+    WhatsNew {
+        tool: String,
+        version: String,
+        published_at: Option<String>,
+        prerelease: bool,
+        url: String,
+        notes: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        update_available: Option<bool>,
+    },
+    /// An available release of a tool, printed by `list`
+    Release {
+        tool: String,
+        version: String,
+        published_at: Option<String>,
+        prerelease: bool,
+        size: u64,
+        variants: Vec<String>,
+        installed_in: Vec<TargetRecord>,
+    },
+    /// A folder installed in an app, printed by `installed`.
+    /// `tool` and `version` are None when the folder doesn't match any known tool
+    Installed {
+        target: TargetRecord,
+        folder: String,
+        path: PathBuf,
+        tool: Option<String>,
+        version: Option<String>,
+        size: Option<u64>,
+        modified: Option<String>,
+        latest: Option<String>,
+        newest: Option<bool>,
+    },
+    /// The newest installed version of a tool in an app, printed by `outdated`
+    Outdated {
+        tool: String,
+        target: TargetRecord,
+        installed: String,
+        latest: Option<String>,
+        update_available: bool,
+    },
+    /// An installation checked by `verify`. The paths are relative to `path`
+    Verified {
+        tool: String,
+        version: String,
+        target: TargetRecord,
+        path: PathBuf,
+        status: VerifyStatus,
+        modified: Vec<PathBuf>,
+        missing: Vec<PathBuf>,
+        extra: Vec<PathBuf>,
+    },
+    /// The releases locked by `lock`, written to `path`
+    Lockfile {
+        path: PathBuf,
+        installs: Vec<LockedInstall>,
+    },
+    /// A setting printed or changed by `config`. `key` is None for the whole configuration,
+    /// and `value` is null for unset settings
+    Config {
+        key: Option<String>,
+        value: serde_json::Value,
+    },
+    /// Progress of an installation, only emitted with `--output ndjson`
+    Progress(ProgressRecord),
+    /// An error that happened while running the command
+    Error { message: String },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    record: &'a Record,
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    schema_version: u32,
+    records: &'a [Record],
+}
+
+/// Sets the output format for the rest of the program, and whether prompts are forbidden
+pub(crate) fn init(format: OutputFormat, non_interactive: bool) {
+    let _ = FORMAT.set(format);
+    let _ = NON_INTERACTIVE.set(non_interactive);
+}
+
+pub(crate) fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+/// True when records are printed instead of human readable text
+pub(crate) fn is_machine() -> bool {
+    format() != OutputFormat::Text
+}
+
+/// True when the user can be prompted: not disabled with `--non-interactive`,
+/// no machine-readable output, and stdin is a terminal
+pub(crate) fn is_interactive() -> bool {
+    !NON_INTERACTIVE.get().copied().unwrap_or_default()
+        && !is_machine()
+        && std::io::stdin().is_terminal()
+}
+
+/// Prints a human readable message.
+/// It goes to stderr when stdout is reserved for the records.
+pub(crate) fn info(message: impl Display) {
+    if is_machine() {
+        eprintln!("{message}");
+    } else {
+        println!("{message}");
+    }
+}
+
+/// Prints long human readable text, like release notes, through `$PAGER` when it is
+/// taller than the terminal. It is printed as is when it fits, when the user can't be
+/// prompted, or when the pager can't be started
+pub(crate) async fn page(text: String) {
+    let term = console::Term::stdout();
+    let fits = term
+        .size_checked()
+        .is_none_or(|(rows, _)| text.lines().count() < rows as usize);
+    if fits || !is_interactive() || !term.is_term() {
+        print!("{text}");
+        return;
+    }
+    // the pager runs until the user quits it, and handles Ctrl+C itself meanwhile
+    let _foreground = cancel::ForegroundGuard::new();
+    let _ = tokio::task::spawn_blocking(move || {
+        if print_paged(&text).is_err() {
+            print!("{text}");
+        }
+    })
+    .await;
+}
+
+fn print_paged(text: &str) -> io::Result<()> {
+    let pager = env::var("PAGER").unwrap_or_else(|_| FALLBACK_PAGER.to_owned());
+    let mut args = pager.split_whitespace();
+    let program = args
+        .next()
+        .ok_or_else(|| io::Error::other("The pager command is empty"))?;
+
+    let mut command = process::Command::new(program);
+    command.args(args).stdin(Stdio::piped());
+    // keep the colors and hyperlinks, and the notes on screen after quitting
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // fails when the pager is quit before reading everything
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    let _ = child.wait();
+    Ok(())
+}
+
+/// Records a result. Ignored in text mode, where the callers print their own messages
+pub(crate) fn emit(record: Record) {
+    match format() {
+        OutputFormat::Text => {}
+        OutputFormat::Json => {
+            // progress is only streamed in ndjson
+            if !matches!(record, Record::Progress(_)) {
+                RECORDS.lock().unwrap().push(record);
+            }
+        }
+        OutputFormat::Ndjson => {
+            let line = Line {
+                schema_version: SCHEMA_VERSION,
+                record: &record,
+            };
+            println!("{}", serde_json::to_string(&line).unwrap());
+        }
+    }
+}
+
+/// Prints the JSON document with every record emitted. Must be called once before exiting
+pub(crate) fn finish() {
+    if format() == OutputFormat::Json {
+        let records = RECORDS.lock().unwrap();
+        let document = Document {
+            schema_version: SCHEMA_VERSION,
+            records: &records,
+        };
+        println!("{}", serde_json::to_string_pretty(&document).unwrap());
+    }
+}
+
+/// Progress bars are hidden when printing records
+pub(crate) fn draw_target() -> ProgressDrawTarget {
+    if is_machine() {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr_with_hz(20)
+    }
+}
+
+/// Formats rows as a table with left aligned columns
+pub(crate) fn format_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) -> String {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    let header = header.map(str::to_owned);
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(column, width)| format!("{column:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_serialization() {
+        let record = Record::Install {
+            tool: "GEProton".to_owned(),
+            version: "GE-Proton9-20".to_owned(),
+            target: TargetRecord::from(&AppInstallations::Steam),
+            install_path: PathBuf::from(
+                "/home/user/.steam/steam/compatibilitytools.d/GE-Proton9-20",
+            ),
+        };
+        let line = Line {
+            schema_version: SCHEMA_VERSION,
+            record: &record,
+        };
+
+        let value = serde_json::to_value(&line).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["type"], "install");
+        assert_eq!(value["version"], "GE-Proton9-20");
+        assert_eq!(value["target"]["kind"], "steam");
+        let path = utils::expand_tilde("~/.steam/steam/compatibilitytools.d/").unwrap();
+        assert!(path.is_absolute());
+        assert_eq!(value["target"]["path"], path.to_string_lossy().as_ref());
+    }
+
+    #[test]
+    fn test_optional_fields_are_skipped() {
+        let record = Record::WhatsNew {
+            tool: "GEProton".to_owned(),
+            version: "GE-Proton9-20".to_owned(),
+            published_at: None,
+            prerelease: false,
+            url: "https://github.com/".to_owned(),
+            notes: None,
+            update_available: None,
+        };
+
+        let value = serde_json::to_value(&record).unwrap();
+        assert_eq!(value["type"], "whats_new");
+        assert!(value["notes"].is_null());
+        assert!(value.get("update_available").is_none());
+    }
+
+    #[test]
+    fn test_format_table() {
+        let rows = vec![[
+            "GE-Proton10-1".to_owned(),
+            "2025-03-04".to_owned(),
+            "-".to_owned(),
+            "-".to_owned(),
+            "Steam \"Native\"".to_owned(),
+        ]];
+        let table = format_table(
+            ["TAG", "PUBLISHED", "SIZE", "VARIANTS", "INSTALLED IN"],
+            &rows,
+        );
+        assert_eq!(
+            table,
+            "TAG            PUBLISHED   SIZE  VARIANTS  INSTALLED IN\n\
+             GE-Proton10-1  2025-03-04  -     -         Steam \"Native\"\n"
+        );
+    }
+}
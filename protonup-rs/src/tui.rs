@@ -0,0 +1,1080 @@
+//! Full-screen terminal interface.
+//!
+//! Shows the detected apps, the versions installed in the selected one and the releases of
+//! its compatibility tools side by side, with the notes of the highlighted release and the
+//! progress of the running installs. Versions are installed and removed without leaving the
+//! interface. Data is loaded, and installs run, in background tasks reporting through a channel.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use futures_util::StreamExt;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, LineGauge, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use tokio::sync::{Semaphore, mpsc};
+
+use libprotonup::apps::{self, AppInstallations};
+use libprotonup::downloads::{self, Release};
+use libprotonup::installer::{Cancelled, InstallEvent, Installer};
+use libprotonup::manifest;
+use libprotonup::sources::{CompatTool, CompatTools};
+use libprotonup::{config, cpu, files, utils};
+
+use crate::download::release_url;
+use crate::markdown::Renderer;
+use crate::{architecture_variants, cancel, installed, output};
+
+/// Most installs shown in the progress pane, the newest ones
+const PROGRESS_ROWS: usize = 6;
+
+/// Lines scrolled by Page Up and Page Down in the release notes
+const NOTES_PAGE: u16 = 10;
+
+/// Result of a background task
+enum Message {
+    Installed(AppInstallations, Result<Vec<InstalledVersion>, String>),
+    Releases(String, Result<Vec<Release>, String>),
+    /// Event of the install with this index in [`Tui::jobs`]
+    Progress(usize, InstallEvent),
+    InstallDone(usize, Result<(), String>),
+    /// Folders removed from an app, and the errors of the ones that couldn't be
+    Removed(AppInstallations, usize, Vec<String>),
+}
+
+/// Data loaded in the background
+enum Loading<T> {
+    Pending,
+    Loaded(T),
+    Failed(String),
+}
+
+/// A folder installed in an app, with the tool and version it was installed from if known
+#[derive(Debug, Clone, PartialEq)]
+struct InstalledVersion {
+    folder: String,
+    path: PathBuf,
+    tool: Option<String>,
+    version: Option<String>,
+}
+
+struct AppEntry {
+    app_inst: AppInstallations,
+    /// None until the installed versions are requested
+    installed: Option<Loading<Vec<InstalledVersion>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum JobState {
+    Running,
+    Finished,
+    Failed(String),
+    Cancelled,
+}
+
+/// An install started from the interface
+struct Job {
+    label: String,
+    stage: String,
+    done: u64,
+    total: u64,
+    state: JobState,
+}
+
+impl Job {
+    fn new(label: String) -> Job {
+        Job {
+            label,
+            stage: "Waiting".to_owned(),
+            done: 0,
+            total: 0,
+            state: JobState::Running,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.state == JobState::Running
+    }
+
+    fn update(&mut self, event: InstallEvent) {
+        let (stage, done, total) = match event {
+            InstallEvent::Started { total, .. } => ("Downloading".to_owned(), 0, total),
+            InstallEvent::Bytes { done, total } => ("Downloading".to_owned(), done, total),
+            InstallEvent::Verifying { done, total } => ("Verifying".to_owned(), done, total),
+            InstallEvent::Unpacking { done, total, .. } => ("Unpacking".to_owned(), done, total),
+            InstallEvent::Retry { attempt, .. } => {
+                (format!("Retrying (attempt {attempt})"), 0, self.total)
+            }
+            InstallEvent::Warning { message } => (message, self.done, self.total),
+            InstallEvent::Finished { .. } => {
+                self.state = JobState::Finished;
+                return;
+            }
+            InstallEvent::Failed { error, .. } => {
+                self.state = JobState::Failed(error);
+                return;
+            }
+            InstallEvent::Cancelled => {
+                self.state = JobState::Cancelled;
+                return;
+            }
+        };
+        (self.stage, self.done, self.total) = (stage, done, total);
+    }
+
+    fn ratio(&self) -> f64 {
+        match self.state {
+            JobState::Finished => 1.0,
+            _ if self.total == 0 => 0.0,
+            _ => (self.done as f64 / self.total as f64).min(1.0),
+        }
+    }
+
+    fn line(&self) -> Line<'static> {
+        match &self.state {
+            JobState::Running => Line::from(format!("{}: {}", self.label, self.stage)),
+            JobState::Finished => Line::from(format!("{}: Installed", self.label)).green(),
+            JobState::Failed(error) => Line::from(format!("{}: {error}", self.label)).red(),
+            JobState::Cancelled => Line::from(format!("{}: Cancelled", self.label)).yellow(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Apps,
+    Installed,
+    Releases,
+}
+
+/// Action waiting for the user to press y
+enum Action {
+    Remove(AppInstallations, Vec<PathBuf>),
+    Quit,
+}
+
+struct Tui {
+    tx: mpsc::UnboundedSender<Message>,
+    apps: Vec<AppEntry>,
+    /// Release lists by tool name
+    releases: HashMap<String, Loading<Vec<Release>>>,
+    /// Tools whose release list was reloaded, fetched again without the cache
+    stale_releases: HashSet<String>,
+    focus: Pane,
+    app_list: ListState,
+    installed_list: ListState,
+    release_list: ListState,
+    /// Tool of the selected app whose releases are shown
+    tool_index: usize,
+    selected_folders: BTreeSet<PathBuf>,
+    selected_tags: BTreeSet<String>,
+    notes_scroll: u16,
+    jobs: Vec<Job>,
+    permits: Arc<Semaphore>,
+    status: Option<String>,
+    confirm: Option<(String, Action)>,
+    quitting: bool,
+}
+
+/// Opens the full-screen interface, until the user quits
+pub(crate) async fn run_tui() -> Result<()> {
+    if !output::is_interactive() {
+        return Err(anyhow!(
+            "The full-screen interface needs a terminal, and is disabled by --non-interactive"
+        ));
+    }
+    let found_apps = apps::list_installed_apps().await;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut tui = Tui::new(found_apps, tx);
+    tui.load_selected();
+
+    let mut terminal = ratatui::try_init()?;
+    let _terminal_guard = cancel::TerminalGuard::new(ratatui::restore);
+    let result = tui.run(&mut terminal, &mut rx).await;
+    ratatui::restore();
+    if cancel::is_cancelled() {
+        cancel::exit_cancelled();
+    }
+    result
+}
+
+impl Tui {
+    fn new(found_apps: Vec<AppInstallations>, tx: mpsc::UnboundedSender<Message>) -> Tui {
+        let mut tui = Tui {
+            tx,
+            apps: found_apps
+                .into_iter()
+                .map(|app_inst| AppEntry {
+                    app_inst,
+                    installed: None,
+                })
+                .collect(),
+            releases: HashMap::new(),
+            stale_releases: HashSet::new(),
+            focus: Pane::Apps,
+            app_list: ListState::default().with_selected(Some(0)),
+            installed_list: ListState::default().with_selected(Some(0)),
+            release_list: ListState::default().with_selected(Some(0)),
+            tool_index: 0,
+            selected_folders: BTreeSet::new(),
+            selected_tags: BTreeSet::new(),
+            notes_scroll: 0,
+            jobs: vec![],
+            permits: Arc::new(Semaphore::new(config::current().concurrency)),
+            status: None,
+            confirm: None,
+            quitting: false,
+        };
+        if tui.apps.is_empty() {
+            tui.status = Some(
+                "No apps found. Please install at least one app before using this feature."
+                    .to_owned(),
+            );
+        }
+        tui.tool_index = tui.default_tool_index();
+        tui
+    }
+
+    async fn run(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        rx: &mut mpsc::UnboundedReceiver<Message>,
+    ) -> Result<()> {
+        let mut events = EventStream::new();
+        let cancel_token = cancel::token();
+        while !self.is_done() {
+            terminal.draw(|frame| self.draw(frame))?;
+            tokio::select! {
+                // SIGTERM, the installs are cancelled too and quit once cleaned up
+                _ = cancel_token.cancelled(), if !self.quitting => self.quitting = true,
+                event = events.next() => match event {
+                    Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => self.on_key(key),
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e.into()),
+                    None => break,
+                },
+                Some(message) = rx.recv() => {
+                    self.on_message(message);
+                    // progress comes in bursts, draw once for all of them
+                    while let Ok(message) = rx.try_recv() {
+                        self.on_message(message);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The user quit, and the installs are finished or cancelled
+    fn is_done(&self) -> bool {
+        self.quitting && !self.jobs.iter().any(Job::is_running)
+    }
+
+    fn selected_app(&self) -> Option<&AppEntry> {
+        self.apps.get(self.app_list.selected()?)
+    }
+
+    /// Tools of the selected app that are built for the target architecture
+    fn tools(&self) -> Vec<CompatTool> {
+        let Some(entry) = self.selected_app() else {
+            return vec![];
+        };
+        let arch = cpu::target_arch();
+        CompatTools
+            .iter()
+            .filter(|tool| entry.app_inst.as_app().is_compatible(tool) && tool.supports_arch(&arch))
+            .cloned()
+            .collect()
+    }
+
+    fn default_tool_index(&self) -> usize {
+        let Some(entry) = self.selected_app() else {
+            return 0;
+        };
+        let default = entry.app_inst.as_app().default_compatibility_tool();
+        self.tools()
+            .iter()
+            .position(|tool| tool.name == default.name)
+            .unwrap_or_default()
+    }
+
+    fn selected_tool(&self) -> Option<CompatTool> {
+        self.tools().get(self.tool_index).cloned()
+    }
+
+    fn installed_versions(&self) -> &[InstalledVersion] {
+        match self
+            .selected_app()
+            .and_then(|entry| entry.installed.as_ref())
+        {
+            Some(Loading::Loaded(versions)) => versions,
+            _ => &[],
+        }
+    }
+
+    /// Releases of the selected tool, without the pre-releases unless they are included
+    fn visible_releases(&self) -> Vec<&Release> {
+        let Some(tool) = self.selected_tool() else {
+            return vec![];
+        };
+        let include_prereleases = config::current().includes_prereleases(&tool);
+        match self.releases.get(&tool.name) {
+            Some(Loading::Loaded(releases)) => releases
+                .iter()
+                .filter(|release| include_prereleases || !release.prerelease)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn highlighted_release(&self) -> Option<&Release> {
+        self.visible_releases()
+            .get(self.release_list.selected()?)
+            .copied()
+    }
+
+    fn is_installed(&self, compat_tool: &CompatTool, release: &Release) -> bool {
+        let folder = compat_tool.installation_name(&release.tag_name);
+        self.installed_versions().iter().any(|version| {
+            version.folder == folder
+                || (version.tool.as_ref() == Some(&compat_tool.name)
+                    && version.version.as_ref() == Some(&release.tag_name))
+        })
+    }
+
+    /// Loads the installed versions of the selected app and the releases of the selected tool,
+    /// if not loaded yet
+    fn load_selected(&mut self) {
+        let Some(index) = self.app_list.selected().filter(|i| *i < self.apps.len()) else {
+            return;
+        };
+        if self.apps[index].installed.is_none() {
+            self.apps[index].installed = Some(Loading::Pending);
+            let app_inst = self.apps[index].app_inst.clone();
+            let tx = self.tx.clone();
+            tokio::spawn(async move {
+                let result = load_installed(&app_inst)
+                    .await
+                    .map_err(|e| format!("{e:#}"));
+                let _ = tx.send(Message::Installed(app_inst, result));
+            });
+        }
+        if let Some(tool) = self.selected_tool()
+            && !self.releases.contains_key(&tool.name)
+        {
+            self.releases.insert(tool.name.clone(), Loading::Pending);
+            let refresh = self.stale_releases.remove(&tool.name);
+            let tx = self.tx.clone();
+            tokio::spawn(async move {
+                let result = match refresh {
+                    true => downloads::refresh_releases(&tool).await,
+                    false => downloads::list_releases(&tool).await,
+                };
+                let result =
+                    result.map_err(|e| format!("Failed to fetch releases for {}: {e}", tool.name));
+                let _ = tx.send(Message::Releases(tool.name, result));
+            });
+        }
+    }
+
+    fn reload(&mut self, app_inst: &AppInstallations) {
+        if let Some(entry) = self.apps.iter_mut().find(|e| e.app_inst == *app_inst) {
+            entry.installed = None;
+        }
+        self.load_selected();
+    }
+
+    fn on_message(&mut self, message: Message) {
+        match message {
+            Message::Installed(app_inst, result) => {
+                if let Some(entry) = self.apps.iter_mut().find(|e| e.app_inst == app_inst) {
+                    entry.installed = Some(match result {
+                        Ok(versions) => Loading::Loaded(versions),
+                        Err(e) => Loading::Failed(e),
+                    });
+                }
+            }
+            Message::Releases(tool, result) => {
+                let releases = match result {
+                    Ok(releases) => Loading::Loaded(releases),
+                    Err(e) => Loading::Failed(e),
+                };
+                self.releases.insert(tool, releases);
+            }
+            Message::Progress(id, event) => self.jobs[id].update(event),
+            Message::InstallDone(id, result) => {
+                let job = &mut self.jobs[id];
+                job.state = match result {
+                    Ok(()) => JobState::Finished,
+                    Err(_) if cancel::is_cancelled() => JobState::Cancelled,
+                    Err(e) => JobState::Failed(e),
+                };
+                let app_inst = self.selected_app().map(|entry| entry.app_inst.clone());
+                // the other apps are reloaded when selected
+                for entry in &mut self.apps {
+                    if Some(&entry.app_inst) != app_inst.as_ref() {
+                        entry.installed = None;
+                    }
+                }
+                if let Some(app_inst) = app_inst {
+                    self.reload(&app_inst);
+                }
+            }
+            Message::Removed(app_inst, removed, errors) => {
+                self.status = Some(match errors.first() {
+                    None => format!("Removed {removed} folder(s) from {app_inst}"),
+                    Some(error) => format!("Removed {removed} folder(s) from {app_inst}. {error}"),
+                });
+                self.reload(&app_inst);
+            }
+        }
+    }
+
+    fn on_key(&mut self, key: KeyEvent) {
+        if let Some((_, action)) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                self.run_action(action);
+            } else {
+                self.status = Some("Cancelled".to_owned());
+            }
+            return;
+        }
+        self.status = None;
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit(),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit(),
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => self.focus_next(true),
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => self.focus_next(false),
+            KeyCode::Down | KeyCode::Char('j') => self.step(true),
+            KeyCode::Up | KeyCode::Char('k') => self.step(false),
+            KeyCode::PageDown => self.notes_scroll = self.notes_scroll.saturating_add(NOTES_PAGE),
+            KeyCode::PageUp => self.notes_scroll = self.notes_scroll.saturating_sub(NOTES_PAGE),
+            KeyCode::Char(' ') => self.toggle(),
+            KeyCode::Char('t') => self.next_tool(),
+            KeyCode::Char('r') => {
+                self.stale_releases
+                    .extend(self.releases.drain().map(|(tool, _)| tool));
+                if let Some(app_inst) = self.selected_app().map(|entry| entry.app_inst.clone()) {
+                    self.reload(&app_inst);
+                }
+            }
+            KeyCode::Enter if self.focus == Pane::Apps => self.focus = Pane::Releases,
+            KeyCode::Enter | KeyCode::Char('i') if self.focus == Pane::Releases => {
+                self.install();
+            }
+            KeyCode::Char('d') | KeyCode::Delete if self.focus == Pane::Installed => {
+                self.ask_remove();
+            }
+            _ => {}
+        }
+    }
+
+    fn focus_next(&mut self, forward: bool) {
+        self.focus = match (self.focus, forward) {
+            (Pane::Apps, true) | (Pane::Releases, false) => Pane::Installed,
+            (Pane::Installed, true) | (Pane::Apps, false) => Pane::Releases,
+            (Pane::Releases, true) | (Pane::Installed, false) => Pane::Apps,
+        };
+    }
+
+    /// Moves the highlight of the focused pane
+    fn step(&mut self, down: bool) {
+        let len = match self.focus {
+            Pane::Apps => self.apps.len(),
+            Pane::Installed => self.installed_versions().len(),
+            Pane::Releases => self.visible_releases().len(),
+        };
+        let state = match self.focus {
+            Pane::Apps => &mut self.app_list,
+            Pane::Installed => &mut self.installed_list,
+            Pane::Releases => &mut self.release_list,
+        };
+        let selected = state.selected().unwrap_or_default();
+        let next = match down {
+            true => (selected + 1).min(len.saturating_sub(1)),
+            false => selected.saturating_sub(1),
+        };
+        state.select(Some(next));
+
+        match self.focus {
+            Pane::Apps if next != selected => {
+                self.installed_list.select(Some(0));
+                self.selected_folders.clear();
+                self.tool_index = self.default_tool_index();
+                self.tool_changed();
+            }
+            Pane::Releases if next != selected => self.notes_scroll = 0,
+            _ => {}
+        }
+    }
+
+    fn next_tool(&mut self) {
+        let count = self.tools().len();
+        if count > 1 {
+            self.tool_index = (self.tool_index + 1) % count;
+            self.tool_changed();
+        }
+    }
+
+    fn tool_changed(&mut self) {
+        self.release_list.select(Some(0));
+        self.selected_tags.clear();
+        self.notes_scroll = 0;
+        self.load_selected();
+    }
+
+    /// Selects or unselects the highlighted version
+    fn toggle(&mut self) {
+        match self.focus {
+            Pane::Apps => {}
+            Pane::Installed => {
+                let Some(version) = self
+                    .installed_list
+                    .selected()
+                    .and_then(|i| self.installed_versions().get(i))
+                else {
+                    return;
+                };
+                let path = version.path.clone();
+                if !self.selected_folders.remove(&path) {
+                    self.selected_folders.insert(path);
+                }
+            }
+            Pane::Releases => {
+                let Some(tag) = self.highlighted_release().map(|r| r.tag_name.clone()) else {
+                    return;
+                };
+                if !self.selected_tags.remove(&tag) {
+                    self.selected_tags.insert(tag);
+                }
+            }
+        }
+        self.step(true);
+    }
+
+    fn quit(&mut self) {
+        if self.jobs.iter().any(Job::is_running) {
+            self.confirm = Some((
+                "Installs are running. Cancel them and quit? (y/n)".to_owned(),
+                Action::Quit,
+            ));
+        } else {
+            self.quitting = true;
+        }
+    }
+
+    fn ask_remove(&mut self) {
+        let Some(app_inst) = self.selected_app().map(|entry| entry.app_inst.clone()) else {
+            return;
+        };
+        let versions = self.installed_versions();
+        let mut folders: Vec<&InstalledVersion> = versions
+            .iter()
+            .filter(|version| self.selected_folders.contains(&version.path))
+            .collect();
+        if folders.is_empty()
+            && let Some(version) = self.installed_list.selected().and_then(|i| versions.get(i))
+        {
+            folders.push(version);
+        }
+        if folders.is_empty() {
+            return;
+        }
+        let names: Vec<&str> = folders.iter().map(|v| v.folder.as_str()).collect();
+        let paths = folders.iter().map(|v| v.path.clone()).collect();
+        self.confirm = Some((
+            format!("Delete {} from {app_inst}? (y/n)", names.join(", ")),
+            Action::Remove(app_inst, paths),
+        ));
+    }
+
+    fn run_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => {
+                cancel::token().cancel();
+                self.status = Some("Cancelling the running installs...".to_owned());
+                self.quitting = true;
+            }
+            Action::Remove(app_inst, paths) => {
+                self.selected_folders.clear();
+                let tx = self.tx.clone();
+                tokio::spawn(async move {
+                    let (removed, errors) = remove_folders(&paths).await;
+                    let _ = tx.send(Message::Removed(app_inst, removed, errors));
+                });
+            }
+        }
+    }
+
+    /// Installs the selected releases of the selected tool, or the highlighted one,
+    /// into the selected app
+    fn install(&mut self) {
+        let (Some(entry), Some(compat_tool)) = (self.selected_app(), self.selected_tool()) else {
+            return;
+        };
+        let app_inst = entry.app_inst.clone();
+        let mut releases: Vec<Release> = self
+            .visible_releases()
+            .into_iter()
+            .filter(|release| self.selected_tags.contains(&release.tag_name))
+            .cloned()
+            .collect();
+        if releases.is_empty() {
+            releases.extend(self.highlighted_release().cloned());
+        }
+
+        let mut skipped = vec![];
+        // variant choices and errors, shown together once every install is started
+        let mut notes: Vec<String> = vec![];
+        for release in releases {
+            if self.is_installed(&compat_tool, &release) {
+                skipped.push(release.tag_name);
+                continue;
+            }
+            let download = if compat_tool.has_variants() {
+                let variants = release.get_all_download_variants(&app_inst, &compat_tool);
+                let selected = architecture_variants::select_unprompted(
+                    &compat_tool,
+                    &release.tag_name,
+                    variants,
+                );
+                let (download, note) = match selected {
+                    Ok((download, note)) => (Some(download), note),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                if let Some(note) = note.filter(|note| !notes.contains(note)) {
+                    notes.push(note);
+                }
+                match download {
+                    Some(download) => download,
+                    None => continue,
+                }
+            } else {
+                release.get_download_info(&app_inst, &compat_tool)
+            };
+
+            let id = self.jobs.len();
+            self.jobs.push(Job::new(format!(
+                "{} {} for {app_inst}",
+                compat_tool.name, release.tag_name
+            )));
+            let installer = Installer::new(compat_tool.clone(), download, vec![app_inst.clone()]);
+            let (tx, permits) = (self.tx.clone(), self.permits.clone());
+            tokio::spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let _in_flight = cancel::InFlightGuard::new();
+                let events = tx.clone();
+                let result = installer
+                    .with_cancellation(cancel::token())
+                    .on_event(move |event| {
+                        let _ = events.send(Message::Progress(id, event));
+                    })
+                    .run()
+                    .await;
+                let result = match result {
+                    Ok(_) => Ok(()),
+                    Err(e) if e.is::<Cancelled>() => Err(e.to_string()),
+                    Err(e) => Err(format!("{e:#}")),
+                };
+                let _ = tx.send(Message::InstallDone(id, result));
+            });
+        }
+        self.selected_tags.clear();
+        if !skipped.is_empty() {
+            notes.push(format!(
+                "Already installed in {app_inst}: {}",
+                skipped.join(", ")
+            ));
+        }
+        if !notes.is_empty() {
+            self.status = Some(notes.join(". "));
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let progress_height = match self.jobs.len() {
+            0 => 0,
+            jobs => jobs.min(PROGRESS_ROWS) as u16 + 2,
+        };
+        let [panes, notes, progress, status] = Layout::vertical([
+            Constraint::Min(8),
+            Constraint::Percentage(40),
+            Constraint::Length(progress_height),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+        let [apps, installed, releases] = Layout::horizontal([
+            Constraint::Percentage(22),
+            Constraint::Percentage(36),
+            Constraint::Percentage(42),
+        ])
+        .areas(panes);
+
+        self.draw_apps(frame, apps);
+        self.draw_installed(frame, installed);
+        self.draw_releases(frame, releases);
+        self.draw_notes(frame, notes);
+        self.draw_progress(frame, progress);
+        self.draw_status(frame, status);
+    }
+
+    fn block(&self, title: String, pane: Pane) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if self.focus == pane {
+            block.border_style(Style::new().fg(Color::Cyan))
+        } else {
+            block
+        }
+    }
+
+    fn list(&self, items: Vec<ListItem<'static>>, title: String, pane: Pane) -> List<'static> {
+        let highlight = match self.focus == pane {
+            true => Style::new().add_modifier(Modifier::REVERSED),
+            false => Style::new().add_modifier(Modifier::BOLD),
+        };
+        List::new(items)
+            .block(self.block(title, pane))
+            .highlight_style(highlight)
+            .highlight_symbol("> ")
+    }
+
+    fn draw_apps(&mut self, frame: &mut Frame, area: Rect) {
+        let items = self
+            .apps
+            .iter()
+            .map(|entry| ListItem::new(entry.app_inst.to_string()))
+            .collect();
+        let list = self.list(items, " Apps ".to_owned(), Pane::Apps);
+        frame.render_stateful_widget(list, area, &mut self.app_list);
+    }
+
+    fn draw_installed(&mut self, frame: &mut Frame, area: Rect) {
+        let title = " Installed ".to_owned();
+        let message = match self
+            .selected_app()
+            .and_then(|entry| entry.installed.as_ref())
+        {
+            None | Some(Loading::Pending) => Some("Loading...".to_owned()),
+            Some(Loading::Failed(e)) => Some(e.clone()),
+            Some(Loading::Loaded(versions)) if versions.is_empty() => {
+                Some("No tools installed".to_owned())
+            }
+            Some(Loading::Loaded(_)) => None,
+        };
+        if let Some(message) = message {
+            let block = self.block(title, Pane::Installed);
+            frame.render_widget(Paragraph::new(message).block(block), area);
+            return;
+        }
+
+        let items = self
+            .installed_versions()
+            .iter()
+            .map(|version| {
+                let mut spans = vec![
+                    Span::raw(checkbox(self.selected_folders.contains(&version.path))),
+                    Span::raw(version.folder.clone()),
+                ];
+                if let Some(tool) = &version.tool {
+                    spans.push(format!("  {tool}").dim());
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = self.list(items, title, Pane::Installed);
+        frame.render_stateful_widget(list, area, &mut self.installed_list);
+    }
+
+    fn draw_releases(&mut self, frame: &mut Frame, area: Rect) {
+        let Some(tool) = self.selected_tool() else {
+            let block = self.block(" Releases ".to_owned(), Pane::Releases);
+            frame.render_widget(Paragraph::new("No compatible tools").block(block), area);
+            return;
+        };
+        let title = match self.tools().len() {
+            1 => format!(" {} releases ", tool.name),
+            _ => format!(" {} releases (t: next tool) ", tool.name),
+        };
+        let message = match self.releases.get(&tool.name) {
+            None | Some(Loading::Pending) => Some("Loading...".to_owned()),
+            Some(Loading::Failed(e)) => Some(e.clone()),
+            Some(Loading::Loaded(_)) => None,
+        };
+        if let Some(message) = message {
+            let block = self.block(title, Pane::Releases);
+            frame.render_widget(Paragraph::new(message).block(block), area);
+            return;
+        }
+
+        let items = self
+            .visible_releases()
+            .into_iter()
+            .map(|release| {
+                let mut spans = vec![
+                    Span::raw(checkbox(self.selected_tags.contains(&release.tag_name))),
+                    Span::raw(release.to_string()),
+                ];
+                if self.is_installed(&tool, release) {
+                    spans.push("  installed".green());
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = self.list(items, title, Pane::Releases);
+        frame.render_stateful_widget(list, area, &mut self.release_list);
+    }
+
+    fn draw_notes(&mut self, frame: &mut Frame, area: Rect) {
+        let (Some(tool), Some(release)) = (self.selected_tool(), self.highlighted_release()) else {
+            frame.render_widget(Block::bordered().title(" Release notes "), area);
+            return;
+        };
+        let renderer = Renderer {
+            styled: false,
+            width: Some(area.width.saturating_sub(2) as usize),
+        };
+        let notes = renderer.render(release.body.as_deref().unwrap_or_default());
+        let mut text = Text::from(release_url(release, &tool).dim());
+        text.push_line("");
+        if notes.is_empty() {
+            text.push_line("(no release notes)");
+        }
+        text.extend(Text::from(notes));
+
+        let block = Block::bordered().title(format!(" Release notes: {} ", release.tag_name));
+        let paragraph = Paragraph::new(text)
+            .block(block)
+            .scroll((self.notes_scroll, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    fn draw_progress(&self, frame: &mut Frame, area: Rect) {
+        if self.jobs.is_empty() {
+            return;
+        }
+        let block = Block::bordered().title(" Installs ");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let jobs = &self.jobs[self.jobs.len().saturating_sub(PROGRESS_ROWS)..];
+        let rows = Layout::vertical(vec![Constraint::Length(1); jobs.len()]).split(inner);
+        for (job, row) in jobs.iter().zip(rows.iter()) {
+            let gauge = LineGauge::default()
+                .filled_style(Style::new().fg(Color::Cyan))
+                .label(job.line())
+                .ratio(job.ratio());
+            frame.render_widget(gauge, *row);
+        }
+    }
+
+    fn draw_status(&self, frame: &mut Frame, area: Rect) {
+        let first = match (&self.confirm, &self.status) {
+            (Some((question, _)), _) => Line::from(question.clone()).yellow(),
+            (None, Some(status)) => Line::from(status.clone()),
+            (None, None) => Line::default(),
+        };
+        let keys = match self.focus {
+            Pane::Apps => "Enter: releases",
+            Pane::Installed => "Space: select  d: delete",
+            Pane::Releases => "Space: select  Enter: install  t: next tool",
+        };
+        let help = Line::from(format!(
+            "Tab: switch pane  ↑↓: move  {keys}  PgUp/PgDn: scroll notes  r: reload  q: quit"
+        ))
+        .dim();
+        frame.render_widget(Paragraph::new(vec![first, help]), area);
+    }
+}
+
+fn checkbox(selected: bool) -> &'static str {
+    if selected { "[x] " } else { "[ ] " }
+}
+
+/// Folders of `app_inst`, newest first, with the tool and version recorded in the install
+/// manifest or read from the folder name
+async fn load_installed(app_inst: &AppInstallations) -> Result<Vec<InstalledVersion>> {
+    let manifest = installed::load_manifest();
+    let mut versions: Vec<InstalledVersion> = app_inst
+        .list_installed_versions()
+        .await?
+        .into_iter()
+        .map(|folder| {
+            let path = installed::folder_path(&folder);
+            let identified = manifest
+                .find(&path)
+                .map(|record| (record.tool.clone(), record.version.clone()))
+                .or_else(|| {
+                    installed::candidate_tools(app_inst, &folder.0.0)
+                        .into_iter()
+                        .find_map(|tool| {
+                            let version = tool.version_from_installation_name(&folder.0.1)?;
+                            Some((tool.name, version))
+                        })
+                });
+            let files::Folder((_, name)) = folder;
+            let (tool, version) = identified.unzip();
+            InstalledVersion {
+                folder: name,
+                path,
+                tool,
+                version,
+            }
+        })
+        .collect();
+    versions.sort_by(|a, b| utils::compare_versions(&b.folder, &a.folder));
+    Ok(versions)
+}
+
+/// Removes the folders and their manifest records. Returns how many were removed, and the
+/// errors of the others
+async fn remove_folders(paths: &[PathBuf]) -> (usize, Vec<String>) {
+    let mut removed = 0;
+    let mut errors = vec![];
+    for path in paths {
+        match files::remove_dir_all(path).await {
+            Ok(()) => {
+                removed += 1;
+                if let Err(e) = manifest::record_removal(path).await {
+                    errors.push(format!("{e:#}"));
+                }
+            }
+            Err(e) => errors.push(format!("Error deleting {}: {e}", path.display())),
+        }
+    }
+    (removed, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use serde_json::json;
+
+    fn release(tag: &str, body: &str) -> Release {
+        serde_json::from_value(json!({
+            "tag_name": tag,
+            "name": tag,
+            "url": null,
+            "assets": [],
+            "body": body,
+            "published_at": "2025-03-04T12:00:00Z"
+        }))
+        .unwrap()
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// An interface showing Steam, with one installed version and two releases of GEProton
+    fn loaded_tui() -> Tui {
+        let (tx, _) = mpsc::unbounded_channel();
+        let mut tui = Tui::new(vec![AppInstallations::Steam], tx);
+        tui.apps[0].installed = Some(Loading::Loaded(vec![InstalledVersion {
+            folder: "GE-Proton9-20".to_owned(),
+            path: PathBuf::from("/steam/compatibilitytools.d/GE-Proton9-20"),
+            tool: Some("GEProton".to_owned()),
+            version: Some("GE-Proton9-20".to_owned()),
+        }]));
+        tui.releases.insert(
+            "GEProton".to_owned(),
+            Loading::Loaded(vec![
+                release("GE-Proton9-22", "## Fixes\n\n- Fixed **A**"),
+                release("GE-Proton9-20", ""),
+            ]),
+        );
+        tui
+    }
+
+    fn screen(tui: &mut Tui) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| tui.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut tui = loaded_tui();
+        let screen = screen(&mut tui);
+        assert!(screen.contains("Steam \"Native\""));
+        assert!(screen.contains("GEProton releases"));
+        assert!(screen.contains("[ ] GE-Proton9-20"));
+        assert!(screen.contains("GE-Proton9-20 (2025-03-04)  installed"));
+        assert!(!screen.contains("GE-Proton9-22 (2025-03-04)  installed"));
+        // notes of the highlighted release, rendered from markdown
+        assert!(screen.contains("Release notes: GE-Proton9-22"));
+        assert!(screen.contains("- Fixed A"));
+    }
+
+    #[tokio::test]
+    async fn test_keys() {
+        let mut tui = loaded_tui();
+        assert_eq!(tui.focus, Pane::Apps);
+        tui.on_key(key(KeyCode::Tab));
+        tui.on_key(key(KeyCode::Tab));
+        assert_eq!(tui.focus, Pane::Releases);
+
+        // selecting moves to the next release
+        tui.on_key(key(KeyCode::Char(' ')));
+        assert!(tui.selected_tags.contains("GE-Proton9-22"));
+        assert_eq!(
+            tui.highlighted_release().map(|r| r.tag_name.as_str()),
+            Some("GE-Proton9-20")
+        );
+        tui.on_key(key(KeyCode::Down));
+        assert_eq!(tui.release_list.selected(), Some(1));
+
+        // deleting asks first
+        tui.on_key(key(KeyCode::BackTab));
+        tui.on_key(key(KeyCode::Char('d')));
+        assert!(matches!(tui.confirm, Some((_, Action::Remove(..)))));
+        tui.on_key(key(KeyCode::Char('n')));
+        assert!(tui.confirm.is_none());
+        assert_eq!(tui.status.as_deref(), Some("Cancelled"));
+
+        tui.on_key(key(KeyCode::Char('q')));
+        assert!(tui.quitting);
+    }
+
+    #[test]
+    fn test_job_progress() {
+        let mut job = Job::new("GEProton GE-Proton9-22 for Steam".to_owned());
+        job.update(InstallEvent::Bytes {
+            done: 25,
+            total: 100,
+        });
+        assert_eq!(job.stage, "Downloading");
+        assert_eq!(job.ratio(), 0.25);
+        job.update(InstallEvent::Unpacking {
+            target: AppInstallations::Steam,
+            install_dir: PathBuf::from("/steam"),
+            entries: 3,
+            done: 50,
+            total: 50,
+        });
+        assert_eq!(job.stage, "Unpacking");
+        assert!(job.is_running());
+        job.update(InstallEvent::Finished {
+            target: AppInstallations::Steam,
+            install_path: PathBuf::from("/steam/GE-Proton9-22"),
+        });
+        assert_eq!(job.state, JobState::Finished);
+        assert_eq!(job.ratio(), 1.0);
+    }
+}
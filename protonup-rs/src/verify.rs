@@ -0,0 +1,209 @@
+//! `verify` subcommand, checks installed folders against the files recorded at install time.
+
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow};
+use indicatif::MultiProgress;
+use libprotonup::apps::App;
+use libprotonup::downloads::Download;
+use libprotonup::files::{self, TreeReport};
+use libprotonup::installer::Installer;
+use libprotonup::manifest::{self, InstallRecord};
+use libprotonup::sources::CompatTool;
+use libprotonup::utils;
+use serde::Serialize;
+
+use crate::cli::VerifyArgs;
+use crate::output::{self, Record, TargetRecord};
+use crate::{download, installed};
+
+/// Result of verifying an installed folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum VerifyStatus {
+    /// Every file matches
+    Ok,
+    /// Files were modified, deleted or added
+    Damaged,
+    /// The folder was damaged and has been reinstalled
+    Repaired,
+    /// No file list was recorded for the folder, it was installed by an older version
+    Unverifiable,
+}
+
+/// Whether `record` was installed in the app or folder given with `--for`
+pub(crate) fn matches_target(record: &InstallRecord, app: &App) -> bool {
+    match app {
+        App::Custom(path) => {
+            let path = utils::expand_tilde(path).unwrap_or_else(|| PathBuf::from(path));
+            record.path.starts_with(path)
+        }
+        _ => app.app_installations().contains(&record.target),
+    }
+}
+
+fn print_paths(label: &str, paths: &[PathBuf]) {
+    for path in paths {
+        println!("    {label} {}", path.display());
+    }
+}
+
+/// Reinstalls the release `record` was created from, rejecting an archive with another checksum
+async fn repair(record: &InstallRecord) -> Result<()> {
+    let compat_tool: CompatTool = record
+        .tool
+        .parse()
+        .map_err(|_| anyhow!("Unknown compatibility tool {}", record.tool))?;
+    let download = Download {
+        file_name: record.asset.clone(),
+        for_app: record.target.clone(),
+        version: record.version.clone(),
+        hash_sum: None,
+        download_url: record.asset_url.clone(),
+        size: 0,
+    };
+    let mut installer = Installer::new(compat_tool.clone(), download, vec![record.target.clone()]);
+    if let Some(checksum) = &record.checksum {
+        installer = installer.with_expected_checksum(checksum.clone());
+    }
+    let multi_progress = MultiProgress::with_draw_target(output::draw_target());
+    download::run_with_progress(&compat_tool, installer, multi_progress).await
+}
+
+/// Compares the files of `record` with its file list. None if no list was recorded
+pub(crate) async fn check(record: &InstallRecord) -> Result<Option<TreeReport>> {
+    let Some(file_list) = &record.file_list else {
+        return Ok(None);
+    };
+    let files = manifest::load_file_list(file_list)?;
+    Ok(Some(files::verify_tree(&record.path, &files).await?))
+}
+
+fn report(record: &InstallRecord, status: VerifyStatus, tree: &TreeReport) {
+    if output::is_machine() {
+        output::emit(Record::Verified {
+            tool: record.tool.clone(),
+            version: record.version.clone(),
+            target: TargetRecord::from(&record.target),
+            path: record.path.clone(),
+            status,
+            modified: tree.modified.clone(),
+            missing: tree.missing.clone(),
+            extra: tree.extra.clone(),
+        });
+        return;
+    }
+    let location = record.path.display();
+    match status {
+        VerifyStatus::Ok => println!("{} {}: OK ({location})", record.tool, record.version),
+        VerifyStatus::Unverifiable => println!(
+            "{} {}: no file list recorded, reinstall it to enable verification ({location})",
+            record.tool, record.version
+        ),
+        VerifyStatus::Damaged | VerifyStatus::Repaired => {
+            let state = if status == VerifyStatus::Repaired {
+                "repaired"
+            } else {
+                "damaged"
+            };
+            println!("{} {}: {state} ({location})", record.tool, record.version);
+            print_paths("modified:", &tree.modified);
+            print_paths("missing: ", &tree.missing);
+            print_paths("extra:   ", &tree.extra);
+        }
+    }
+}
+
+/// Verifies the installed folders recorded in the manifest, and reinstalls the damaged ones
+/// with `--repair`. Fails if a damaged folder remains
+pub(crate) async fn run_verify(args: VerifyArgs) -> Result<()> {
+    let app = args.target.as_deref().map(App::from_str_or_path);
+    let manifest = installed::load_manifest();
+    let records: Vec<&InstallRecord> = manifest
+        .installs
+        .iter()
+        .filter(|record| app.as_ref().is_none_or(|app| matches_target(record, app)))
+        .filter(|record| {
+            args.version
+                .as_deref()
+                .is_none_or(|version| utils::match_version(version, &record.version))
+        })
+        .collect();
+
+    if records.is_empty() {
+        output::info("No installation recorded by protonup-rs matches");
+        return Ok(());
+    }
+
+    let mut damaged = 0;
+    for record in records {
+        let Some(tree) = check(record).await? else {
+            report(record, VerifyStatus::Unverifiable, &TreeReport::default());
+            continue;
+        };
+        if tree.is_intact() {
+            report(record, VerifyStatus::Ok, &tree);
+            continue;
+        }
+        if !args.repair {
+            report(record, VerifyStatus::Damaged, &tree);
+            damaged += 1;
+            continue;
+        }
+        match repair(record).await {
+            Ok(()) => report(record, VerifyStatus::Repaired, &tree),
+            Err(e) => {
+                output::info(format!(
+                    "Failed to repair {} {}: {e:#}",
+                    record.tool, record.version
+                ));
+                report(record, VerifyStatus::Damaged, &tree);
+                damaged += 1;
+            }
+        }
+    }
+
+    match damaged {
+        0 => Ok(()),
+        1 => Err(anyhow!("1 installation is damaged")),
+        n => Err(anyhow!("{n} installations are damaged")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libprotonup::apps::AppInstallations;
+    use libprotonup::test_util;
+
+    fn record(target: AppInstallations, path: &str) -> InstallRecord {
+        test_util::install_record(target, "GE-Proton10-1", PathBuf::from(path))
+    }
+
+    #[test]
+    fn test_matches_target() {
+        let flatpak = record(
+            AppInstallations::SteamFlatpak,
+            "/home/user/.var/app/com.valvesoftware.Steam/data/Steam/compatibilitytools.d/GE-Proton10-1",
+        );
+        assert!(matches_target(&flatpak, &App::Steam));
+        assert!(!matches_target(&flatpak, &App::Lutris));
+
+        let custom = record(
+            AppInstallations::new_custom_app_install("/opt/tools".to_owned()),
+            "/opt/tools/GE-Proton10-1",
+        );
+        assert!(matches_target(
+            &custom,
+            &App::Custom("/opt/tools".to_owned())
+        ));
+        assert!(matches_target(
+            &custom,
+            &App::Custom("/opt/tools/".to_owned())
+        ));
+        assert!(!matches_target(
+            &custom,
+            &App::Custom("/opt/other".to_owned())
+        ));
+    }
+}